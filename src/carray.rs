@@ -0,0 +1,48 @@
+// The tables as C arrays for projects which are written in C, e.g. with pvsneslib.
+// The source file has got the arrays and the header file declares them along with their length.
+// SNES tables are bytes, the colours of other consoles are written as a whole value each.
+use crate::target::Target;
+
+// Writes the arrays themselves, sixteen values per line.
+pub fn write_source(tables: &[(String, &[u8])], header_name: &str, target: Target) -> String {
+    let mut output = format!("#include \"{}\"\n", header_name);
+    let digits = if target == Target::Snes {2} else {4};
+
+    for (label, data) in tables {
+        output.push_str(&format!("\nconst {} {}[{}] = {{\n", target.c_type(), label, length_define(label)));
+        for line in target.values(data).chunks(16) {
+            let values: Vec<String> = line.iter().map(|value| format!("0x{:0width$X}", value, width = digits)).collect();
+            output.push_str(&format!("    {},\n", values.join(", ")));
+        }
+        output.push_str("};\n");
+    }
+
+    return output;
+}
+
+// Declares every array, the length is a define so it can be used in constant expressions.
+pub fn write_header(tables: &[(String, &[u8])], header_name: &str, target: Target) -> String {
+    let guard = header_guard(header_name);
+    let mut output = format!("#ifndef {0}\n#define {0}\n\n", guard);
+
+    for (label, data) in tables {
+        output.push_str(&format!("#define {} {}\n", length_define(label), target.values(data).len()));
+    }
+    output.push('\n');
+    for (label, _) in tables {
+        output.push_str(&format!("extern const {} {}[{}];\n", target.c_type(), label, length_define(label)));
+    }
+
+    output.push_str(&format!("\n#endif // {}\n", guard));
+    return output;
+}
+
+// E.g. RED_TABLE_LENGTH for red_table.
+fn length_define(label: &str) -> String {
+    return format!("{}_LENGTH", label.to_uppercase());
+}
+
+// Everything which isn't allowed in a macro name is replaced with an underscore, e.g. GRADIENT_H for gradient.h.
+fn header_guard(header_name: &str) -> String {
+    return header_name.chars().map(|character| if character.is_ascii_alphanumeric() {character.to_ascii_uppercase()} else {'_'}).collect();
+}
@@ -0,0 +1,577 @@
+// Small ASM snippets which go along with the ripped tables.
+use crate::hdma::{NumberFormat, Syntax};
+
+// The frame counter only has got eight bits so every animation is unrolled into that many frames.
+const FRAME_INDEX_SIZE: usize = 256;
+
+// The first HDMA channel which is used by the driver.
+// Channels 0 to 2 are usually taken by the game itself.
+const FIRST_CHANNEL: usize = 3;
+
+// Converts how long a frame is shown (in milliseconds) into frames at 60 Hz.
+// Still images and frames without a delay are shown for a single frame.
+fn frame_length(delay: u32) -> usize {
+    return ((delay as usize * 60 + 500) / 1000).max(1);
+}
+
+// Which image frame is shown for each value of the frame counter.
+// If the animation doesn't fit exactly into the frame counter, the last loop is cut short.
+fn frame_index(delays: &[u32]) -> Vec<usize> {
+    let mut index = Vec::with_capacity(FRAME_INDEX_SIZE);
+
+    while !delays.is_empty() && index.len() < FRAME_INDEX_SIZE {
+        for (frame, delay) in delays.iter().enumerate() {
+            index.extend(std::iter::repeat(frame).take(frame_length(*delay)));
+        }
+    }
+
+    index.truncate(FRAME_INDEX_SIZE);
+    return index;
+}
+
+// Whether the animation runs longer than the frame counter can count.
+pub fn animation_too_long(delays: &[u32]) -> bool {
+    return delays.iter().map(|delay| frame_length(*delay)).sum::<usize>() > FRAME_INDEX_SIZE;
+}
+
+// Writes a pointer table for each table of a frame and a routine which switches the HDMA channels between them.
+// labels contains the table labels of every frame in the same order as the delays.
+// The suffix is appended to the labels of the driver so that there can be several animations in the same file.
+pub fn animation_driver(labels: &[Vec<String>], delays: &[u32], suffix: &str, format: &NumberFormat) -> String {
+    let driver = Driver { routine: "animate_gradient", index: "gradient_frame_index", pointers: "gradient_pointers", counter: "gradient_frame_counter" };
+    let mut output = String::from("; Call animate_gradient once per frame after the HDMA channels have been set up.\n");
+    output.push_str("!gradient_frame_counter = $14\n");
+    output.push_str(&channel_defines(labels));
+    output.push_str(&driver.write(labels, &frame_index(delays), suffix, format));
+    return output;
+}
+
+// Same as above but for the steps of a crossfade which are picked by a timer instead of the frame counter.
+// The timer goes from 0 (the first table) to 255 (the last one) and is left to the game to count.
+pub fn crossfade_driver(labels: &[Vec<String>], suffix: &str, format: &NumberFormat) -> String {
+    let driver = Driver { routine: "crossfade_gradient", index: "gradient_fade_index", pointers: "gradient_fade_pointers", counter: "gradient_fade_timer" };
+    let mut output = String::from("; Call crossfade_gradient once per frame after the HDMA channels have been set up.\n");
+    output.push_str("; Count up the timer from 0 to 255 to fade from the first to the last table (the default is free RAM in SMW).\n");
+    output.push_str("!gradient_fade_timer = $0F5E\n");
+    output.push_str(&channel_defines(labels));
+    output.push_str(&driver.write(labels, &timer_index(labels.len()), suffix, format));
+    return output;
+}
+
+// Two routines for the steps of a screen fade, one which fades in and one which fades out.
+// Both use the same tables, the fade out simply goes through them backwards. Just like the crossfade, the timer goes
+// from 0 to 255 which means that each routine only needs to count up.
+pub fn fade_driver(labels: &[Vec<String>], suffix: &str, format: &NumberFormat) -> String {
+    let fade_in = Driver { routine: "fade_in_gradient", index: "gradient_fade_in_index", pointers: "gradient_fade_in_pointers", counter: "gradient_fade_timer" };
+    let fade_out = Driver { routine: "fade_out_gradient", index: "gradient_fade_out_index", pointers: "gradient_fade_out_pointers", counter: "gradient_fade_timer" };
+    let reversed: Vec<Vec<String>> = labels.iter().rev().cloned().collect();
+
+    let mut output = String::from("; Call fade_in_gradient or fade_out_gradient once per frame after the HDMA channels have been set up.\n");
+    output.push_str("; Count up the timer from 0 to 255 during the fade (the default is free RAM in SMW).\n");
+    output.push_str("!gradient_fade_timer = $0F5E\n");
+    output.push_str(&channel_defines(labels));
+    output.push_str(&fade_in.write(labels, &timer_index(labels.len()), suffix, format));
+    output.push('\n');
+    output.push_str(&fade_out.write(&reversed, &timer_index(labels.len()), suffix, format));
+    return output;
+}
+
+// Spreads the steps evenly over the timer, the first and last step are only reached at 0 and 255 respectively.
+fn timer_index(steps: usize) -> Vec<usize> {
+    let steps = steps.max(1);
+    return (0..FRAME_INDEX_SIZE).map(|timer| (timer * (steps - 1) + (FRAME_INDEX_SIZE - 1) / 2) / (FRAME_INDEX_SIZE - 1)).collect();
+}
+
+// Every table of a step gets its own HDMA channel.
+fn channel_defines(labels: &[Vec<String>]) -> String {
+    let table_count = labels.first().map_or(0, |tables| tables.len());
+
+    let mut output = String::from("; The bank of the tables has to be written to $43x4 beforehand.\n");
+    for table in 0..table_count {
+        output.push_str(&format!("!gradient_channel_{} = {}\n", table, FIRST_CHANNEL + table));
+    }
+
+    return output;
+}
+
+// An HDMA table along with how it's transferred.
+pub struct Channel {
+    pub label: String,
+    // The transfer mode of $43x0, e.g. 0 for one byte and 2 for two bytes to the same register.
+    pub transfer_mode: u8,
+    // The low byte of the register the table is written to, e.g. $32 for $2132.
+    pub register: u8
+}
+
+// Lists which channel each table goes to for tables which are set up by hand.
+// The channels go up to channel 7, any table beyond that has to be left out (or put on a channel of the game).
+pub fn channel_comments(channels: &[Channel]) -> String {
+    let mut output = String::from("; HDMA channels (the bank of each table goes to $43x4):\n");
+
+    for (index, channel) in channels.iter().enumerate() {
+        match FIRST_CHANNEL + index {
+            number @ 0..=7 => output.push_str(&format!("; Channel {}: {} to $21{:02X} with transfer mode {}\n",
+                number, channel.label, channel.register, channel.transfer_mode)),
+            _ => output.push_str(&format!("; No channel left: {}\n", channel.label))
+        }
+    }
+
+    return output;
+}
+
+// Sets up an HDMA channel for each table and enables them.
+// The channels are numbered after their position, i.e. they use the !gradient_channel_N defines.
+fn channel_setup(channels: &[Channel], hdma_enable: &str) -> String {
+    let mut output = String::new();
+
+    for (index, channel) in channels.iter().enumerate() {
+        output.push_str(&format!("REP #$20\nLDA #${:02X}{:02X}\nSTA $4300+(!gradient_channel_{}<<4)\n", channel.register, channel.transfer_mode, index));
+        output.push_str(&format!("LDA #{}\nSTA $4302+(!gradient_channel_{}<<4)\n", channel.label, index));
+        output.push_str(&format!("SEP #$20\nLDA #{}>>16\nSTA $4304+(!gradient_channel_{}<<4)\n", channel.label, index));
+    }
+
+    let enable_bits: Vec<String> = (0..channels.len()).map(|index| format!("(1<<!gradient_channel_{})", index)).collect();
+    output.push_str(&format!("LDA #{}\nTSB {}\n", enable_bits.join("|"), hdma_enable));
+
+    return output;
+}
+
+// The init routine of a spotlight: The colour tables darken everything (by subtracting their colour) except for the
+// inside of the window. The window (window 1) is used as the colour window.
+// The registers default to the RAM mirrors of SMW which are written to the actual registers during NMI.
+pub fn spotlight_init(channels: &[Channel], suffix: &str) -> String {
+    let mut output = String::from("; Call spotlight_init once when the level (or whatever else) is loaded.\n");
+    output.push_str("!spotlight_wobjsel = $43\n!spotlight_cgwsel = $44\n!spotlight_cgadsub = $40\n!spotlight_hdmaen = $0D9F\n");
+    output.push_str(&channel_defines(&[channels.iter().map(|channel| channel.label.clone()).collect()]));
+
+    output.push_str(&format!("\nspotlight_init{}:\n", suffix));
+    output.push_str("PHP\nSEP #$20\n");
+    output.push_str("; Window 1 is the colour window.\nLDA #$20\nSTA !spotlight_wobjsel\n");
+    output.push_str("; No colour math inside of the colour window, the fixed colour is used.\nLDA #$20\nSTA !spotlight_cgwsel\n");
+    output.push_str("; Subtract the fixed colour from every layer and the backdrop.\nLDA #$BF\nSTA !spotlight_cgadsub\n");
+    output.push_str(&channel_setup(channels, "!spotlight_hdmaen"));
+    output.push_str("PLP\nRTL\n");
+
+    return output;
+}
+
+// A patch which can be applied as it is: The tables go to free space and an init routine sets up their channels.
+// The hijack is only a template since it depends on the game, it has to point to code which runs once when the level
+// (or whatever else) is loaded. By default, there is no hijack at all.
+pub fn asar_patch(channels: &[Channel], tables: &str) -> String {
+    let mut output = String::from("lorom\n\n");
+    output.push_str("; Set this to the code which is hijacked. The JSL overwrites four bytes there which have to be put\n");
+    output.push_str("; into gradient_patch_hook so that they still run. SMW games can set !gradient_hdmaen to $0D9F.\n");
+    output.push_str("!gradient_hijack = $000000\n!gradient_hdmaen = $420C\n");
+    output.push_str(&channel_defines(&[channels.iter().map(|channel| channel.label.clone()).collect()]));
+
+    output.push_str("\nif !gradient_hijack != 0\norg !gradient_hijack\nautoclean JSL gradient_patch_hook\nendif\n");
+
+    output.push_str("\nfreecode\n\ngradient_patch_hook:\n; The overwritten code goes here.\nJSL gradient_patch_init\nRTL\n");
+    output.push_str("\ngradient_patch_init:\nPHP\nSEP #$20\n");
+    output.push_str(&channel_setup(channels, "!gradient_hdmaen"));
+    output.push_str("PLP\nRTL\n");
+
+    output.push_str(&format!("\nfreedata\n\n{}", tables));
+    return output;
+}
+
+// A file for the uberASM tool: init sets up the channels once and main runs every frame.
+// uberASM already puts the file into free space, which is why the tables simply follow the routines.
+// The overworld clears HDMA when it switches between the maps so the channels are enabled again on every frame there.
+pub fn uberasm(channels: &[Channel], tables: &str, overworld: bool) -> String {
+    let mut output = channel_defines(&[channels.iter().map(|channel| channel.label.clone()).collect()]);
+
+    output.push_str("\ninit:\n");
+    output.push_str(&channel_setup(channels, "$0D9F|!addr"));
+    output.push_str("RTL\n");
+
+    output.push_str("\nmain:\n");
+    if overworld {
+        let enable_bits: Vec<String> = (0..channels.len()).map(|index| format!("(1<<!gradient_channel_{})", index)).collect();
+        output.push_str(&format!("LDA #{}\nTSB $0D9F|!addr\n", enable_bits.join("|")));
+    }
+    output.push_str("RTL\n");
+
+    output.push_str(&format!("\n{}", tables));
+    return output;
+}
+
+// The init routine of a layered sky: The backdrop (CG-RAM colour 0) gradient is on the main screen and the fixed
+// colour gradient is added onto it so that both of them can scroll or fade separately.
+// Just like the spotlight, the registers default to the RAM mirrors of SMW.
+pub fn backdrop_init(channels: &[Channel], suffix: &str) -> String {
+    let mut output = String::from("; Call gradient_backdrop_init once when the level (or whatever else) is loaded.\n");
+    output.push_str("!backdrop_cgwsel = $44\n!backdrop_cgadsub = $40\n!backdrop_hdmaen = $0D9F\n");
+    output.push_str(&channel_defines(&[channels.iter().map(|channel| channel.label.clone()).collect()]));
+
+    output.push_str(&format!("\ngradient_backdrop_init{}:\n", suffix));
+    output.push_str("PHP\nSEP #$20\n");
+    output.push_str("; Colour math everywhere with the fixed colour.\nLDA #$00\nSTA !backdrop_cgwsel\n");
+    output.push_str("; Add the fixed colour to the backdrop.\nLDA #$20\nSTA !backdrop_cgadsub\n");
+    output.push_str(&channel_setup(channels, "!backdrop_hdmaen"));
+    output.push_str("PLP\nRTL\n");
+
+    return output;
+}
+
+// Points the HDMA channels to the tables of the field which is drawn next since each field of an interlaced screen
+// only shows every other line of the gradient. Interlacing itself has to be enabled in $2133 by the game.
+pub fn field_driver(even: &[String], odd: &[String], suffix: &str) -> String {
+    let mut output = String::from("; Call switch_field once per frame during NMI after the HDMA channels have been set up.\n");
+    output.push_str(&channel_defines(&[even.to_vec()]));
+
+    // The field flag of $213F flips at the start of V-blank so during NMI, it already is the next field.
+    output.push_str(&format!("\nswitch_field{}:\n", suffix));
+    output.push_str("PHP\nSEP #$20\nLDA $213F\nREP #$20\nBMI .odd\n");
+    for (table, label) in even.iter().enumerate() {
+        output.push_str(&format!("LDA #{}\nSTA $4302+(!gradient_channel_{}<<4)\n", label, table));
+    }
+    output.push_str("PLP\nRTL\n\n.odd\n");
+    for (table, label) in odd.iter().enumerate() {
+        output.push_str(&format!("LDA #{}\nSTA $4302+(!gradient_channel_{}<<4)\n", label, table));
+    }
+    output.push_str("PLP\nRTL\n");
+
+    return output;
+}
+
+// Picks the tables of a region at assembly time: Every table of a region has got its suffix so the define can be
+// appended to the labels, e.g. red_table!gradient_region_suffix.
+pub fn region_define() -> String {
+    let mut output = String::from("; Set !gradient_pal to 1 before including this file for PAL and append !gradient_region_suffix to the labels.\n");
+    output.push_str("!gradient_pal ?= 0\n");
+    output.push_str("if !gradient_pal\n!gradient_region_suffix = \"_pal\"\nelse\n!gradient_region_suffix = \"_ntsc\"\nendif\n");
+    return output;
+}
+
+// How the fixed colour is combined with the layers.
+pub struct ColourMath {
+    pub subtract: bool,
+    pub half: bool,
+    // The enable bits of $2131: BG1 to BG4, OBJ and the backdrop from the lowest bit upwards.
+    pub layers: u8
+}
+
+impl ColourMath {
+    // The value of $2131 for these layers.
+    pub fn cgadsub(&self, layers: u8) -> u8 {
+        return (if self.subtract {0x80} else {0x00}) | (if self.half {0x40} else {0x00}) | (layers & 0x3F);
+    }
+}
+
+// An init routine which sets up the colour math for the fixed colour tables.
+// The registers are written directly by default, these can be replaced with the RAM mirrors of SMW ($44 and $40).
+pub fn colour_math_init(colour_math: &ColourMath, suffix: &str) -> String {
+    let cgadsub = colour_math.cgadsub(colour_math.layers);
+
+    let mut output = String::from("; Call gradient_colour_math once when the level (or whatever else) is loaded.\n");
+    output.push_str("!gradient_cgwsel = $2130\n!gradient_cgadsub = $2131\n");
+
+    output.push_str(&format!("\ngradient_colour_math{}:\n", suffix));
+    output.push_str("PHP\nSEP #$20\n");
+    output.push_str("; Colour math everywhere with the fixed colour.\nLDA #$00\nSTA !gradient_cgwsel\n");
+    output.push_str(&format!("; {} the fixed colour{}.\nLDA #${:02X}\nSTA !gradient_cgadsub\n",
+        if colour_math.subtract {"Subtract"} else {"Add"}, if colour_math.half {" and halve the result"} else {""}, cgadsub));
+    output.push_str("PLP\nRTL\n");
+
+    return output;
+}
+
+// The pointer and length table of a big gradient which has been split as well as a routine which gets the table after
+// the current one once the scroll code has reached its end.
+// The label is the one of the whole table, the labels of the parts have their number appended to it.
+pub fn split_table(label: &str, parts: &[(String, usize)], format: &NumberFormat) -> String {
+    let mut output = format!("; {} is split into {} tables: {}_pointers has got the address of each of them and\n", label, parts.len(), label);
+    output.push_str(&format!("; {}_lengths how many scanlines each of them covers.\n", label));
+    output.push_str(&format!("; Call {}_continue with the number of the finished table in X (A is 8-bit, X and Y are 16-bit).\n", label));
+    output.push_str("; It returns the number of the next table in X and its address in $00-$02 or sets the carry if there is none.\n");
+
+    // That's the only routine which is written for every assembler. The other ones don't keep track of the register
+    // sizes on their own and each of them has got its own kind of local labels.
+    let (sizes, done) = match format.syntax {
+        Syntax::Asar => ("", ".done"),
+        Syntax::Ca65 => (".a8\n.i16\n", "@done"),
+        Syntax::Tass64 => (".as\n.xl\n", "_done")
+    };
+    output.push_str(&format!("\n{}\n{}", format.label(&format!("{}_continue", label)), sizes));
+    output.push_str(&format!("INX\nCPX #{}\nBCS {}\n", format.word((parts.len() >> 8) as u8, parts.len() as u8), done));
+    output.push_str("REP #$20\nSTX $00\nTXA\nASL\nCLC\nADC $00\nTAY\nSEP #$20\n");
+    output.push_str(&format!("LDA {0}_pointers,y\nSTA $00\nLDA {0}_pointers+1,y\nSTA $01\nLDA {0}_pointers+2,y\nSTA $02\n", label));
+    output.push_str(&format!("CLC\n{}\nRTL\n", if format.syntax == Syntax::Ca65 {format.label(done)} else {String::from(done)}));
+
+    let labels: Vec<&str> = parts.iter().map(|(label, _)| label.as_str()).collect();
+    output.push_str(&format!("\n{}\n{} {}\n", format.label(&format!("{}_pointers", label)), format.long_directive(), labels.join(", ")));
+
+    let lengths: Vec<String> = parts.iter().map(|(_, scanlines)| format.word((scanlines >> 8) as u8, *scanlines as u8)).collect();
+    output.push_str(&format!("\n{}\n{} {}\n", format.label(&format!("{}_lengths", label)), format.word_directive(), lengths.join(", ")));
+
+    return output;
+}
+
+// A small ASM file which only includes the binary files of the tables so that huge tables don't bloat the patch.
+// Every table comes with a define of its size, e.g. !gradient_table_size.
+pub fn incbin_stub(tables: &[(String, String, usize)]) -> String {
+    let mut output = String::from("; The tables are in binary files next to this file.\n");
+    for (label, _, size) in tables {
+        output.push_str(&format!("!{}_size = {}\n", label, size));
+    }
+
+    for (label, file_name, _) in tables {
+        output.push_str(&format!("\n{}:\nincbin \"{}\"\n", label, file_name));
+    }
+
+    return output;
+}
+
+// Makes asar fail if a table ends up somewhere where it doesn't work instead of glitching in-game.
+// HDMA only increments the low word of the address which is why a table mustn't cross a bank border. Big gradients
+// are split on purpose and aren't read by HDMA directly so they're only checked against the budget (if there is one).
+pub fn size_asserts(tables: &[(String, usize, bool)], budget: Option<usize>) -> String {
+    let mut output = String::from("; Size checks\n");
+    if let (true, Some(budget)) = (tables.iter().any(|(_, _, big)| *big), budget) {
+        output.push_str(&format!("!gradient_size_budget = {}\n", budget));
+    }
+
+    for (label, size, big) in tables {
+        if !big {
+            output.push_str(&format!("assert {0}>>16 == ({0}+{1}-1)>>16, \"{0} crosses a bank border.\"\n", label, size));
+        }
+        else if budget.is_some() {
+            output.push_str(&format!("assert {1} <= !gradient_size_budget, \"{0} is larger than the budget.\"\n", label, size));
+        }
+    }
+
+    return output;
+}
+
+// Lists the size of every table whenever the file is assembled so that the used space shows up in every build.
+pub fn size_prints(tables: &[(String, usize)]) -> String {
+    let mut output = String::from("; Size report\n");
+    for (label, size) in tables {
+        output.push_str(&format!("print \"{}: $\", hex({})\n", label, size));
+    }
+
+    let total: usize = tables.iter().map(|(_, size)| size).sum();
+    output.push_str(&format!("print \"Total: $\", hex({})\n", total));
+    return output;
+}
+
+// Constants for driver code which refers to the tables, e.g. !red_table_length.
+// The DMA mode is the transfer mode of $43x0. Big gradients and IRQ tables aren't read by HDMA so they've got none.
+pub fn table_defines(tables: &[(String, usize, usize, Option<u8>)], format: &NumberFormat) -> String {
+    let define = |name: String, value: String| match format.syntax {
+        Syntax::Asar => format!("!{} = {}\n", name, value),
+        Syntax::Ca65 | Syntax::Tass64 => format!("{} = {}\n", name, value)
+    };
+
+    let mut output = String::new();
+    for (label, scanlines, length, transfer_mode) in tables {
+        output.push_str(&define(format!("{}_scanlines", label), scanlines.to_string()));
+        output.push_str(&define(format!("{}_length", label), length.to_string()));
+        if let Some(transfer_mode) = transfer_mode {
+            output.push_str(&define(format!("{}_dma_mode", label), format.byte(*transfer_mode as usize)));
+        }
+    }
+
+    return output;
+}
+
+// Keeps the labels of several files apart: Outside the namespace, red_table becomes sunset_red_table in asar and
+// sunset::red_table in ca65 and sunset.red_table in 64tass.
+pub fn namespace(name: &str, body: &str, format: &NumberFormat) -> String {
+    return match format.syntax {
+        Syntax::Asar => format!("namespace {}\n{}\nnamespace off\n", name, body),
+        Syntax::Ca65 => format!(".scope {}\n{}\n.endscope\n", name, body),
+        Syntax::Tass64 => format!("{} .block\n{}\n.bend\n", name, body)
+    };
+}
+
+// Writes an address as short as possible, e.g. $1C for the direct page.
+fn address(value: u32) -> String {
+    match value {
+        0..=0xFF => format!("${:02X}", value),
+        0x100..=0xFFFF => format!("${:04X}", value),
+        _ => format!("${:06X}", value)
+    }
+}
+
+// A routine which builds the scanlines of a big gradient which are currently visible.
+// Big gradients aren't real HDMA tables (they're too tall and fixed colour needs three bytes) so the routine walks
+// through the table until it reaches the scroll position and writes the scanlines of the screen as two real tables to RAM:
+// One with red and green and another one with blue. Both write twice to $2132 so that the rows have got the same
+// size and can share an index (blue simply gets written twice).
+// The tables are at most as many rows as there are scanlines (3 bytes each) and the termination byte large.
+pub fn scroll_routine(label: &str, scroll: u32, ram: u32, scanlines: u32) -> String {
+    let mut output = format!("; Call {}_scroll once per frame to build the {} scanlines which are visible at the scroll position.\n", label, scanlines);
+    output.push_str("; It writes two HDMA tables to RAM which both go to $2132 with transfer mode 2: !gradient_scroll_rg with red and\n");
+    output.push_str("; green and !gradient_scroll_b with blue. !gradient_scroll_bank goes to $43x4. $00-$07 are used as scratch RAM.\n");
+    output.push_str(&format!("!gradient_scroll = {}\n", address(scroll)));
+    output.push_str(&format!("!gradient_scroll_bank = ${:02X}\n", ram >> 16));
+    output.push_str(&format!("!gradient_scroll_rg = ${:04X}\n!gradient_scroll_b = ${:04X}\n", ram & 0xFFFF, (ram + 0x300) & 0xFFFF));
+
+    output.push_str(&format!("\n{}_scroll:\n", label));
+    output.push_str("PHP\nPHB\nREP #$30\n");
+    output.push_str("; $00 is how many scanlines are skipped and $02 how many are left to write.\n");
+    output.push_str(&format!("LDA !gradient_scroll\nSTA $00\nLDA #${:04X}\nSTA $02\n", scanlines));
+    output.push_str("SEP #$20\nLDA #!gradient_scroll_bank\nPHA\nPLB\nREP #$20\n");
+    output.push_str("LDX #$0000\nLDY #$0000\n");
+
+    // Rows which are completely above the screen are skipped.
+    output.push_str(".skip\n");
+    output.push_str(&format!("LDA.l {},x\nAND #$00FF\nBEQ .end\nCMP $00\nBEQ .next\nBCS .found\n", label));
+    output.push_str(".next\nEOR #$FFFF\nSEC\nADC $00\nSTA $00\nINX #4\nBRA .skip\n");
+
+    // $06 is how many scanlines of the current row are left, $04 how many of them go into the next row in RAM.
+    output.push_str(".found\nSEC\nSBC $00\nSTA $06\n");
+    output.push_str(".write\nLDA $06\nCMP $02\nBCC +\nLDA $02\n+\nCMP #$0081\nBCC +\nLDA #$0080\n+\nSTA $04\n");
+    output.push_str("SEP #$20\nSTA !gradient_scroll_rg,y\nSTA !gradient_scroll_b,y\n");
+    output.push_str(&format!("LDA.l {}+1,x\nSTA !gradient_scroll_rg+1,y\n", label));
+    output.push_str(&format!("LDA.l {}+2,x\nSTA !gradient_scroll_rg+2,y\n", label));
+    output.push_str(&format!("LDA.l {}+3,x\nSTA !gradient_scroll_b+1,y\nSTA !gradient_scroll_b+2,y\n", label));
+    output.push_str("REP #$20\nINY #3\n");
+    output.push_str("LDA $02\nSEC\nSBC $04\nSTA $02\nBEQ .end\n");
+    output.push_str("LDA $06\nSEC\nSBC $04\nSTA $06\nBNE .write\n");
+    output.push_str(&format!("INX #4\nLDA.l {},x\nAND #$00FF\nBEQ .end\nSTA $06\nBRA .write\n", label));
+
+    output.push_str(".end\nSEP #$20\nLDA #$00\nSTA !gradient_scroll_rg,y\nSTA !gradient_scroll_b,y\n");
+    output.push_str("PLB\nPLP\nRTL\n");
+
+    return output;
+}
+
+// An IRQ handler which writes the values of a plain table for when there are no HDMA channels left.
+// The entries are the scanline where the values change and the values for each register. Every IRQ writes the values
+// of an entry and sets up the IRQ of the next one, shortly before the HBlank of the scanline above it. The first entry
+// is written by the init routine before the frame starts.
+pub fn irq_driver(label: &str, registers: &[u16], entries: &[(usize, Vec<u8>)], suffix: &str, format: &NumberFormat) -> String {
+    let mut output = format!("; Call gradient_irq_init{} at the start of every frame (e.g. in NMI) and gradient_irq{} from the IRQ handler\n", suffix, suffix);
+    output.push_str("; once the IRQ has been acknowledged ($4211).\n");
+    output.push_str(&format!("; Every entry of {} is the scanline followed by the values which are written there, $FFFF ends it.\n", label));
+    output.push_str("!gradient_irq_index = $0F60\n");
+    output.push_str("; NMI, the H/V-IRQ and auto joypad read.\n!gradient_nmitimen = $B1\n");
+    output.push_str("!gradient_irq_htime = $0110\n");
+
+    output.push_str(&format!("\ngradient_irq_init{}:\n", suffix));
+    output.push_str("PHP\nPHB\nPHK\nPLB\nREP #$30\n");
+    output.push_str("LDA #!gradient_irq_htime\nSTA $4207\n");
+    output.push_str(&format!("LDX #$0000\nJSR {}_write\n", label));
+    output.push_str("SEP #$20\nLDA #!gradient_nmitimen\nSTA $4200\n");
+    output.push_str("PLB\nPLP\nRTL\n");
+
+    output.push_str(&format!("\ngradient_irq{}:\n", suffix));
+    output.push_str("PHP\nPHB\nPHK\nPLB\nREP #$30\n");
+    output.push_str(&format!("LDX !gradient_irq_index\nJSR {}_write\n", label));
+    output.push_str("PLB\nPLP\nRTL\n");
+
+    // The IRQ fires on the scanline before the values change (the end of the table is never reached).
+    output.push_str(&format!("\n{}_write:\n", label));
+    output.push_str("SEP #$20\n");
+    for (index, register) in registers.iter().enumerate() {
+        output.push_str(&format!("LDA {}+{},x\nSTA ${:04X}\n", label, 2 + index, register));
+    }
+    output.push_str(&format!("REP #$20\nTXA\nCLC\nADC #{}\nSTA !gradient_irq_index\nTAX\n", format.word(0, 2 + registers.len() as u8)));
+    output.push_str(&format!("LDA {},x\nDEC\nSTA $4209\nRTS\n", label));
+
+    output.push_str(&format!("\n{}\n", format.label(label)));
+    for (scanline, values) in entries {
+        let values: Vec<String> = values.iter().map(|value| format.byte(*value as usize)).collect();
+        output.push_str(&format!("{} {}{}{} {}\n", format.word_directive(), format.word((scanline >> 8) as u8, *scanline as u8),
+            format.separator(), format.byte_directive(), values.join(",")));
+    }
+    output.push_str(&format!("{} {}\n", format.word_directive(), format.word(0xFF, 0xFF)));
+
+    return output;
+}
+
+// Two copies of every table in RAM for tables which are changed while they're shown (e.g. for fades or colour cycling).
+// The game writes to the back buffer and sets !gradient_buffer_ready once it's done, the swap routine then shows it
+// during NMI. That way, HDMA never reads a table which is only halfway written. Both buffers are laid out the same way,
+// each table right after the previous one.
+// tables contains the label and size of every table, each of them has got its own channel.
+pub fn double_buffer(tables: &[(String, usize)], ram: u32, format: &NumberFormat) -> String {
+    let mut output = String::from("; Call gradient_buffer_init once after the HDMA channels have been set up and gradient_buffer_swap during NMI.\n");
+    output.push_str("; Write the new values to the buffer which isn't shown (buffer 1 if !gradient_buffer_index is 0 and vice versa)\n");
+    output.push_str("; and set !gradient_buffer_ready afterwards. Both buffers start out as copies of the tables.\n");
+    output.push_str("!gradient_buffer_index = $0F62\n!gradient_buffer_ready = $0F63\n");
+    output.push_str(&format!("!gradient_buffer_bank = ${:02X}\n", ram >> 16));
+    output.push_str(&channel_defines(&[tables.iter().map(|(label, _)| label.clone()).collect()]));
+
+    let buffer_size: usize = tables.iter().map(|(_, size)| size).sum();
+    let mut offset = (ram & 0xFFFF) as usize;
+    for (label, size) in tables {
+        output.push_str(&format!("!{0}_buffer_0 = ${1:04X}\n!{0}_buffer_1 = ${2:04X}\n", label, offset, offset + buffer_size));
+        offset += size;
+    }
+
+    output.push_str("\ngradient_buffer_init:\n");
+    output.push_str("PHP\nPHB\nREP #$30\n");
+    for (label, size) in tables {
+        for buffer in 0..2 {
+            output.push_str(&format!("LDX #{}\nLDY #!{}_buffer_{}\nLDA #{}\n", label, label, buffer, format.word(((size - 1) >> 8) as u8, (size - 1) as u8)));
+            output.push_str(&format!("MVN !gradient_buffer_bank, {}>>16\n", label));
+        }
+    }
+    // MVN leaves the data bank at the one of the buffers.
+    output.push_str("PLB\nSEP #$20\nSTZ !gradient_buffer_index\nSTZ !gradient_buffer_ready\n");
+    output.push_str(&format!("LDA #!gradient_buffer_bank\n{}", tables.iter().enumerate()
+        .map(|(index, _)| format!("STA $4304+(!gradient_channel_{}<<4)\n", index)).collect::<String>()));
+    output.push_str("REP #$20\n");
+    for (index, (label, _)) in tables.iter().enumerate() {
+        output.push_str(&format!("LDA #!{}_buffer_0\nSTA $4302+(!gradient_channel_{}<<4)\n", label, index));
+    }
+    output.push_str("PLP\nRTL\n");
+
+    // The table address is only read at the start of a frame so writing it during NMI doesn't tear.
+    output.push_str("\ngradient_buffer_swap:\n");
+    output.push_str("PHP\nSEP #$20\nLDA !gradient_buffer_ready\nBEQ .done\nSTZ !gradient_buffer_ready\n");
+    output.push_str("LDA !gradient_buffer_index\nEOR #$01\nSTA !gradient_buffer_index\nREP #$20\nBNE .second\n");
+    for (index, (label, _)) in tables.iter().enumerate() {
+        output.push_str(&format!("LDA #!{}_buffer_0\nSTA $4302+(!gradient_channel_{}<<4)\n", label, index));
+    }
+    output.push_str("BRA .done\n.second\n");
+    for (index, (label, _)) in tables.iter().enumerate() {
+        output.push_str(&format!("LDA #!{}_buffer_1\nSTA $4302+(!gradient_channel_{}<<4)\n", label, index));
+    }
+    output.push_str(".done\nPLP\nRTL\n");
+
+    return output;
+}
+
+// The labels of a routine which switches tables depending on a counter.
+struct Driver {
+    routine: &'static str,
+    index: &'static str,
+    pointers: &'static str,
+    counter: &'static str
+}
+
+impl Driver {
+    // The counter is looked up in the index which in turn picks the pointer of each table.
+    fn write(&self, labels: &[Vec<String>], index: &[usize], suffix: &str, format: &NumberFormat) -> String {
+        let table_count = labels.first().map_or(0, |tables| tables.len());
+
+        let mut output = format!("\n{}{}:\n", self.routine, suffix);
+        output.push_str("PHP\nREP #$30\n");
+        output.push_str(&format!("LDA !{}\nAND #$00FF\nTAX\n", self.counter));
+        output.push_str(&format!("LDA {}{},x\nAND #$00FF\nASL\nTAX\n", self.index, suffix));
+        for table in 0..table_count {
+            output.push_str(&format!("LDA {}_{}{},x\n", self.pointers, table, suffix));
+            output.push_str(&format!("STA $4302+(!gradient_channel_{}<<4)\n", table));
+        }
+        output.push_str("PLP\nRTL\n");
+
+        output.push_str(&format!("\n{}\n", format.label(&format!("{}{}", self.index, suffix))));
+        for line in index.chunks(16) {
+            let values: Vec<String> = line.iter().map(|frame| format.byte(*frame)).collect();
+            output.push_str(&format!("{} {}\n", format.byte_directive(), values.join(", ")));
+        }
+
+        for table in 0..table_count {
+            let pointers: Vec<&str> = labels.iter().filter_map(|tables| tables.get(table)).map(|label| label.as_str()).collect();
+            output.push_str(&format!("\n{}\n{} {}\n", format.label(&format!("{}_{}{}", self.pointers, table, suffix)), format.word_directive(), pointers.join(", ")));
+        }
+
+        return output;
+    }
+}
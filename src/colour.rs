@@ -1,6 +1,14 @@
-use crate::hdma::{HdmaTable, HdmaRow, HdmaWriteMode};
+use crate::codegen;
+use crate::filters::{self, ColourSpace, Dither, Filter};
+use crate::hdma::{HdmaTable, HdmaRow, HdmaWriteMode, NumberFormat};
+use crate::json;
+use crate::window;
 
-use image::{Rgb, ImageBuffer};
+use std::str::FromStr;
+use crate::messages::Message;
+
+use image::{Rgb, Rgba, RgbaImage, ImageBuffer};
+use indicatif::ProgressBar;
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum HdmaColourMode {
@@ -8,6 +16,291 @@ pub enum HdmaColourMode {
     FixedClourTwo,
     BigGradient,
     CgRam,
+    // Not a colour at all but the screen brightness ($2100).
+    Brightness,
+    // Neither is this one, it's the mosaic size ($2106).
+    Mosaic,
+    // The left and right window position ($2126 and $2127) from a mask.
+    Window,
+    // Colour math ($2131) which is switched on and off by a mask.
+    ColourMath,
+}
+
+impl HdmaColourMode {
+    // The name of the mode as it's entered in the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            HdmaColourMode::FixedClourThree => "single",
+            HdmaColourMode::FixedClourTwo => "double",
+            HdmaColourMode::BigGradient => "big",
+            HdmaColourMode::CgRam => "cgram",
+            HdmaColourMode::Brightness => "brightness",
+            HdmaColourMode::Mosaic => "mosaic",
+            HdmaColourMode::Window => "window",
+            HdmaColourMode::ColourMath => "math"
+        }
+    }
+}
+
+// How the rows of the image are stretched to the output height.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Resample {
+    Nearest,
+    Linear,
+    Cubic
+}
+
+impl FromStr for Resample {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "nearest" => Ok(Resample::Nearest),
+            "linear" => Ok(Resample::Linear),
+            "cubic" => Ok(Resample::Cubic),
+            _ => Err(())
+        }
+    }
+}
+
+impl Resample {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Resample::Nearest => "nearest",
+            Resample::Linear => "linear",
+            Resample::Cubic => "cubic"
+        }
+    }
+}
+
+// How the values get to the registers: Either HDMA tables (the default) or a plain table which is written by an IRQ
+// for when there are no HDMA channels left.
+#[derive(Copy, Clone, PartialEq)]
+pub enum OutputDriver {
+    Hdma,
+    Irq
+}
+
+impl FromStr for OutputDriver {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "hdma" => Ok(OutputDriver::Hdma),
+            "irq" => Ok(OutputDriver::Irq),
+            _ => Err(())
+        }
+    }
+}
+
+impl OutputDriver {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputDriver::Hdma => "hdma",
+            OutputDriver::Irq => "irq"
+        }
+    }
+}
+
+// Which lines of an interlaced image end up in a table: Both fields averaged into one table or only those of one field.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Interlace {
+    Doubled,
+    Even,
+    Odd
+}
+
+// Everything which determines how an image is ripped.
+#[derive(Clone)]
+pub struct RipSettings {
+    pub height: u32,
+    // How many scanlines the screen has got, i.e. 224 or 239 with overscan.
+    pub scanlines: u32,
+    // Interlaced images are sampled at twice the scanlines and then brought down to a single field.
+    pub interlace: Option<Interlace>,
+    // Moves the table down (or up if negative) by this many scanlines for when HDMA starts a line early or late.
+    pub line_offset: i32,
+    // Scanlines at the top (e.g. SMW's status bar) which get a single colour so that the gradient starts below them.
+    // Without a colour, they repeat the first colour of the gradient.
+    pub reserve_top: u32,
+    pub reserve_colour: Option<Rgb<u8>>,
+    pub x_pos: u32,
+    pub x_end: u32,
+    pub sample_radius: u32,
+    pub denoise: Option<Filter>,
+    pub smooth: Option<u32>,
+    pub dither: Option<Dither>,
+    pub max_levels: Option<u32>,
+    // Neighbouring scanlines which are closer than the tolerance in this colour space are merged.
+    pub merge_space: Option<ColourSpace>,
+    pub merge_tolerance: f64,
+    // Whether the levels are chosen by k-means instead of being evenly spaced.
+    pub cluster_levels: bool,
+    // The palette of an indexed image. CGRAM mode snaps the colours to it since it writes them as they are.
+    pub palette: Option<Vec<Rgb<u8>>>,
+    pub resample: Resample,
+    pub mirror: Option<u32>,
+    pub stride: u32,
+    pub extrapolate: bool,
+    pub pad_to: Option<u32>,
+    pub loop_blend: Option<u32>,
+    pub hold_colour: Option<Rgb<u8>>,
+    pub hold_transparent: bool,
+    pub gamma: f32,
+    // Doubles the colours to make up for half colour math.
+    pub compensate_half: bool,
+    // Inverts the colours for subtractive colour math so that subtracting them from white gives the gradient.
+    pub invert: bool,
+    // In degrees, used for recoloured copies of the tables.
+    pub hue_shift: i32,
+    pub brightness: i32,
+    pub contrast: i32,
+    pub saturation: i32,
+    pub y_start: u32,
+    pub y_end: u32,
+    pub ranges: Vec<(u32, u32)>,
+    pub range_filler: u32,
+    pub mode: HdmaColourMode,
+    // Modes whose tables are written after the ones of the main mode.
+    pub extra_modes: Vec<HdmaColourMode>,
+    pub cgram_index: Option<u8>,
+    // Several colours at once: Each column (x position) gets its own table for its colour index.
+    pub cgram_columns: Vec<(u32, u8)>,
+    // Writes two neighbouring colours per scanline, the second one is sampled right next to the first one.
+    pub cgram_pair: bool,
+    // Scales the brightness of brightness tables, e.g. for the steps of a fade.
+    pub brightness_scale: f32,
+    // The BG enable bits of mosaic tables (bit 0 is BG1, bit 3 is BG4).
+    pub mosaic_layers: u8,
+    // Whether fixed colour channels which are the same on every scanline share a table.
+    pub merge_channels: bool,
+    // Whether a grayscale gradient is written as a single table. That changes the labels which is why it's left out
+    // where several tables have to have got the same ones, e.g. the frames of an animation.
+    pub merge_grayscale: bool,
+    // The colour bits of the fixed colour channels which are ripped at all.
+    pub channels: u8,
+    // The $2131 values of colour math tables where the mask is bright and dark respectively
+    // and the luminance between them.
+    pub math_values: (u8, u8),
+    pub math_threshold: u8,
+    // Big gradients which take up more bytes than that are split into several tables.
+    pub split_size: usize,
+    pub driver: OutputDriver,
+    // The scroll position and the RAM of the routine which builds the visible part of a big gradient.
+    pub scroll_routine: Option<(u32, u32)>,
+    pub optimise: bool,
+    pub number_format: NumberFormat,
+    // Overrides whether the tables are written as bytes, words or longs.
+    pub write_mode: Option<HdmaWriteMode>,
+    // The order of the bytes in each word and of the two words of a scanline.
+    pub big_endian: bool,
+    pub swap_words: bool,
+    // Put in front of every table label (e.g. to tell apart the gradients of several files).
+    pub label_prefix: String,
+    // Appended to every label (e.g. for the frames of an animation).
+    pub label_suffix: String
+}
+
+// The size of a table once it has been written.
+pub struct TableStats {
+    pub name: String,
+    pub row_count: usize,
+    pub byte_size: usize,
+    pub unoptimised_size: usize,
+    // The bytes of the table itself for binary output.
+    pub data: Vec<u8>,
+    // How many bytes each scanline of an HDMA table has got. IRQ tables aren't HDMA tables so they've got none.
+    pub scanline_size: Option<usize>,
+    // The mode of this very table since extra modes are written along with the main mode.
+    pub mode: HdmaColourMode
+}
+
+impl TableStats {
+    pub fn to_json(&self) -> String {
+        return format!("{{\"name\":{},\"rows\":{},\"bytes\":{},\"unoptimised_bytes\":{},\"saved_bytes\":{}}}",
+            json::string(&self.name), self.row_count, self.byte_size, self.unoptimised_size,
+            self.unoptimised_size.saturating_sub(self.byte_size));
+    }
+
+    // The whole table: Every row (read back from the bytes) along with the bytes themselves.
+    pub fn table_to_json(&self) -> String {
+        let values = |data: &[u8]| data.iter().map(|value| value.to_string()).collect::<Vec<String>>().join(",");
+        let rows: Vec<String> = self.read_rows().into_iter().map(|row| match row {
+            TableRow::Repeat(count, data) => format!("{{\"type\":\"repeat\",\"count\":{},\"data\":[{}]}}", count, values(data)),
+            TableRow::Continuous(scanlines) => {
+                let scanlines: Vec<String> = scanlines.iter().map(|data| format!("[{}]", values(data))).collect();
+                format!("{{\"type\":\"continuous\",\"count\":{},\"data\":[{}]}}", scanlines.len(), scanlines.join(","))
+            }
+            TableRow::End => String::from("{\"type\":\"end\"}")
+        }).collect();
+
+        return format!("{{\"name\":{},\"rows\":[{}],\"bytes\":[{}]}}", json::string(&self.name), rows.join(","), values(&self.data));
+    }
+
+    // The values which HDMA writes on each scanline. Repeat rows only write once but since the registers keep their
+    // values, that's the same as writing them on every scanline.
+    pub fn scanline_values(&self) -> Vec<&[u8]> {
+        let mut output = Vec::new();
+
+        for row in self.read_rows() {
+            match row {
+                TableRow::Repeat(count, data) => output.extend(std::iter::repeat(data).take(count)),
+                TableRow::Continuous(scanlines) => output.extend(scanlines),
+                TableRow::End => break
+            }
+        }
+
+        return output;
+    }
+
+    // The first byte of a row is the scanline count. Up to $80, the values are repeated for that many scanlines, above
+    // that, each of the (count - $80) scanlines has got its own values. A count of 0 ends the table.
+    // Big gradients don't have got continuous rows, their counts go up to $FF instead.
+    // IRQ tables aren't HDMA tables so they've got no rows.
+    fn read_rows(&self) -> Vec<TableRow<'_>> {
+        let scanline_size = match self.scanline_size {
+            Some(scanline_size) => scanline_size,
+            None => return Vec::new()
+        };
+        let mut rows = Vec::new();
+        let mut index = 0;
+
+        while index < self.data.len() {
+            let count = self.data[index] as usize;
+            index += 1;
+
+            if count == 0 {
+                rows.push(TableRow::End);
+                break;
+            }
+            else if count > 0x80 && self.mode != HdmaColourMode::BigGradient {
+                let end = (index + (count - 0x80) * scanline_size).min(self.data.len());
+                rows.push(TableRow::Continuous(self.data[index..end].chunks(scanline_size).collect()));
+                index = end;
+            }
+            else {
+                let end = (index + scanline_size).min(self.data.len());
+                rows.push(TableRow::Repeat(count, &self.data[index..end]));
+                index = end;
+            }
+        }
+
+        return rows;
+    }
+}
+
+// A row of a table which has been read back from its bytes.
+enum TableRow<'a> {
+    Repeat(usize, &'a [u8]),
+    Continuous(Vec<&'a [u8]>),
+    End
+}
+
+// The ASM file of the ripped tables and the size of each table.
+pub struct RippedTables {
+    pub text: String,
+    pub mode: HdmaColourMode,
+    pub stats: Vec<TableStats>
 }
 
 // The colour indeces of the colours
@@ -28,6 +321,15 @@ impl ColourIndex {
             ColourIndex::Blue => 0x80
         }
     }
+
+    // The name of a table with only this channel.
+    fn table_name(&self) -> &'static str {
+        match self {
+            ColourIndex::Red => "red_table",
+            ColourIndex::Green => "green_table",
+            ColourIndex::Blue => "blue_table"
+        }
+    }
 }
 
 // Note for both functions:
@@ -51,26 +353,364 @@ fn to_cgram_colour(colour: Rgb<u8>) -> u16 {
     return ((red as u16) << 0) | ((green as u16) << 5) | ((blue as u16) << 10);
 }
 
-pub fn get_rgb_from_image(image: ImageBuffer<Rgb<u8>, Vec<u8>>, x_input: u32, y_start: u32, y_end: u32, output_height: u32) -> Vec<Rgb<u8>> {
-    let mut colours = Vec::new();
+// Gets the colours of the gradient.
+// Each scanline is the average of the pixels from x_pos to x_end (both included).
+// The sample radius widens that area in every direction which gets rid of stray pixels and JPEG artefacts.
+pub fn get_rgb_from_image(image: &ImageBuffer<Rgba<u8>, Vec<u8>>, settings: &RipSettings) -> Vec<Rgb<u8>> {
+    let radius = settings.sample_radius;
+    let x_start = settings.x_pos.saturating_sub(radius);
+    let x_end = (settings.x_end + radius).min(image.width() - 1);
 
-    // Calculate the transformation of the rows.
-    let input_height = y_end - y_start;
-    let mut y_real: f64 = y_start as f64;
-    let delta_y: f64 = output_height as f64 / input_height as f64;
+    let ranges = if settings.ranges.is_empty() {
+        vec![(settings.y_start, settings.y_end)]
+    }
+    else {
+        settings.ranges.clone()
+    };
+
+    // Sample every row of the input first, then stretch them to the output height.
+    // Multiple ranges are simply put after each other, optionally with a few rows inbetween.
+    // Rows without a single visible pixel are left out unless they hold the previous colour.
+    let mut rows: Vec<Rgb<u8>> = Vec::new();
+    for (index, (y_start, y_end)) in ranges.into_iter().enumerate() {
+        if index > 0 {
+            if let Some(&last) = rows.last() {
+                rows.extend(std::iter::repeat(last).take(settings.range_filler as usize));
+            }
+        }
 
-    // Get the colour of the very left pixel
-    for _ in 0..output_height {
-        let colour = *image.get_pixel(x_input, y_real.round() as u32);
+        for y in y_start..y_end {
+            let colour = average_area(image, x_start, x_end, y.saturating_sub(radius), (y + radius).min(image.height() - 1));
 
-        y_real += delta_y;
+            // Rows which hold the previous colour only do so if there is one, otherwise they're left out as well.
+            let hold = match colour {
+                Some(colour) => settings.hold_colour == Some(colour),
+                None => settings.hold_transparent
+            };
 
-        colours.push(colour);
+            match (hold, colour, rows.last()) {
+                (true, _, Some(&last)) => rows.push(last),
+                (true, _, None) => {}
+                (false, Some(colour), _) => rows.push(colour),
+                (false, None, _) => {}
+            }
+        }
+    }
+
+    if rows.is_empty() {
+        log::warn!("{}", tr!(Message::AllRowsTransparent));
+        rows.push(Rgb([0, 0, 0]));
+    }
+
+    // A mirrored gradient only rips the part above the mirror, the rest is reflected.
+    let mirror = match settings.mirror {
+        Some(scanline) => scanline,
+        None => return fit_rows(&rows, settings.height, settings)
+    };
+
+    let mut colours = fit_rows(&rows, mirror, settings);
+    for scanline in mirror..settings.height {
+        // If the reflection is longer than the ripped part, the first colour is simply repeated.
+        let reflected = (2 * mirror).saturating_sub(scanline + 1);
+        colours.push(colours[reflected as usize]);
     }
 
     return colours;
 }
 
+// Brings the rows to the output height, either by resampling them or by continuing them.
+fn fit_rows(rows: &[Rgb<u8>], output_height: u32, settings: &RipSettings) -> Vec<Rgb<u8>> {
+    if settings.extrapolate && rows.len() < output_height as usize {
+        return extrapolate_rows(rows, output_height);
+    }
+
+    return resample_rows(rows, output_height, settings.resample);
+}
+
+// Keeps the rows as they are and continues the trend of the last few rows until the output height is reached.
+// The trend is measured over up to eight rows so that a single odd row doesn't throw it off.
+fn extrapolate_rows(rows: &[Rgb<u8>], output_height: u32) -> Vec<Rgb<u8>> {
+    const TREND_ROWS: usize = 8;
+
+    let last = rows[rows.len() - 1];
+    let distance = TREND_ROWS.min(rows.len() - 1);
+    let first = rows[rows.len() - 1 - distance];
+
+    // A single row has got no trend so it's just repeated.
+    let slopes: [f64; 3] = [0, 1, 2].map(|channel| {
+        if distance == 0 {0.0} else {(last.0[channel] as f64 - first.0[channel] as f64) / distance as f64}
+    });
+
+    let mut colours = rows.to_vec();
+    for offset in 1..=(output_height as usize - rows.len()) {
+        colours.push(Rgb([0, 1, 2].map(|channel| {
+            (last.0[channel] as f64 + slopes[channel] * offset as f64).round().clamp(0.0, 255.0) as u8
+        })));
+    }
+
+    return colours;
+}
+
+// Stretches the rows to the output height.
+// Each scanline is mapped to its centre in the input, i.e. the first and last scanline aren't stretched by half a row.
+fn resample_rows(rows: &[Rgb<u8>], output_height: u32, resample: Resample) -> Vec<Rgb<u8>> {
+    // Interpolation only makes sense for stretching, skipping rows loses detail.
+    if rows.len() > output_height as usize {
+        return downscale_rows(rows, output_height);
+    }
+
+    let last_row = rows.len() as isize - 1;
+    let delta_y: f64 = rows.len() as f64 / output_height as f64;
+
+    // Rows outside of the input repeat the edges.
+    let row = |index: isize| -> [f64; 3] {
+        let colour = rows[index.clamp(0, last_row) as usize];
+        return [colour.0[0] as f64, colour.0[1] as f64, colour.0[2] as f64];
+    };
+
+    return (0..output_height).map(|scanline| {
+        let y_real = (scanline as f64 + 0.5) * delta_y - 0.5;
+        let y = y_real.floor() as isize;
+        let t = y_real - y as f64;
+
+        let channels: [f64; 3] = match resample {
+            Resample::Nearest => row(y_real.round() as isize),
+            Resample::Linear => {
+                let (above, below) = (row(y), row(y + 1));
+                [0, 1, 2].map(|channel| above[channel] + (below[channel] - above[channel]) * t)
+            }
+            // Catmull-Rom spline, it passes through every row so the original colours stay intact.
+            Resample::Cubic => {
+                let (p0, p1, p2, p3) = (row(y - 1), row(y), row(y + 1), row(y + 2));
+                [0, 1, 2].map(|channel| {
+                    let (p0, p1, p2, p3) = (p0[channel], p1[channel], p2[channel], p3[channel]);
+                    0.5 * (2.0 * p1 + (p2 - p0) * t + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+                        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t)
+                })
+            }
+        };
+
+        // Cubic interpolation can overshoot.
+        Rgb(channels.map(|channel| channel.round().clamp(0.0, 255.0) as u8))
+    }).collect();
+}
+
+// Shrinks the rows to the output height.
+// Every scanline is the average of all the rows it covers, weighted by how much of a row is covered.
+fn downscale_rows(rows: &[Rgb<u8>], output_height: u32) -> Vec<Rgb<u8>> {
+    let delta_y: f64 = rows.len() as f64 / output_height as f64;
+
+    return (0..output_height).map(|scanline| {
+        let top = scanline as f64 * delta_y;
+        let bottom = top + delta_y;
+
+        let mut sum = [0f64; 3];
+        let mut row = top.floor() as usize;
+        while (row as f64) < bottom && row < rows.len() {
+            let weight = (bottom.min(row as f64 + 1.0) - top.max(row as f64)).max(0.0);
+            for (channel_sum, channel) in sum.iter_mut().zip(rows[row].0.iter()) {
+                *channel_sum += *channel as f64 * weight;
+            }
+            row += 1;
+        }
+
+        Rgb(sum.map(|channel| (channel / delta_y).round().clamp(0.0, 255.0) as u8))
+    }).collect();
+}
+
+// Reads a colour written as #RRGGBB (the # is optional).
+pub fn parse_hex_colour(text: &str) -> Option<Rgb<u8>> {
+    let text = text.trim_start_matches('#');
+    if text.len() != 6 {
+        return None;
+    }
+
+    let value = u32::from_str_radix(text, 16).ok()?;
+    return Some(Rgb([(value >> 16) as u8, (value >> 8) as u8, value as u8]));
+}
+
+fn to_rgb(pixel: &Rgba<u8>) -> Rgb<u8> {
+    return Rgb([pixel.0[0], pixel.0[1], pixel.0[2]]);
+}
+
+// Averages the pixels of a row from x_start to x_end (both included).
+fn average_row(image: &ImageBuffer<Rgba<u8>, Vec<u8>>, x_start: u32, x_end: u32, y: u32) -> Option<Rgb<u8>> {
+    return average_area(image, x_start, x_end, y, y);
+}
+
+// Averages the pixels of a rectangle (all edges included).
+// The pixels are weighted by their alpha so that transparent pixels don't count.
+// If every pixel is transparent, there is no colour.
+fn average_area(image: &ImageBuffer<Rgba<u8>, Vec<u8>>, x_start: u32, x_end: u32, y_start: u32, y_end: u32) -> Option<Rgb<u8>> {
+    let mut sum = [0u64; 3];
+    let mut weight = 0u64;
+
+    for y in y_start..=y_end {
+        for x in x_start..=x_end {
+            let pixel = image.get_pixel(x, y);
+            let alpha = pixel.0[3] as u64;
+            for (channel_sum, channel) in sum.iter_mut().zip(pixel.0.iter()) {
+                *channel_sum += *channel as u64 * alpha;
+            }
+            weight += alpha;
+        }
+    }
+
+    if weight == 0 {
+        return None;
+    }
+
+    // Round to the nearest value rather than down.
+    return Some(Rgb(sum.map(|channel_sum| ((channel_sum + weight / 2) / weight) as u8)));
+}
+
+// Finds the column with the least noise, for images where not every column is clean (e.g. because of dithering or objects in front of the gradient).
+// A column is scored by how much each pixel differs from its neighbours above and below (high frequency noise)
+// as well as from the average of its row (so that a stray solid column doesn't win). The lowest score wins.
+pub fn find_cleanest_column(image: &ImageBuffer<Rgba<u8>, Vec<u8>>, y_start: u32, y_end: u32) -> u32 {
+    let row_averages: Vec<Rgb<u8>> = (y_start..y_end)
+        .map(|y| average_row(image, 0, image.width() - 1, y).unwrap_or(Rgb([0, 0, 0])))
+        .collect();
+
+    let difference = |a: &Rgb<u8>, b: &Rgb<u8>| -> u64 {
+        return a.0.iter().zip(b.0.iter()).map(|(a, b)| (*a as i64 - *b as i64).unsigned_abs()).sum();
+    };
+
+    let mut best_column = 0;
+    let mut best_score = u64::MAX;
+
+    for x in 0..image.width() {
+        let mut score = 0;
+
+        for y in y_start..y_end {
+            let pixel = to_rgb(image.get_pixel(x, y));
+
+            score += difference(&pixel, &row_averages[(y - y_start) as usize]);
+
+            // The second difference is zero for any straight line so it only punishes noise and not the gradient itself.
+            if y > y_start && y + 1 < y_end {
+                let above = to_rgb(image.get_pixel(x, y - 1));
+                let below = to_rgb(image.get_pixel(x, y + 1));
+                score += pixel.0.iter().zip(above.0.iter().zip(below.0.iter()))
+                    .map(|(pixel, (above, below))| (2 * *pixel as i64 - *above as i64 - *below as i64).unsigned_abs())
+                    .sum::<u64>();
+            }
+        }
+
+        log::trace!("Column {} has got a score of {}", x, score);
+
+        if score < best_score {
+            best_column = x;
+            best_score = score;
+        }
+    }
+
+    return best_column;
+}
+
+// Two channels which are the same on every scanline can be written at once since COLDATA takes several channel bits.
+// Returns the pair (if there is one) along with the remaining channel.
+fn find_equal_channels(colours: &[Rgb<u8>]) -> Option<(ColourIndex, ColourIndex, ColourIndex)> {
+    let is_equal = |first: ColourIndex, second: ColourIndex| colours.iter().all(|colour| {
+        colour.0[first as usize] >> 3 == colour.0[second as usize] >> 3
+    });
+
+    if is_equal(ColourIndex::Red, ColourIndex::Green) {
+        return Some((ColourIndex::Red, ColourIndex::Green, ColourIndex::Blue));
+    }
+    if is_equal(ColourIndex::Red, ColourIndex::Blue) {
+        return Some((ColourIndex::Red, ColourIndex::Blue, ColourIndex::Green));
+    }
+    if is_equal(ColourIndex::Green, ColourIndex::Blue) {
+        return Some((ColourIndex::Green, ColourIndex::Blue, ColourIndex::Red));
+    }
+
+    return None;
+}
+
+fn is_grayscale(colours: &[Rgb<u8>]) -> bool {
+    return colours.iter().all(|colour| {
+        let (red, green, blue) = get_colour_triplet(*colour);
+        red >> 3 == green >> 3 && green >> 3 == blue >> 3
+    });
+}
+
+// All three channel bits are set so a single table writes the whole colour.
+fn create_grayscale_table(colours: Vec<Rgb<u8>>) -> HdmaTable {
+    let mut output = HdmaTable::new_real_table(Vec::new(), 1, HdmaWriteMode::Bytes, "grayscale_table");
+
+    for colour in colours {
+        output.push(HdmaRow::new_scanline(&[to_fixed_colour(colour, ColourIndex::Red) | 0xE0]));
+    }
+
+    return output;
+}
+
+// Creates a table for a pair of equal channels (with both channel bits set) and one for the remaining channel.
+// That's one table less than the single mode and a byte less per row than the double mode.
+fn create_merged_tables(colours: Vec<Rgb<u8>>, channels: (ColourIndex, ColourIndex, ColourIndex)) -> [HdmaTable; 2] {
+    let (first, second, single) = channels;
+
+    let merged_name = match (first, second) {
+        (ColourIndex::Red, ColourIndex::Green) => "red_green_merged_table",
+        (ColourIndex::Red, ColourIndex::Blue) => "red_blue_merged_table",
+        _ => "green_blue_merged_table"
+    };
+    let mut merged_table = HdmaTable::new_real_table(Vec::new(), 1, HdmaWriteMode::Bytes, merged_name);
+    let mut single_table = HdmaTable::new_real_table(Vec::new(), 1, HdmaWriteMode::Bytes, single.table_name());
+
+    for colour in colours {
+        merged_table.push(HdmaRow::new_scanline(&[to_fixed_colour(colour, first) | second.colour_bit()]));
+        single_table.push(HdmaRow::new_scanline(&[to_fixed_colour(colour, single)]));
+    }
+
+    return [merged_table, single_table];
+}
+
+// How large a table is once it's been written, used to tell how much merging the channels saves.
+fn written_size(mut table: HdmaTable, optimise: bool) -> usize {
+    if optimise {
+        table.coagulate();
+    }
+
+    return table.byte_size();
+}
+
+// Only the selected channels (given as their colour bits) for gradients which are combined with other tables.
+// In the double mode, two channels share a table just like the dual table.
+fn create_channel_tables(colours: Vec<Rgb<u8>>, channel_bits: u8, dual: bool) -> Vec<HdmaTable> {
+    let channels: Vec<ColourIndex> = [ColourIndex::Red, ColourIndex::Green, ColourIndex::Blue].iter()
+        .copied()
+        .filter(|channel| channel_bits & channel.colour_bit() != 0)
+        .collect();
+
+    if dual && channels.len() == 2 {
+        let (first, second) = (channels[0], channels[1]);
+        let name = match (first, second) {
+            (ColourIndex::Red, ColourIndex::Green) => "red_green_table",
+            (ColourIndex::Red, ColourIndex::Blue) => "red_blue_table",
+            _ => "green_blue_table"
+        };
+        let mut output = HdmaTable::new_real_table(Vec::new(), 2, HdmaWriteMode::Bytes, name);
+
+        for colour in colours {
+            output.push(HdmaRow::new_scanline(&[to_fixed_colour(colour, first), to_fixed_colour(colour, second)]));
+        }
+
+        return vec![output];
+    }
+
+    return channels.iter().map(|channel| {
+        let mut output = HdmaTable::new_real_table(Vec::new(), 1, HdmaWriteMode::Bytes, channel.table_name());
+
+        for colour in &colours {
+            output.push(HdmaRow::new_scanline(&[to_fixed_colour(*colour, *channel)]));
+        }
+
+        output
+    }).collect();
+}
+
 // A three colour version of the above.
 pub fn create_mode_0_tables(colours: Vec<Rgb<u8>>) -> [HdmaTable; 3] {
     let mut red_table = HdmaTable::new_real_table (Vec::new(), 1, HdmaWriteMode::Bytes, "red_table");
@@ -182,7 +822,7 @@ pub fn create_cgram_table(colours: Vec<Rgb<u8>>, cgram_index: Option<u8>) -> Hdm
     for colour in colours {
         let cgram_colour = to_cgram_colour(colour);
         let low_byte = (cgram_colour & 0x00FF) as u8;
-        let high_byte = ((cgram_colour & 0xFF00) >> 9) as u8;
+        let high_byte = ((cgram_colour & 0xFF00) >> 8) as u8;
 
         match cgram_index {
             Some(index) => output.push(HdmaRow::new_scanline(&[0x00, index, low_byte, high_byte])),
@@ -193,6 +833,79 @@ pub fn create_cgram_table(colours: Vec<Rgb<u8>>, cgram_index: Option<u8>) -> Hdm
     return output;
 }
 
+// Two neighbouring colours per scanline: The colour index is written first, followed by both colours. That's five bytes
+// but HDMA can only transfer up to four bytes per scanline and channel so the second colour gets its own table.
+// The first table writes the index twice to $2121 and the first colour to $2122 (transfer mode 3), the second one
+// writes the second colour to $2122 as well (transfer mode 2). Since CG-RAM goes to the next index after each colour,
+// the second table only has to come after the first one, i.e. on a higher channel.
+pub fn create_cgram_pair_tables(first_colours: Vec<Rgb<u8>>, second_colours: Vec<Rgb<u8>>, cgram_index: u8) -> Vec<HdmaTable> {
+    let mut first_table = HdmaTable::new_real_table(Vec::new(), 4, HdmaWriteMode::Words, "colour_pair_table");
+    let mut second_table = HdmaTable::new_real_table(Vec::new(), 2, HdmaWriteMode::Words, "colour_pair_second_table");
+
+    for (first_colour, second_colour) in first_colours.into_iter().zip(second_colours) {
+        let [first_low, first_high] = to_cgram_colour(first_colour).to_le_bytes();
+
+        first_table.push(HdmaRow::new_scanline(&[cgram_index, cgram_index, first_low, first_high]));
+        second_table.push(HdmaRow::new_scanline(&to_cgram_colour(second_colour).to_le_bytes()));
+    }
+
+    return vec![first_table, second_table];
+}
+
+// The brightness only has got 16 levels (0 is black, 15 is full brightness) which are taken from the luminance.
+// Forced blank (bit 7) is never set.
+pub fn create_brightness_table(colours: Vec<Rgb<u8>>, scale: f32) -> HdmaTable {
+    let mut output = HdmaTable::new_real_table(Vec::new(), 1, HdmaWriteMode::Bytes, "brightness_table");
+
+    for colour in colours {
+        output.push(HdmaRow::new_scanline(&[(to_brightness(colour) as f32 * scale).round() as u8]));
+    }
+
+    return output;
+}
+
+pub fn to_brightness(colour: Rgb<u8>) -> u8 {
+    return to_level(colour);
+}
+
+// The mosaic size (from 1x1 to 16x16 pixels) is taken from the luminance as well: Black is no mosaic at all and white
+// the largest one. It's in the upper four bits, the lower four bits enable the mosaic for each BG.
+pub fn create_mosaic_table(colours: Vec<Rgb<u8>>, layers: u8) -> HdmaTable {
+    let mut output = HdmaTable::new_real_table(Vec::new(), 1, HdmaWriteMode::Bytes, "mosaic_table");
+
+    for colour in colours {
+        output.push(HdmaRow::new_scanline(&[(to_level(colour) << 4) | (layers & 0x0F)]));
+    }
+
+    return output;
+}
+
+// Switches colour math depending on the luminance: Scanlines at least as bright as the threshold use the first value,
+// the others the second one. That way, either the colour math or just the layers it applies to can be switched.
+pub fn create_colour_math_table(colours: Vec<Rgb<u8>>, threshold: u8, values: (u8, u8)) -> HdmaTable {
+    let mut output = HdmaTable::new_real_table(Vec::new(), 1, HdmaWriteMode::Bytes, "colour_math_table");
+
+    for colour in colours {
+        output.push(HdmaRow::new_scanline(&[if luminance(colour) >= threshold as f64 {values.0} else {values.1}]));
+    }
+
+    return output;
+}
+
+// Brings the luminance down to 16 levels.
+fn to_level(colour: Rgb<u8>) -> u8 {
+    return (luminance(colour) * 15.0 / 255.0).round() as u8;
+}
+
+// Grey is taken as it is, the weights would only add rounding errors.
+fn luminance(colour: Rgb<u8>) -> f64 {
+    let (red, green, blue) = get_colour_triplet(colour);
+    if red == green && green == blue {
+        return red as f64;
+    }
+    return red as f64 * 0.299 + green as f64 * 0.587 + blue as f64 * 0.114;
+}
+
 fn get_colour_triplet(colour: Rgb<u8>) -> (u8, u8, u8) {
     let red = colour.0[ColourIndex::Red as usize];
     let green = colour.0[ColourIndex::Green as usize];
@@ -252,60 +965,658 @@ fn get_colour_count(colours: &Vec<Rgb<u8>>) -> [(isize, ColourIndex); 3] {
 
 
 
+// Applies the brightness, contrast and saturation (all in percent from -100 to 100).
+// Contrast stretches the colours away from middle grey whereas saturation stretches them away from their own grey.
+fn adjust_colours(colours: Vec<Rgb<u8>>, settings: &RipSettings) -> Vec<Rgb<u8>> {
+    let brightness = settings.brightness as f64 * 255.0 / 100.0;
+    let contrast = 1.0 + settings.contrast as f64 / 100.0;
+    let saturation = 1.0 + settings.saturation as f64 / 100.0;
+
+    return colours.iter().map(|colour| {
+        let [red, green, blue] = colour.0.map(|value| (value as f64 - 128.0) * contrast + 128.0 + brightness);
+        let grey = red * 0.299 + green * 0.587 + blue * 0.114;
+
+        Rgb([red, green, blue].map(|value| (grey + (value - grey) * saturation).round().clamp(0.0, 255.0) as u8))
+    }).collect();
+}
+
+// Rotates the hue of every colour while keeping its saturation and brightness (in the HSV sense).
+fn shift_hue(colours: Vec<Rgb<u8>>, degrees: i32) -> Vec<Rgb<u8>> {
+    return colours.iter().map(|colour| {
+        let [red, green, blue] = colour.0.map(|value| value as f64 / 255.0);
+        let max = red.max(green).max(blue);
+        let min = red.min(green).min(blue);
+        let chroma = max - min;
+
+        // Greys have got no hue to shift.
+        if chroma == 0.0 {
+            return *colour;
+        }
+
+        let hue = if max == red {
+            ((green - blue) / chroma).rem_euclid(6.0)
+        }
+        else if max == green {
+            (blue - red) / chroma + 2.0
+        }
+        else {
+            (red - green) / chroma + 4.0
+        };
+        let hue = (hue + degrees as f64 / 60.0).rem_euclid(6.0);
+
+        // Going back to RGB from the hue sector.
+        let second = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (red, green, blue) = match hue as u32 {
+            0 => (chroma, second, 0.0),
+            1 => (second, chroma, 0.0),
+            2 => (0.0, chroma, second),
+            3 => (0.0, second, chroma),
+            4 => (second, 0.0, chroma),
+            _ => (chroma, 0.0, second)
+        };
+
+        Rgb([red, green, blue].map(|value| ((value + min) * 255.0).round() as u8))
+    }).collect();
+}
+
+// Optimises a table (if enabled) and writes it.
+// Scrollable tables aren't real HDMA tables so they only get their repeating rows merged.
+fn finish_table(mut table: HdmaTable, settings: &RipSettings, repeat_only: bool, progress: &ProgressBar) -> (String, TableStats) {
+    let optimise = settings.optimise;
+    if let Some(write_mode) = settings.write_mode {
+        table.set_write_mode(write_mode);
+    }
+    table.set_label_prefix(&settings.label_prefix);
+    table.set_label_suffix(&settings.label_suffix);
+    progress.set_message(table.name());
+
+    // The optimisation adds the termination byte which is why it's counted here as well.
+    let unoptimised_size = table.byte_size() + if optimise {1} else {0};
+
+    if optimise {
+        if repeat_only {
+            table.coagulate_repeat();
+        }
+        else {
+            table.coagulate();
+        }
+
+        log::trace!("{}: Optimised from {} to {} bytes.", table.label(), unoptimised_size, table.byte_size());
+    }
+
+    // Only the order changes so it doesn't matter for the optimisation.
+    table.reorder_words(settings.big_endian, settings.swap_words);
+
+    // Scrollable tables can get large enough that they don't fit into a single bank anymore.
+    if repeat_only && table.byte_size() > settings.split_size {
+        let (output, stats) = write_split_table(table, unoptimised_size, settings);
+        progress.inc(1);
+        return (output, stats);
+    }
+
+    log::debug!("{}: {} rows, {} bytes", table.label(), table.row_count(), table.byte_size());
+
+    let stats = TableStats {
+        name: table.label(),
+        row_count: table.row_count(),
+        byte_size: table.byte_size(),
+        unoptimised_size: unoptimised_size,
+        data: table.bytes(),
+        scanline_size: Some(table.scanline_size()),
+        mode: settings.mode
+    };
+    let output = table.write_table(&settings.number_format);
+    progress.inc(1);
+
+    return (output, stats);
+}
+
+// The registers an IRQ writes to and the values of each scanline in the same order.
+// Fixed colour is always written channel by channel, no matter whether it's the single, double or big mode.
+// Window tables need the whole image and CG-RAM needs an index so neither of them is supported.
+fn create_irq_writes(colours: Vec<Rgb<u8>>, settings: &RipSettings) -> (Vec<u16>, Vec<Vec<u8>>) {
+    let channels = [ColourIndex::Red, ColourIndex::Green, ColourIndex::Blue];
+
+    match settings.mode {
+        HdmaColourMode::FixedClourThree | HdmaColourMode::FixedClourTwo | HdmaColourMode::BigGradient =>
+            (vec![0x2132; 3], colours.into_iter().map(|colour| channels.iter().map(|channel| to_fixed_colour(colour, *channel)).collect()).collect()),
+        HdmaColourMode::CgRam => {
+            let index = settings.cgram_index.unwrap_or(0);
+            (vec![0x2121, 0x2122, 0x2122], colours.into_iter().map(|colour| {
+                let [low, high] = to_cgram_colour(colour).to_le_bytes();
+                vec![index, low, high]
+            }).collect())
+        }
+        HdmaColourMode::Brightness =>
+            (vec![0x2100], colours.into_iter().map(|colour| vec![(to_brightness(colour) as f32 * settings.brightness_scale).round() as u8]).collect()),
+        HdmaColourMode::Mosaic =>
+            (vec![0x2106], colours.into_iter().map(|colour| vec![(to_level(colour) << 4) | (settings.mosaic_layers & 0x0F)]).collect()),
+        HdmaColourMode::ColourMath => {
+            let (bright, dark) = settings.math_values;
+            (vec![0x2131], colours.into_iter().map(|colour| vec![if luminance(colour) >= settings.math_threshold as f64 {bright} else {dark}]).collect())
+        }
+        HdmaColourMode::Window => (Vec::new(), Vec::new())
+    }
+}
+
+// Writes the values as a plain table along with the IRQ handler which writes them.
+// With the optimisation, only the scanlines where the values change get an entry.
+fn write_irq_table(colours: Vec<Rgb<u8>>, settings: &RipSettings) -> (String, TableStats) {
+    let (registers, values) = create_irq_writes(colours, settings);
+    let scanline_count = values.len();
+
+    let mut entries: Vec<(usize, Vec<u8>)> = Vec::new();
+    for (scanline, data) in values.into_iter().enumerate() {
+        if !settings.optimise || !matches!(entries.last(), Some((_, last)) if *last == data) {
+            entries.push((scanline, data));
+        }
+    }
+
+    // Every entry is the scanline (a word) and the values, the table ends with $FFFF.
+    let label = format!("{}irq_table{}", settings.label_prefix, settings.label_suffix);
+    let entry_size = 2 + registers.len();
+    let mut data: Vec<u8> = entries.iter().flat_map(|(scanline, values)| {
+        IntoIterator::into_iter((*scanline as u16).to_le_bytes()).chain(values.iter().copied())
+    }).collect();
+    data.extend_from_slice(&[0xFF, 0xFF]);
+    let stats = TableStats {
+        name: label.clone(),
+        row_count: entries.len(),
+        byte_size: entries.len() * entry_size + 2,
+        unoptimised_size: scanline_count * entry_size + 2,
+        data: data,
+        scanline_size: None,
+        mode: settings.mode
+    };
+    log::debug!("{}: {} rows, {} bytes", stats.name, stats.row_count, stats.byte_size);
+
+    return (codegen::irq_driver(&label, &registers, &entries, &settings.label_suffix, &settings.number_format), stats);
+}
+
+// Writes a scrollable table which is too large for split_size as several tables.
+// The parts count as a single table in the stats since that's how they're ripped.
+fn write_split_table(table: HdmaTable, unoptimised_size: usize, settings: &RipSettings) -> (String, TableStats) {
+    let label = table.label();
+    let row_count = table.row_count();
+    // Binary output is left in one piece, whoever inserts it knows best where to put it.
+    let data = table.bytes();
+    let scanline_size = table.scanline_size();
+    let parts = table.split(settings.split_size);
+    log::info!("{}", tr!(Message::TableSplit, label, parts.len(), settings.split_size));
+
+    let byte_size = parts.iter().map(|part| part.byte_size()).sum();
+    let part_labels: Vec<(String, usize)> = parts.iter().map(|part| (part.label(), part.scanline_count())).collect();
+
+    let mut output = codegen::split_table(&label, &part_labels, &settings.number_format);
+    for part in parts {
+        log::debug!("{}: {} rows, {} bytes", part.label(), part.row_count(), part.byte_size());
+        output.push_str(&format!("\n{}", part.write_table(&settings.number_format)));
+    }
+
+    let stats = TableStats {
+        name: label,
+        row_count: row_count,
+        byte_size: byte_size,
+        unoptimised_size: unoptimised_size,
+        data: data,
+        scanline_size: Some(scanline_size),
+        mode: settings.mode
+    };
+
+    return (output, stats);
+}
+
+// Samples the colours of the image and runs them through every filter.
+fn sample_colours(settings: &RipSettings, image: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    blend: Option<(&RgbaImage, f32)>) -> Vec<Rgb<u8>> {
+    let (_, colours) = sample_colour_stages(settings, image, blend);
+    return colours;
+}
+
+// Same as above but also gets the colours before they're brought down to the exact colours of the table (i.e. before
+// they're snapped to a palette, posterized or dithered), e.g. to compare them with each other.
+fn sample_colour_stages(settings: &RipSettings, image: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    blend: Option<(&RgbaImage, f32)>) -> (Vec<Rgb<u8>>, Vec<Rgb<u8>>) {
+    // The reserved scanlines aren't sampled, the gradient is fit into the ones below them instead.
+    let reserved_lines = if settings.interlace.is_some() {settings.reserve_top * 2} else {settings.reserve_top};
+    let sample_settings = RipSettings { height: settings.height.saturating_sub(reserved_lines).max(1), ..settings.clone() };
+    let mut colours = get_rgb_from_image(image, &sample_settings);
+    if let Some((other, factor)) = blend {
+        colours = filters::mix(&colours, &get_rgb_from_image(other, &sample_settings), factor);
+    }
+    match settings.interlace {
+        Some(Interlace::Doubled) => colours = filters::merge_fields(colours),
+        Some(Interlace::Even) => colours = filters::field(colours, false),
+        Some(Interlace::Odd) => colours = filters::field(colours, true),
+        None => {}
+    }
+    if settings.line_offset != 0 {
+        colours = filters::shift(colours, settings.line_offset);
+    }
+    if let Some(filter) = settings.denoise {
+        colours = filter.apply(colours);
+    }
+    if let Some(sigma) = settings.smooth {
+        colours = filters::smooth(colours, sigma);
+    }
+    if settings.gamma != 1.0 {
+        colours = filters::gamma(colours, settings.gamma);
+    }
+    if settings.brightness != 0 || settings.contrast != 0 || settings.saturation != 0 {
+        colours = adjust_colours(colours, settings);
+    }
+    if settings.hue_shift != 0 {
+        colours = shift_hue(colours, settings.hue_shift);
+    }
+    if settings.compensate_half {
+        colours = filters::double(colours);
+    }
+    if settings.stride > 1 {
+        colours = filters::stride(colours, settings.stride as usize);
+    }
+    // Short tables are padded with the final colour so that the gradient reaches the bottom of the screen.
+    if let (Some(pad_to), Some(&last)) = (settings.pad_to, colours.last()) {
+        colours.resize(colours.len().max(pad_to.saturating_sub(settings.reserve_top) as usize), last);
+    }
+    if let Some(blend_rows) = settings.loop_blend {
+        colours = filters::blend_loop(colours, blend_rows as usize);
+    }
+    if let Some(space) = settings.merge_space {
+        colours = filters::merge_similar(colours, space, settings.merge_tolerance);
+    }
+    let original = colours.clone();
+    if let (Some(palette), HdmaColourMode::CgRam) = (&settings.palette, settings.mode) {
+        colours = filters::snap_to_palette(colours, palette);
+    }
+    // Both of these have to come last since they work with the exact colours of the table.
+    match (settings.max_levels, settings.cluster_levels) {
+        (Some(levels), true) => colours = filters::cluster_levels(colours, levels),
+        (Some(levels), false) => colours = filters::posterize(colours, levels),
+        (None, _) => {}
+    }
+    if let Some(dither) = settings.dither {
+        colours = dither.apply(colours);
+    }
+
+    return (finish_colours(settings, original), finish_colours(settings, colours));
+}
+
+// Puts the reserved scanlines in front of the colours and inverts them.
+fn finish_colours(settings: &RipSettings, mut colours: Vec<Rgb<u8>>) -> Vec<Rgb<u8>> {
+    if settings.reserve_top > 0 {
+        let fill = settings.reserve_colour.or_else(|| colours.first().copied()).unwrap_or(Rgb([0, 0, 0]));
+        colours.splice(0..0, std::iter::repeat(fill).take(settings.reserve_top as usize));
+    }
+    // The SNES only keeps the upper five bits so inverting the whole byte inverts them as well.
+    if settings.invert {
+        colours = filters::invert(colours);
+    }
+
+    return colours;
+}
+
+// Lists the colour of every scanline as it's sampled and as the SNES shows it (a CGRAM colour), e.g. for other tools.
+// The sampled colour is the one before it's posterized or dithered, the CGRAM colour the one which ends up in the table.
+pub fn colours_to_csv(settings: &RipSettings, image: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> String {
+    let mut output = String::from("scanline,red,green,blue,snes\n");
+    let (original, colours) = sample_colour_stages(settings, image, None);
+
+    for (scanline, (colour, snes_colour)) in original.into_iter().zip(colours).enumerate() {
+        output.push_str(&format!("{},{},{},{},0x{:04X}\n", scanline, colour.0[0], colour.0[1], colour.0[2], to_cgram_colour(snes_colour)));
+    }
+
+    return output;
+}
+
+// The colour of every scanline as it's written to CGRAM, e.g. for palette files.
+pub fn cgram_colours(settings: &RipSettings, image: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> Vec<u16> {
+    return sample_colours(settings, image, None).into_iter().map(to_cgram_colour).collect();
+}
+
+// The colour of every scanline before and after it's posterized or dithered, both with eight bits per channel.
+pub fn compared_colours(settings: &RipSettings, image: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> (Vec<Rgb<u8>>, Vec<Rgb<u8>>) {
+    return sample_colour_stages(settings, image, None);
+}
+
+// The colour of every scanline before it's brought down to five bits per channel.
+pub fn sampled_colours(settings: &RipSettings, image: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> Vec<Rgb<u8>> {
+    return sample_colours(settings, image, None);
+}
+
 // That one creates a string from the ASM file.
-pub fn write_table(height: u32, x_pos: u32, y_start: u32, y_end: u32, mode: HdmaColourMode,
-    cgram_index: Option<u8>, image: ImageBuffer<Rgb<u8>, Vec<u8>>, optimise: bool) -> String {
+pub fn write_table(settings: &RipSettings, image: ImageBuffer<Rgba<u8>, Vec<u8>>, progress: &ProgressBar) -> RippedTables {
+    return write_blended_table(settings, &image, None, progress);
+}
 
-    match mode {
-        HdmaColourMode::FixedClourThree => {
-            let colours = get_rgb_from_image(image, x_pos, y_start, y_end, height);
-            let hdma_tables = create_mode_0_tables(colours);
+// Same as above but the colours can be blended with those of a second image which is sampled the same way.
+// The factor goes from 0 (only the first image) to 1 (only the second image).
+pub fn write_blended_table(settings: &RipSettings, image: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    blend: Option<(&RgbaImage, f32)>, progress: &ProgressBar) -> RippedTables {
+    let mode = settings.mode;
 
-            let mut output = String::new();
+    // Sampling the image counts as a step as well.
+    progress.set_length(match mode {
+        _ if settings.driver == OutputDriver::Irq => 2,
+        HdmaColourMode::FixedClourThree => 4,
+        HdmaColourMode::FixedClourTwo => 3,
+        HdmaColourMode::CgRam if settings.cgram_pair => 3,
+        HdmaColourMode::CgRam => 1 + settings.cgram_columns.len().max(1) as u64,
+        HdmaColourMode::BigGradient | HdmaColourMode::Brightness | HdmaColourMode::Mosaic
+            | HdmaColourMode::Window | HdmaColourMode::ColourMath => 2
+    });
+    progress.set_message("sampling");
 
-            for mut table in hdma_tables {
-                if optimise {
-                    table.coagulate();
-                }
-                output.push_str(&format!("{}\n", table.write_table()));
-            }
+    let colours = sample_colours(settings, image, blend);
+    progress.inc(1);
+
+    let mut output = RippedTables { text: String::new(), mode: mode, stats: Vec::new() };
 
-            return output;
+    if settings.driver == OutputDriver::Irq {
+        progress.set_message("irq_table");
+        let (text, stats) = write_irq_table(colours, settings);
+        output.text = text;
+        output.stats.push(stats);
+        progress.inc(1);
+        return output;
+    }
+
+    // Equal channels share a table instead (only if it's been asked for since the labels change).
+    // Grayscale gradients only need a single table for all three channels which is done on its own.
+    let fixed_colour = (mode == HdmaColourMode::FixedClourThree || mode == HdmaColourMode::FixedClourTwo) && settings.channels == 0xE0;
+    let grayscale = (settings.merge_channels || settings.merge_grayscale) && is_grayscale(&colours);
+    let merged_channels = if settings.merge_channels {find_equal_channels(&colours)} else {None};
+    if fixed_colour && (grayscale || merged_channels.is_some()) {
+        let (unmerged_size, unmerged_count) = if mode == HdmaColourMode::FixedClourThree {
+            (IntoIterator::into_iter(create_mode_0_tables(colours.clone())).map(|table| written_size(table, settings.optimise)).sum::<usize>(), 3)
         }
-        HdmaColourMode::FixedClourTwo => {
-            let colours = get_rgb_from_image(image, x_pos, y_start, y_end, height);
-            let hdma_tables = create_mode_2_table(colours);
+        else {
+            (IntoIterator::into_iter(create_mode_2_table(colours.clone())).map(|table| written_size(table, settings.optimise)).sum::<usize>(), 2)
+        };
 
-            let mut output = String::new();
+        let (tables, merged_count) = match merged_channels {
+            Some(channels) if !grayscale => (Vec::from(create_merged_tables(colours, channels)), 2),
+            _ => (vec![create_grayscale_table(colours)], 3)
+        };
+        let table_count = tables.len();
 
-            for mut table in hdma_tables {
-                if optimise {
-                    table.coagulate();
-                }
-                output.push_str(&format!("{}\n", table.write_table()));
-            }
+        for table in tables {
+            let (text, stats) = finish_table(table, settings, false, progress);
+            output.text.push_str(&format!("{}\n", text));
+            output.stats.push(stats);
+        }
 
-            return output;
+        let merged_size: usize = output.stats.iter().map(|stats| stats.byte_size).sum();
+        log::info!("{}", tr!(Message::ChannelsMerged, output.stats[0].name, merged_count,
+            unmerged_size as isize - merged_size as isize, unmerged_count - table_count));
+        return output;
+    }
+
+    match mode {
+        HdmaColourMode::FixedClourThree | HdmaColourMode::FixedClourTwo if settings.channels != 0xE0 => {
+            for table in create_channel_tables(colours, settings.channels, mode == HdmaColourMode::FixedClourTwo) {
+                let (text, stats) = finish_table(table, settings, false, progress);
+                output.text.push_str(&format!("{}\n", text));
+                output.stats.push(stats);
+            }
+        }
+        HdmaColourMode::FixedClourThree => {
+            for table in create_mode_0_tables(colours) {
+                let (text, stats) = finish_table(table, settings, false, progress);
+                output.text.push_str(&format!("{}\n", text));
+                output.stats.push(stats);
+            }
+        }
+        HdmaColourMode::FixedClourTwo => {
+            for table in create_mode_2_table(colours) {
+                let (text, stats) = finish_table(table, settings, false, progress);
+                output.text.push_str(&format!("{}\n", text));
+                output.stats.push(stats);
+            }
         }
         HdmaColourMode::BigGradient => {
-            let colours = get_rgb_from_image(image, x_pos, y_start, y_end, height);
-            let mut hdma_table = create_big_gradient_table(colours);
+            let (text, stats) = finish_table(create_big_gradient_table(colours), settings, true, progress);
+            output.text = text;
 
-            if optimise {
-                hdma_table.coagulate_repeat();
+            // Split tables are made of several tables, these have to be walked with the continue routine instead.
+            match settings.scroll_routine {
+                Some(_) if stats.byte_size > settings.split_size => log::warn!("{}", tr!(Message::ScrollRoutineSplit, stats.name)),
+                Some((scroll, ram)) => output.text.push_str(&format!("\n{}", codegen::scroll_routine(&stats.name, scroll, ram, settings.scanlines))),
+                None => {}
             }
+            output.stats.push(stats);
+        }
+        // Every column is sampled on its own, the colours from XPOS aren't used at all.
+        HdmaColourMode::CgRam if !settings.cgram_columns.is_empty() => {
+            let mut channels = Vec::new();
+            let mut tables = String::new();
+
+            for (x_pos, cgram_index) in &settings.cgram_columns {
+                let column_settings = RipSettings {
+                    x_pos: *x_pos,
+                    x_end: *x_pos,
+                    cgram_index: Some(*cgram_index),
+                    label_suffix: format!("_cgram{:02X}{}", cgram_index, settings.label_suffix),
+                    ..settings.clone()
+                };
+                let colours = sample_colours(&column_settings, image, blend);
+                let (text, stats) = finish_table(create_cgram_table(colours, column_settings.cgram_index), &column_settings, false, progress);
 
-            return hdma_table.write_table();
+                // The colour index is written twice to $2121 (the first write only sets up the register), followed
+                // by the colour which is written twice to $2122.
+                channels.push(codegen::Channel { label: stats.name.clone(), transfer_mode: 0x03, register: 0x21 });
+                tables.push_str(&format!("{}\n", text));
+                output.stats.push(stats);
+            }
+
+            output.text = codegen::channel_comments(&channels) + &tables;
         }
-        HdmaColourMode::CgRam => {
-            let colours = get_rgb_from_image(image, x_pos, y_start, y_end, height);
-            let mut hdma_table = create_cgram_table(colours, cgram_index);
+        // The second colour comes from the area right next to the first one.
+        HdmaColourMode::CgRam if settings.cgram_pair => {
+            let width = settings.x_end - settings.x_pos + 1;
+            let second_settings = RipSettings { x_pos: settings.x_pos + width, x_end: settings.x_end + width, ..settings.clone() };
+            let second_colours = sample_colours(&second_settings, image, blend);
+            let tables = create_cgram_pair_tables(colours, second_colours, settings.cgram_index.unwrap_or(0));
 
-            if optimise {
-                hdma_table.coagulate();
+            let mut channels = Vec::new();
+            let mut text = String::new();
+            for (table, (transfer_mode, register)) in tables.into_iter().zip([(0x03, 0x21), (0x02, 0x22)]) {
+                let (table_text, stats) = finish_table(table, settings, false, progress);
+                channels.push(codegen::Channel { label: stats.name.clone(), transfer_mode: transfer_mode, register: register });
+                text.push_str(&format!("{}\n", table_text));
+                output.stats.push(stats);
             }
 
-            return hdma_table.write_table();
+            output.text = codegen::channel_comments(&channels) + &text;
+        }
+        HdmaColourMode::CgRam => {
+            let (text, stats) = finish_table(create_cgram_table(colours, settings.cgram_index), settings, false, progress);
+            output.text = text;
+            output.stats.push(stats);
+        }
+        HdmaColourMode::Brightness => {
+            let (text, stats) = finish_table(create_brightness_table(colours, settings.brightness_scale), settings, false, progress);
+            output.text = text;
+            output.stats.push(stats);
         }
+        HdmaColourMode::Mosaic => {
+            let (text, stats) = finish_table(create_mosaic_table(colours, settings.mosaic_layers), settings, false, progress);
+            output.text = text;
+            output.stats.push(stats);
+        }
+        HdmaColourMode::Window => {
+            let (text, stats) = finish_table(window::create_window_table(image, settings), settings, false, progress);
+            output.text = text;
+            output.stats.push(stats);
+        }
+        HdmaColourMode::ColourMath => {
+            let (text, stats) = finish_table(create_colour_math_table(colours, settings.math_threshold, settings.math_values), settings, false, progress);
+            output.text = text;
+            output.stats.push(stats);
+        }
+    }
+
+    return output;
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    // The settings of the interactive mode for an image which is a single column of this height.
+    pub fn settings(height: u32, mode: HdmaColourMode) -> RipSettings {
+        return RipSettings {
+            height: height,
+            scanlines: 224,
+            interlace: None,
+            line_offset: 0,
+            reserve_top: 0,
+            reserve_colour: None,
+            x_pos: 0,
+            x_end: 0,
+            sample_radius: 0,
+            denoise: None,
+            smooth: None,
+            dither: None,
+            max_levels: None,
+            cluster_levels: false,
+            palette: None,
+            merge_space: None,
+            merge_tolerance: filters::JUST_NOTICEABLE_DIFFERENCE,
+            resample: Resample::Nearest,
+            mirror: None,
+            stride: 1,
+            extrapolate: false,
+            pad_to: None,
+            loop_blend: None,
+            hold_colour: None,
+            hold_transparent: false,
+            gamma: 1.0,
+            compensate_half: false,
+            invert: false,
+            hue_shift: 0,
+            brightness: 0,
+            contrast: 0,
+            saturation: 0,
+            y_start: 0,
+            y_end: height,
+            ranges: Vec::new(),
+            range_filler: 0,
+            mode: mode,
+            extra_modes: Vec::new(),
+            cgram_index: None,
+            cgram_columns: Vec::new(),
+            cgram_pair: false,
+            brightness_scale: 1.0,
+            mosaic_layers: 0x0F,
+            merge_channels: false,
+            merge_grayscale: true,
+            channels: 0xE0,
+            math_values: (0x20, 0x00),
+            math_threshold: 0x80,
+            split_size: 0x8000,
+            driver: OutputDriver::Hdma,
+            scroll_routine: None,
+            optimise: true,
+            number_format: NumberFormat::default(),
+            write_mode: None,
+            big_endian: false,
+            swap_words: false,
+            label_prefix: String::new(),
+            label_suffix: String::new()
+        };
+    }
+
+    // The stats of a table just like finish_table makes them, without the settings.
+    fn table_stats(table: &HdmaTable, mode: HdmaColourMode) -> TableStats {
+        return TableStats {
+            name: table.label(),
+            row_count: table.row_count(),
+            byte_size: table.byte_size(),
+            unoptimised_size: table.byte_size(),
+            data: table.bytes(),
+            scanline_size: Some(table.scanline_size()),
+            mode: mode
+        };
+    }
+
+    // 200 lines of the same colour need a repeat row of $80 lines followed by one of 72 lines. Neither of them may be
+    // read as a continuous row.
+    #[test]
+    fn read_rows_with_long_flat_region() {
+        let colours: Vec<Rgb<u8>> = std::iter::repeat(Rgb([80, 80, 80])).take(200)
+            .chain((0..10).map(|level| Rgb([level * 17, level * 17, level * 17]))).collect();
+        let mut table = create_brightness_table(colours.clone(), 1.0);
+        table.coagulate();
+        let stats = table_stats(&table, HdmaColourMode::Brightness);
+
+        assert_eq!(&stats.data[..2], &[0x80, to_brightness(colours[0])]);
+        assert_eq!(&stats.data[2..4], &[72, to_brightness(colours[0])]);
+        let expected: Vec<u8> = colours.iter().map(|colour| to_brightness(*colour)).collect();
+        let values: Vec<u8> = stats.scanline_values().iter().map(|data| data[0]).collect();
+        assert_eq!(values, expected);
+    }
+
+    // Big gradients go up to $FF lines per row and don't have got continuous rows at all.
+    #[test]
+    fn read_rows_of_big_gradient() {
+        let colours: Vec<Rgb<u8>> = std::iter::repeat(Rgb([255, 0, 0])).take(0x90)
+            .chain(std::iter::repeat(Rgb([0, 0, 255])).take(0x30)).collect();
+        let mut table = create_big_gradient_table(colours.clone());
+        table.coagulate_repeat();
+        let stats = table_stats(&table, HdmaColourMode::BigGradient);
+
+        assert_eq!(stats.data[0], 0x90);
+        let values = stats.scanline_values();
+        assert_eq!(values.len(), colours.len());
+        assert_eq!(values[0x8F], &[0x3F, 0x40, 0x80]);
+        assert_eq!(values[0x90], &[0x20, 0x40, 0x9F]);
+    }
+
+    // A CGRAM table with long continuous rows (which have to be split) and flat regions, read back from its bytes and
+    // from the written ASM file.
+    #[test]
+    fn read_rows_matches_written_table() {
+        let colours: Vec<Rgb<u8>> = (0..300u32).map(|line| match line {
+            100..=199 => Rgb([0, 128, 255]),
+            _ => Rgb([(line * 8 % 256) as u8, (line * 24 % 256) as u8, 255 - (line * 8 % 256) as u8])
+        }).collect();
+        let mut table = create_cgram_table(colours.clone(), Some(3));
+        table.coagulate();
+        let stats = table_stats(&table, HdmaColourMode::CgRam);
+        assert_eq!(stats.data[0], 0x80 + 100);
+
+        let expected: Vec<Vec<u8>> = colours.iter().map(|colour| {
+            let cgram_colour = to_cgram_colour(*colour);
+            vec![0x00, 3, (cgram_colour & 0x00FF) as u8, (cgram_colour >> 8) as u8]
+        }).collect();
+        let values: Vec<Vec<u8>> = stats.scanline_values().iter().map(|data| data.to_vec()).collect();
+        assert_eq!(values, expected);
+        assert_eq!(crate::hdma::tests::assemble(&table.write_table(&NumberFormat::default())), stats.data);
+    }
+
+    // A grayscale gradient gets a single table with all three channel bits even if the channels aren't merged, unless the
+    // labels have to stay the same.
+    #[test]
+    fn grayscale_gradient_is_a_single_table() {
+        let image = ImageBuffer::from_fn(1, 224, |_, y| Rgba([y as u8, y as u8, y as u8, 0xFF]));
+
+        let tables = write_table(&settings(224, HdmaColourMode::FixedClourTwo), image.clone(), &ProgressBar::hidden());
+        let names: Vec<&str> = tables.stats.iter().map(|stats| stats.name.as_str()).collect();
+        assert_eq!(names, vec!["grayscale_table"]);
+        assert!(tables.stats[0].scanline_values().iter().all(|values| values[0] & 0xE0 == 0xE0));
+
+        let fixed_labels = RipSettings { merge_grayscale: false, ..settings(224, HdmaColourMode::FixedClourTwo) };
+        assert_eq!(write_table(&fixed_labels, image, &ProgressBar::hidden()).stats.len(), 2);
+    }
+
+    // The default keeps the rows as they are if the image is as tall as the table and repeats every row equally often
+    // if it's stretched.
+    #[test]
+    fn nearest_resampling() {
+        let rows: Vec<Rgb<u8>> = (0..112).map(|row| Rgb([row as u8, 0, 0])).collect();
+
+        assert_eq!(resample_rows(&rows, 112, Resample::Nearest), rows);
+        let stretched: Vec<Rgb<u8>> = rows.iter().flat_map(|row| [*row, *row]).collect();
+        assert_eq!(resample_rows(&rows, 224, Resample::Nearest), stretched);
     }
 }
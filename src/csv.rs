@@ -0,0 +1,71 @@
+// Tables from a text file with the values of each scanline, e.g. for effect curves which have been computed elsewhere.
+// Every line is a scanline and its values are separated by commas or spaces. They can be decimal or hexadecimal
+// (prefixed with $ or 0x) and negative values are written as two's complement. Empty lines and lines starting with
+// ; or # are skipped.
+use crate::error::RipperError;
+use crate::hdma::{HdmaTable, HdmaRow, HdmaWriteMode};
+use crate::messages::Message;
+
+// Reads the values and puts them into a table.
+// Each value is a byte or, if words or longs are written, a word or a long. Without a row size, it's taken from the first scanline.
+pub fn create_csv_table(input_name: &str, text: &str, row_size: Option<usize>, write_mode: HdmaWriteMode) -> Result<HdmaTable, RipperError> {
+    let value_size = match write_mode {
+        HdmaWriteMode::Bytes => 1,
+        HdmaWriteMode::Words => 2,
+        HdmaWriteMode::Longs => 3
+    };
+    let mut row_size = row_size;
+    let mut rows = Vec::new();
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid_line = || RipperError::BadArguments(tr!(Message::InvalidCsvLine, line_index + 1, input_name, line));
+        let values = line.split(|character: char| character == ',' || character.is_whitespace())
+            .filter(|value| !value.is_empty())
+            .map(|value| parse_value(value, value_size).ok_or_else(invalid_line))
+            .collect::<Result<Vec<[u8; 4]>, RipperError>>()?;
+        let data: Vec<u8> = values.iter().flat_map(|value| value[..value_size].to_vec()).collect();
+
+        // The values have to fill up a row exactly (words and longs are rounded up to a whole value).
+        let size = *row_size.get_or_insert(data.len());
+        if !(1..=4).contains(&size) || values.len() != (size + value_size - 1) / value_size {
+            return Err(invalid_line());
+        }
+        // The last value only gets the bytes which are left in the row, e.g. the second long of four bytes is a byte.
+        if data[size..].iter().any(|value| *value != 0) {
+            return Err(invalid_line());
+        }
+
+        rows.push(HdmaRow::new_scanline(&data));
+    }
+
+    match row_size {
+        Some(size) if !rows.is_empty() => Ok(HdmaTable::new_real_table(rows, size, write_mode, "csv_table")),
+        _ => Err(RipperError::BadArguments(tr!(Message::EmptyCsv, input_name)))
+    }
+}
+
+// A single value in little endian, no matter whether it's a byte, a word or a long.
+fn parse_value(text: &str, value_size: usize) -> Option<[u8; 4]> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text)
+    };
+    let magnitude = match digits.strip_prefix('$').or_else(|| digits.strip_prefix("0x")) {
+        Some(hex_digits) => i64::from_str_radix(hex_digits, 16).ok()?,
+        None => digits.parse::<i64>().ok()?
+    };
+    let value = if negative {-magnitude} else {magnitude};
+
+    // Anything from the smallest signed to the largest unsigned value.
+    let bits = value_size as u32 * 8;
+    if value < -(1 << (bits - 1)) || value >= 1 << bits {
+        return None;
+    }
+
+    return Some((value as u32).to_le_bytes());
+}
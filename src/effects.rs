@@ -0,0 +1,214 @@
+// Tables which aren't ripped from an image but generated from a few parameters instead.
+// They go through the same optimisation and writer as the gradients.
+use crate::expression::Expression;
+use crate::hdma::{HdmaTable, HdmaRow, HdmaWriteMode, NumberFormat};
+
+use std::str::FromStr;
+
+// The shape of a single period of a wave. Each of them goes from -1 to 1 (except for expressions which can be
+// anything) and starts at 0.
+#[derive(Clone)]
+pub enum Shape {
+    Sine,
+    Triangle,
+    Sawtooth,
+    Expression(Expression)
+}
+
+// Expressions are entered separately.
+impl FromStr for Shape {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "sine" => Ok(Shape::Sine),
+            "triangle" => Ok(Shape::Triangle),
+            "sawtooth" => Ok(Shape::Sawtooth),
+            _ => Err(())
+        }
+    }
+}
+
+impl Shape {
+    pub fn name(&self) -> &str {
+        match self {
+            Shape::Sine => "sine",
+            Shape::Triangle => "triangle",
+            Shape::Sawtooth => "sawtooth",
+            Shape::Expression(expression) => expression.text()
+        }
+    }
+
+    // The position is counted in periods.
+    fn evaluate(&self, position: f64, scanline: u32) -> f64 {
+        match self {
+            Shape::Sine => (position * std::f64::consts::TAU).sin(),
+            Shape::Triangle => (position * std::f64::consts::TAU).sin().asin() / std::f64::consts::FRAC_PI_2,
+            Shape::Sawtooth => (position + 0.5).rem_euclid(1.0) * 2.0 - 1.0,
+            Shape::Expression(expression) => expression.evaluate(position, scanline)
+        }
+    }
+}
+
+// A wave which goes over a range of scanlines. Outside of that range, the value is zero.
+pub struct Wave {
+    pub shape: Shape,
+    // How far the wave goes up and down.
+    pub amplitude: f64,
+    // How many scanlines a single period takes up.
+    pub wavelength: f64,
+    // Where the wave starts (in degrees).
+    pub phase: f64,
+    // The first scanline of the wave and the scanline after the last one.
+    pub start: u32,
+    pub end: u32
+}
+
+impl Wave {
+    pub fn value(&self, scanline: u32) -> f64 {
+        if scanline < self.start || scanline >= self.end {
+            return 0.0;
+        }
+
+        let position = (scanline - self.start) as f64 / self.wavelength + self.phase / 360.0;
+        return self.amplitude * self.shape.evaluate(position, scanline);
+    }
+}
+
+// A generated table along with the register it's written to.
+pub struct EffectTable {
+    pub table: HdmaTable,
+    pub register: u16,
+    // The transfer mode of $43x0, e.g. 2 for registers which are written twice.
+    pub transfer_mode: u8
+}
+
+// The register of the horizontal scroll value of a BG, $210D for BG1 up to $2113 for BG4.
+// The vertical scroll value always comes right after it.
+fn scroll_register(layer: u8, vertical: bool) -> u16 {
+    return 0x210D + (layer as u16 - 1) * 2 + if vertical {1} else {0};
+}
+
+// Writes the scroll offset of every scanline for heat haze, underwater effects and the like.
+// The scroll registers are written twice (low byte first) so the table has to be transferred with mode 2.
+// Since HDMA overwrites the scroll value, the game has to add the position of the layer to the table (or leave the
+// layer where it is).
+pub fn create_scroll_wave_table(wave: &Wave, layer: u8, vertical: bool, height: u32) -> EffectTable {
+    let mut output = HdmaTable::new_real_table(Vec::new(), 2, HdmaWriteMode::Words, "scroll_wave_table");
+
+    for scanline in 0..height {
+        let offset = wave.value(scanline).round() as i16;
+        output.push(HdmaRow::new_scanline(&offset.to_le_bytes()));
+    }
+
+    return EffectTable { table: output, register: scroll_register(layer, vertical), transfer_mode: 2 };
+}
+
+// The floor of a Mode 7 perspective which starts at the horizon and gets closer towards the bottom of the screen.
+pub struct Perspective {
+    // The first scanline of the floor.
+    pub horizon: u32,
+    // The zoom at the bottom of the table, 1 being the size of the tilemap.
+    pub scale: f64,
+    // The rotation of the floor (in degrees), only the scaling is written without it.
+    pub angle: Option<f64>
+}
+
+impl Perspective {
+    // How far each scanline is zoomed out: The distance to the floor (and thus the zoom) goes up the closer the
+    // scanline is to the horizon. The scanlines above the horizon are left at a zoom of 1, usually a different BG
+    // mode is used there anyway.
+    fn zoom(&self, scanline: u32, height: u32) -> f64 {
+        if scanline < self.horizon {
+            return 1.0;
+        }
+
+        return self.scale * (height - self.horizon) as f64 / (scanline - self.horizon + 1) as f64;
+    }
+}
+
+// The Mode 7 matrix is made of signed 8.8 fixed point numbers. The zoom right at the horizon easily exceeds them
+// so these are capped.
+fn to_fixed_point(value: f64) -> [u8; 2] {
+    return ((value * 256.0).round().max(i16::MIN as f64).min(i16::MAX as f64) as i16).to_le_bytes();
+}
+
+// Writes the Mode 7 matrix of every scanline.
+// Without a rotation, only A ($211B) and D ($211E) are needed which are the same so there is one table for both of them
+// (but it still needs two HDMA channels). With a rotation, A and B as well as C and D are written together with
+// transfer mode 3 instead.
+pub fn create_mode7_tables(perspective: &Perspective, height: u32) -> Vec<EffectTable> {
+    match perspective.angle {
+        None => {
+            let mut output = HdmaTable::new_real_table(Vec::new(), 2, HdmaWriteMode::Words, "mode7_scale_table");
+
+            for scanline in 0..height {
+                output.push(HdmaRow::new_scanline(&to_fixed_point(perspective.zoom(scanline, height))));
+            }
+
+            return vec![
+                EffectTable { table: output, register: 0x211B, transfer_mode: 2 }
+            ];
+        }
+        Some(angle) => {
+            let mut ab_table = HdmaTable::new_real_table(Vec::new(), 4, HdmaWriteMode::Words, "mode7_ab_table");
+            let mut cd_table = HdmaTable::new_real_table(Vec::new(), 4, HdmaWriteMode::Words, "mode7_cd_table");
+            let (sine, cosine) = angle.to_radians().sin_cos();
+
+            for scanline in 0..height {
+                let zoom = perspective.zoom(scanline, height);
+                let [a_low, a_high] = to_fixed_point(cosine * zoom);
+                let [b_low, b_high] = to_fixed_point(sine * zoom);
+                let [c_low, c_high] = to_fixed_point(-sine * zoom);
+
+                ab_table.push(HdmaRow::new_scanline(&[a_low, a_high, b_low, b_high]));
+                cd_table.push(HdmaRow::new_scanline(&[c_low, c_high, a_low, a_high]));
+            }
+
+            return vec![
+                EffectTable { table: ab_table, register: 0x211B, transfer_mode: 3 },
+                EffectTable { table: cd_table, register: 0x211D, transfer_mode: 3 }
+            ];
+        }
+    }
+}
+
+// Writes a wave to any register, either as a byte (transfer mode 0) or as a word which is written twice to the same
+// register (transfer mode 2). The wave goes around the offset, values which don't fit wrap around.
+pub fn create_wave_table(wave: &Wave, offset: f64, register: u16, words: bool, height: u32) -> EffectTable {
+    let mut output = if words {
+        HdmaTable::new_real_table(Vec::new(), 2, HdmaWriteMode::Words, "wave_table")
+    }
+    else {
+        HdmaTable::new_real_table(Vec::new(), 1, HdmaWriteMode::Bytes, "wave_table")
+    };
+
+    for scanline in 0..height {
+        let value = (offset + wave.value(scanline)).round() as i64;
+        output.push(HdmaRow::new_scanline(&(value as u16).to_le_bytes()[..if words {2} else {1}]));
+    }
+
+    return EffectTable { table: output, register: register, transfer_mode: if words {2} else {0} };
+}
+
+// Optimises a generated table (if requested) and writes it down together with how it's transferred.
+pub fn write_effect_table(effect: EffectTable, optimise: bool, format: &NumberFormat) -> String {
+    let mut output = format!("; Write to ${:04X} with transfer mode {}.\n", effect.register, effect.transfer_mode);
+    output.push_str(&write_generated_table(effect.table, optimise, format));
+    return output;
+}
+
+// Same as above but for tables where the register isn't known.
+// Only the optimisation adds the termination byte so unoptimised tables get it here.
+pub fn write_generated_table(mut table: HdmaTable, optimise: bool, format: &NumberFormat) -> String {
+    if optimise {
+        table.coagulate();
+    }
+    else {
+        table.push(HdmaRow::Finish);
+    }
+
+    log::debug!("{}: {} rows, {} bytes", table.label(), table.row_count(), table.byte_size());
+
+    return table.write_table(format);
+}
@@ -0,0 +1,79 @@
+use crate::json;
+use crate::messages::{self, Message};
+
+use std::fmt;
+
+// Everything which can go wrong while ripping a gradient.
+// Each kind of error has got its own exit code so that build systems can react to them.
+#[derive(Debug)]
+pub enum RipperError {
+    BadArguments(String),
+    UnreadableImage(String),
+    OutOfRange(String),
+    WriteFailure(String),
+    OutdatedOutput(String),
+    AssemblyFailed(String),
+}
+
+// How errors are reported: Either as a plain message or as a JSON object.
+#[derive(Copy, Clone, PartialEq)]
+pub enum ErrorFormat {
+    Human,
+    Json
+}
+
+impl RipperError {
+    // Malformed command lines count as bad arguments as well, exit code 1 isn't used.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RipperError::BadArguments(_) => 2,
+            RipperError::UnreadableImage(_) => 3,
+            RipperError::OutOfRange(_) => 4,
+            RipperError::WriteFailure(_) => 5,
+            RipperError::OutdatedOutput(_) => 6,
+            RipperError::AssemblyFailed(_) => 7
+        }
+    }
+
+    // The name of the error for the structured diagnostics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RipperError::BadArguments(_) => "bad_arguments",
+            RipperError::UnreadableImage(_) => "unreadable_image",
+            RipperError::OutOfRange(_) => "out_of_range",
+            RipperError::WriteFailure(_) => "write_failure",
+            RipperError::OutdatedOutput(_) => "outdated_output",
+            RipperError::AssemblyFailed(_) => "assembly_failed"
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            RipperError::BadArguments(message) |
+            RipperError::UnreadableImage(message) |
+            RipperError::OutOfRange(message) |
+            RipperError::WriteFailure(message) |
+            RipperError::OutdatedOutput(message) |
+            RipperError::AssemblyFailed(message) => message
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        return format!("{{\"error\":{},\"code\":{},\"message\":{}}}",
+            json::string(self.kind()), self.exit_code(), json::string(self.message()));
+    }
+
+    // Prints the error to stderr in the requested format.
+    pub fn report(&self, format: ErrorFormat) {
+        match format {
+            ErrorFormat::Human => eprintln!("{}: {}", messages::text(Message::ErrorPrefix), self),
+            ErrorFormat::Json => eprintln!("{}", self.to_json())
+        }
+    }
+}
+
+impl fmt::Display for RipperError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
@@ -0,0 +1,277 @@
+// A small formula for custom waves, e.g. "sin(t * 2 * pi) + 0.5 * sin(t * 6 * pi)".
+// It knows the four basic arithmetic operations, % (remainder), ^ (power), brackets, a few functions and the
+// following variables:
+// * t is the position within the wave in periods (i.e. it goes up by 1 every wavelength), including the phase.
+// * y is the scanline.
+// * pi is, well, pi.
+use std::str::FromStr;
+
+#[derive(Clone)]
+pub struct Expression {
+    text: String,
+    root: Node
+}
+
+#[derive(Copy, Clone)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Power
+}
+
+#[derive(Copy, Clone)]
+enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Abs,
+    Sqrt,
+    Floor,
+    Ceil,
+    Round
+}
+
+#[derive(Clone)]
+enum Node {
+    Number(f64),
+    Position,
+    Scanline,
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+    Call(Function, Box<Node>)
+}
+
+impl Expression {
+    // The expression as it has been entered.
+    pub fn text(&self) -> &str {
+        return &self.text;
+    }
+
+    pub fn evaluate(&self, position: f64, scanline: u32) -> f64 {
+        return self.root.evaluate(position, scanline as f64);
+    }
+}
+
+impl FromStr for Expression {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { characters: text.chars().filter(|character| !character.is_whitespace()).collect(), position: 0 };
+        let root = parser.sum().ok_or(())?;
+
+        // Anything left over means that the expression is malformed, e.g. a bracket too many.
+        if parser.position < parser.characters.len() {
+            return Err(());
+        }
+
+        return Ok(Self { text: String::from(text.trim()), root: root });
+    }
+}
+
+impl Node {
+    fn evaluate(&self, position: f64, scanline: f64) -> f64 {
+        match self {
+            Node::Number(value) => *value,
+            Node::Position => position,
+            Node::Scanline => scanline,
+            Node::Negate(node) => -node.evaluate(position, scanline),
+            Node::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(position, scanline), right.evaluate(position, scanline));
+                match operator {
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    Operator::Divide => left / right,
+                    Operator::Remainder => left.rem_euclid(right),
+                    Operator::Power => left.powf(right)
+                }
+            }
+            Node::Call(function, argument) => {
+                let argument = argument.evaluate(position, scanline);
+                match function {
+                    Function::Sin => argument.sin(),
+                    Function::Cos => argument.cos(),
+                    Function::Tan => argument.tan(),
+                    Function::Abs => argument.abs(),
+                    Function::Sqrt => argument.sqrt(),
+                    Function::Floor => argument.floor(),
+                    Function::Ceil => argument.ceil(),
+                    Function::Round => argument.round()
+                }
+            }
+        }
+    }
+}
+
+// A plain recursive descent parser: Sums are made of products, products of powers and so on.
+struct Parser {
+    characters: Vec<char>,
+    position: usize
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        return self.characters.get(self.position).copied();
+    }
+
+    // Skips the character if it's the expected one.
+    fn accept(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            return true;
+        }
+
+        return false;
+    }
+
+    fn sum(&mut self) -> Option<Node> {
+        let mut node = self.product()?;
+
+        loop {
+            let operator = match self.peek() {
+                Some('+') => Operator::Add,
+                Some('-') => Operator::Subtract,
+                _ => return Some(node)
+            };
+            self.position += 1;
+            node = Node::Binary(operator, Box::new(node), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Option<Node> {
+        let mut node = self.unary()?;
+
+        loop {
+            let operator = match self.peek() {
+                Some('*') => Operator::Multiply,
+                Some('/') => Operator::Divide,
+                Some('%') => Operator::Remainder,
+                _ => return Some(node)
+            };
+            self.position += 1;
+            node = Node::Binary(operator, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    // The minus sign binds less than the power so that -2^2 is -4.
+    fn unary(&mut self) -> Option<Node> {
+        if self.accept('-') {
+            return Some(Node::Negate(Box::new(self.unary()?)));
+        }
+
+        return self.power();
+    }
+
+    // Powers go from right to left, i.e. 2^3^2 is 2^9.
+    fn power(&mut self) -> Option<Node> {
+        let base = self.primary()?;
+
+        if self.accept('^') {
+            return Some(Node::Binary(Operator::Power, Box::new(base), Box::new(self.unary()?)));
+        }
+
+        return Some(base);
+    }
+
+    fn primary(&mut self) -> Option<Node> {
+        if self.accept('(') {
+            let node = self.sum()?;
+            return if self.accept(')') {Some(node)} else {None};
+        }
+
+        let start = self.position;
+        match self.peek()? {
+            character if character.is_ascii_digit() || character == '.' => {
+                while self.peek().is_some_and(|character| character.is_ascii_digit() || character == '.') {
+                    self.position += 1;
+                }
+                let number: String = self.characters[start..self.position].iter().collect();
+                return number.parse().ok().map(Node::Number);
+            }
+            character if character.is_ascii_alphabetic() => {
+                while self.peek().is_some_and(|character| character.is_ascii_alphabetic()) {
+                    self.position += 1;
+                }
+                let name: String = self.characters[start..self.position].iter().collect();
+                let function = match name.as_str() {
+                    "t" => return Some(Node::Position),
+                    "y" => return Some(Node::Scanline),
+                    "pi" => return Some(Node::Number(std::f64::consts::PI)),
+                    "sin" => Function::Sin,
+                    "cos" => Function::Cos,
+                    "tan" => Function::Tan,
+                    "abs" => Function::Abs,
+                    "sqrt" => Function::Sqrt,
+                    "floor" => Function::Floor,
+                    "ceil" => Function::Ceil,
+                    "round" => Function::Round,
+                    _ => return None
+                };
+
+                if !self.accept('(') {
+                    return None;
+                }
+                let argument = self.sum()?;
+                return if self.accept(')') {Some(Node::Call(function, Box::new(argument)))} else {None};
+            }
+            _ => return None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(text: &str) -> f64 {
+        return text.parse::<Expression>().unwrap().evaluate(0.25, 10);
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(evaluate("1 + 2 * 3"), 7.0);
+        assert_eq!(evaluate("(1 + 2) * 3"), 9.0);
+        assert_eq!(evaluate("10 - 4 - 3"), 3.0);
+        assert_eq!(evaluate("12 / 3 / 2"), 2.0);
+        assert_eq!(evaluate("7 % 4 * 2"), 6.0);
+        assert_eq!(evaluate("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(evaluate("2 * 3 ^ 2"), 18.0);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(evaluate("-2 ^ 2"), -4.0);
+        assert_eq!(evaluate("(-2) ^ 2"), 4.0);
+        assert_eq!(evaluate("2 ^ -1"), 0.5);
+        assert_eq!(evaluate("--3"), 3.0);
+        assert_eq!(evaluate("4 * -y"), -40.0);
+        assert_eq!(evaluate("-7 % 4"), 1.0);
+    }
+
+    #[test]
+    fn variables_and_functions() {
+        assert_eq!(evaluate("t"), 0.25);
+        assert_eq!(evaluate("y"), 10.0);
+        assert_eq!(evaluate("sin(t * 2 * pi)"), 1.0);
+        assert_eq!(evaluate("floor(2.7) + ceil(2.2) + round(2.5) + abs(-1) + sqrt(16)"), 2.0 + 3.0 + 3.0 + 1.0 + 4.0);
+    }
+
+    #[test]
+    fn malformed_expressions() {
+        for text in ["", "1 +", "(1 + 2", "1 + 2)", "2 * * 3", "sin 1", "sin(1", "foo(1)", "x", "1..2", "3 $ 4"] {
+            assert!(text.parse::<Expression>().is_err(), "{} should be rejected", text);
+        }
+    }
+
+    // Dividing by zero isn't an error, it's up to the caller what to do with the result.
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(evaluate("1 / 0"), f64::INFINITY);
+        assert_eq!(evaluate("-1 / (y - 10)"), f64::NEG_INFINITY);
+        assert!(evaluate("0 / 0").is_nan());
+        assert!(evaluate("5 % 0").is_nan());
+    }
+}
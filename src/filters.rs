@@ -0,0 +1,487 @@
+use std::str::FromStr;
+use image::Rgb;
+
+// Filters which are applied to the sampled colours before they're turned into tables.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Filter {
+    // Replaces every colour with the median of the colours around it (the number is the size of the window).
+    // This gets rid of single scanline outliers (e.g. sprites or compression speckles) without blurring the gradient.
+    Median(usize),
+    // Only keeps the colour stops of the gradient and interpolates between them (the number is how far in 8-bit
+    // steps a colour may be off before it counts as a stop). This rebuilds a clean gradient from a noisy one
+    // (e.g. a JPEG screenshot).
+    Stops(u8)
+}
+
+// Reads a filter as it's entered in the command line, e.g. "median:3".
+impl FromStr for Filter {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (name, value) = match text.split_once(':') {
+            Some((name, value)) => (name, Some(value)),
+            None => (text, None)
+        };
+
+        match (name, value.map(|value| value.parse::<usize>())) {
+            ("median", None) => Ok(Filter::Median(3)),
+            ("median", Some(Ok(size))) if size > 0 => Ok(Filter::Median(size)),
+            // Eight is exactly one step of a five bit colour.
+            ("stops", None) => Ok(Filter::Stops(8)),
+            ("stops", Some(Ok(tolerance))) if tolerance <= u8::MAX as usize => Ok(Filter::Stops(tolerance as u8)),
+            _ => Err(())
+        }
+    }
+}
+
+impl Filter {
+    // How the filter is entered in the command line (and recorded in the header).
+    pub fn name(&self) -> String {
+        match self {
+            Filter::Median(size) => format!("median:{}", size),
+            Filter::Stops(tolerance) => format!("stops:{}", tolerance)
+        }
+    }
+
+    pub fn apply(&self, colours: Vec<Rgb<u8>>) -> Vec<Rgb<u8>> {
+        match self {
+            Filter::Median(size) => median(&colours, *size),
+            Filter::Stops(tolerance) => reinterpolate_stops(&colours, *tolerance)
+        }
+    }
+}
+
+// Blurs the colours along the column with a Gaussian whose standard deviation is the given number of scanlines.
+// This gets rid of dithering in the source art which would otherwise end up as huge continuous tables.
+// Beyond the ends, the first and last colour are simply repeated.
+// A Gaussian which is wider than the whole column hardly blurs it any further which is why sigma is capped at the length
+// of the column. Otherwise, huge values would take forever (and overflow).
+pub fn smooth(colours: Vec<Rgb<u8>>, sigma: u32) -> Vec<Rgb<u8>> {
+    let sigma = sigma.min(colours.len().max(1) as u32) as f64;
+    let radius = (3.0 * sigma) as isize;
+    let weights: Vec<f64> = (-radius..=radius).map(|offset| (-(offset as f64 * offset as f64) / (2.0 * sigma * sigma)).exp()).collect();
+    let total: f64 = weights.iter().sum();
+    let last = colours.len() as isize - 1;
+
+    return (0..colours.len() as isize).map(|index| {
+        let mut sums = [0.0; 3];
+        for (offset, weight) in (-radius..=radius).zip(&weights) {
+            let colour = colours[(index + offset).clamp(0, last) as usize];
+            for (sum, value) in sums.iter_mut().zip(colour.0) {
+                *sum += value as f64 * weight;
+            }
+        }
+
+        Rgb(sums.map(|sum| (sum / total).round() as u8))
+    }).collect();
+}
+
+// Raises every channel to the power of 1/gamma, i.e. a gamma above 1 brightens the darker colours and one below 1
+// darkens them. White and black always stay the same.
+pub fn gamma(colours: Vec<Rgb<u8>>, gamma: f32) -> Vec<Rgb<u8>> {
+    return colours.iter().map(|colour| {
+        Rgb(colour.0.map(|value| ((value as f32 / 255.0).powf(1.0 / gamma) * 255.0).round() as u8))
+    }).collect();
+}
+
+// Doubles every channel (up to white) for colour math which halves its result.
+// Channels which are brighter than half can't be doubled completely so these stay a bit darker than the source.
+pub fn double(colours: Vec<Rgb<u8>>) -> Vec<Rgb<u8>> {
+    return colours.iter().map(|colour| Rgb(colour.0.map(|value| value.saturating_mul(2)))).collect();
+}
+
+// Turns every channel upside down, i.e. black becomes white and vice versa.
+pub fn invert(colours: Vec<Rgb<u8>>) -> Vec<Rgb<u8>> {
+    return colours.iter().map(|colour| Rgb(colour.0.map(|value| 255 - value))).collect();
+}
+
+// How the colours are brought down to the five bits per channel of the SNES.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Dither {
+    // Carries the rounding error of each scanline over to the next one so that the colours alternate between the
+    // neighbouring SNES colours and look like the original colour on average.
+    Diffusion
+}
+
+impl FromStr for Dither {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "diffusion" => Ok(Dither::Diffusion),
+            _ => Err(())
+        }
+    }
+}
+
+impl Dither {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dither::Diffusion => "diffusion"
+        }
+    }
+
+    pub fn apply(&self, colours: Vec<Rgb<u8>>) -> Vec<Rgb<u8>> {
+        match self {
+            Dither::Diffusion => diffuse_error(&colours)
+        }
+    }
+}
+
+// The SNES shows a five bit value as 43210432 (see colour.rs) so the error is measured against that.
+// The output only has got the upper five bits set which is exactly what the table keeps.
+fn diffuse_error(colours: &[Rgb<u8>]) -> Vec<Rgb<u8>> {
+    let shown = |level: i32| (level << 3) | (level >> 2);
+    let mut error = [0i32; 3];
+
+    return colours.iter().map(|colour| {
+        let mut output = [0u8; 3];
+        for (channel, value) in output.iter_mut().enumerate() {
+            let target = colour.0[channel] as i32 + error[channel];
+
+            // Only the level below the target and the one above it come into question.
+            let lower = (target >> 3).clamp(0, 31);
+            let upper = (lower + 1).min(31);
+            let level = if (shown(upper) - target).abs() < (target - shown(lower)).abs() {upper} else {lower};
+
+            error[channel] = target - shown(level);
+            *value = (level << 3) as u8;
+        }
+
+        Rgb(output)
+    }).collect();
+}
+
+// Brings every channel down to the given number of evenly spaced SNES levels (from 2 to 32).
+// Fewer levels mean longer runs of the same value which the table optimisation merges into fewer rows.
+pub fn posterize(colours: Vec<Rgb<u8>>, levels: u32) -> Vec<Rgb<u8>> {
+    let step = 31.0 / (levels - 1) as f64;
+
+    return colours.iter().map(|colour| {
+        Rgb(colour.0.map(|value| {
+            let level = ((value >> 3) as f64 / step).round() * step;
+            (level.round() as u8) << 3
+        }))
+    }).collect();
+}
+
+// Same as above but the levels are chosen by k-means clustering for each channel separately.
+// Unlike evenly spaced levels, they're placed where most of the colours are which keeps the error as low as possible.
+pub fn cluster_levels(colours: Vec<Rgb<u8>>, levels: u32) -> Vec<Rgb<u8>> {
+    let mut output = colours.clone();
+
+    for channel in 0..3 {
+        let values: Vec<u8> = colours.iter().map(|colour| colour.0[channel] >> 3).collect();
+        let centroids = k_means(&values, levels as usize);
+
+        for (colour, value) in output.iter_mut().zip(values) {
+            let nearest = centroids.iter().min_by_key(|centroid| (**centroid as i32 - value as i32).abs()).copied().unwrap_or(value);
+            colour.0[channel] = nearest << 3;
+        }
+    }
+
+    return output;
+}
+
+// Clusters five bit values into up to k levels.
+// The values only go up to 31 so they're counted first which makes every iteration go over at most 32 values.
+fn k_means(values: &[u8], k: usize) -> Vec<u8> {
+    const MAX_ITERATIONS: usize = 100;
+
+    let mut counts = [0usize; 32];
+    for value in values {
+        counts[*value as usize] += 1;
+    }
+    let present: Vec<usize> = (0..32).filter(|value| counts[*value] > 0).collect();
+
+    // There is nothing to cluster if there are few enough values already.
+    if present.len() <= k {
+        return present.iter().map(|value| *value as u8).collect();
+    }
+
+    // The centroids start out evenly spread over the values which are actually there.
+    let mut centroids: Vec<f64> = (0..k).map(|index| present[index * (present.len() - 1) / (k - 1).max(1)] as f64).collect();
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut sums = vec![0.0; k];
+        let mut weights = vec![0usize; k];
+
+        for value in &present {
+            let nearest = (0..k).min_by(|a, b| {
+                (centroids[*a] - *value as f64).abs().partial_cmp(&(centroids[*b] - *value as f64).abs()).unwrap()
+            }).unwrap();
+            sums[nearest] += (*value * counts[*value]) as f64;
+            weights[nearest] += counts[*value];
+        }
+
+        // Empty clusters keep their centroid.
+        let updated: Vec<f64> = (0..k).map(|index| {
+            if weights[index] > 0 {sums[index] / weights[index] as f64} else {centroids[index]}
+        }).collect();
+
+        if updated == centroids {
+            break;
+        }
+        centroids = updated;
+    }
+
+    return centroids.iter().map(|centroid| centroid.round() as u8).collect();
+}
+
+// The colour space in which neighbouring scanlines are compared when deciding whether they can be merged.
+#[derive(Copy, Clone, PartialEq)]
+pub enum ColourSpace {
+    // The SNES levels themselves. By default, only colours which end up exactly the same are merged, with a tolerance
+    // it's the distance in levels.
+    Rgb,
+    Lab,
+    OkLab
+}
+
+impl FromStr for ColourSpace {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "rgb" => Ok(ColourSpace::Rgb),
+            "lab" => Ok(ColourSpace::Lab),
+            "oklab" => Ok(ColourSpace::OkLab),
+            _ => Err(())
+        }
+    }
+}
+
+impl ColourSpace {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColourSpace::Rgb => "rgb",
+            ColourSpace::Lab => "lab",
+            ColourSpace::OkLab => "oklab"
+        }
+    }
+
+    // How far apart two colours are as they're shown on the SNES.
+    // Lab is the classic delta E (CIE76) and OKLab is scaled by 100 so that both are roughly comparable.
+    // RGB is measured in SNES levels.
+    pub fn distance(&self, first: Rgb<u8>, second: Rgb<u8>) -> f64 {
+        let (first, second) = match self {
+            ColourSpace::Rgb => (snes_levels(first), snes_levels(second)),
+            ColourSpace::Lab => (to_lab(first), to_lab(second)),
+            ColourSpace::OkLab => (to_oklab(first), to_oklab(second))
+        };
+
+        return first.iter().zip(&second).map(|(a, b)| (a - b) * (a - b)).sum::<f64>().sqrt();
+    }
+}
+
+// The default tolerance for merging scanlines.
+// A delta E of 2.3 is generally considered the smallest difference one can notice.
+pub const JUST_NOTICEABLE_DIFFERENCE: f64 = 2.3;
+
+// Replaces every colour which is no farther than the tolerance from the start of its run with that colour so that the table
+// optimisation merges them into a single repeat row. A tolerance of 0 only merges colours which are exactly the same.
+// Measuring against the start of the run (rather than the previous colour) keeps slow gradients from being flattened.
+pub fn merge_similar(colours: Vec<Rgb<u8>>, space: ColourSpace, tolerance: f64) -> Vec<Rgb<u8>> {
+    let mut run_start = match colours.first() {
+        Some(&first) => first,
+        None => return colours
+    };
+
+    return colours.iter().map(|colour| {
+        if space.distance(run_start, *colour) > tolerance {
+            run_start = *colour;
+        }
+        run_start
+    }).collect();
+}
+
+// Replaces every colour with the closest colour of the palette, e.g. so that an indexed image keeps its exact colours.
+// If two colours are just as close, the earlier one in the palette wins.
+pub fn snap_to_palette(colours: Vec<Rgb<u8>>, palette: &[Rgb<u8>]) -> Vec<Rgb<u8>> {
+    return colours.into_iter().map(|colour| {
+        palette.iter().copied()
+            .min_by(|first, second| ColourSpace::Lab.distance(colour, *first).partial_cmp(&ColourSpace::Lab.distance(colour, *second)).unwrap())
+            .unwrap_or(colour)
+    }).collect();
+}
+
+// The five bit level of each channel.
+fn snes_levels(colour: Rgb<u8>) -> [f64; 3] {
+    return colour.0.map(|value| (value >> 3) as f64);
+}
+
+// The colour the SNES actually shows (see colour.rs) in linear light.
+fn to_linear(colour: Rgb<u8>) -> [f64; 3] {
+    return colour.0.map(|value| {
+        let level = value >> 3;
+        let value = ((level << 3) | (level >> 2)) as f64 / 255.0;
+        if value <= 0.04045 {value / 12.92} else {((value + 0.055) / 1.055).powf(2.4)}
+    });
+}
+
+fn to_lab(colour: Rgb<u8>) -> [f64; 3] {
+    let [red, green, blue] = to_linear(colour);
+
+    // XYZ relative to the D65 white point.
+    let x = (red * 0.4124 + green * 0.3576 + blue * 0.1805) / 0.95047;
+    let y = red * 0.2126 + green * 0.7152 + blue * 0.0722;
+    let z = (red * 0.0193 + green * 0.1192 + blue * 0.9505) / 1.08883;
+
+    let f = |t: f64| if t > 216.0 / 24389.0 {t.cbrt()} else {(24389.0 / 27.0 * t + 16.0) / 116.0};
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+
+    return [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)];
+}
+
+fn to_oklab(colour: Rgb<u8>) -> [f64; 3] {
+    let [red, green, blue] = to_linear(colour);
+
+    let l = (0.4122214708 * red + 0.5363325363 * green + 0.0514459929 * blue).cbrt();
+    let m = (0.2119034982 * red + 0.6806995451 * green + 0.1073969566 * blue).cbrt();
+    let s = (0.0883024619 * red + 0.2817188376 * green + 0.6299787005 * blue).cbrt();
+
+    return [
+        (0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s) * 100.0,
+        (1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s) * 100.0,
+        (0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s) * 100.0
+    ];
+}
+
+// Blends two gradients of the same height, a factor of 0 gives the first one and 1 the second one.
+pub fn mix(colours: &[Rgb<u8>], other: &[Rgb<u8>], factor: f32) -> Vec<Rgb<u8>> {
+    return colours.iter().zip(other).map(|(first, second)| {
+        Rgb([0, 1, 2].map(|channel| {
+            (first.0[channel] as f32 + (second.0[channel] as f32 - first.0[channel] as f32) * factor).round() as u8
+        }))
+    }).collect();
+}
+
+// Interlaced images have got twice as many lines as there are scanlines: Each field only shows every other line.
+// The even field starts with the first line, the odd field with the second one.
+pub fn field(colours: Vec<Rgb<u8>>, odd: bool) -> Vec<Rgb<u8>> {
+    return colours.iter().skip(odd as usize).step_by(2).copied().collect();
+}
+
+// Averages each pair of lines into a single scanline for a table which is the same in both fields.
+pub fn merge_fields(colours: Vec<Rgb<u8>>) -> Vec<Rgb<u8>> {
+    return colours.chunks(2).map(|pair| {
+        Rgb([0, 1, 2].map(|channel| {
+            (pair.iter().map(|colour| colour.0[channel] as u32).sum::<u32>() as f32 / pair.len() as f32).round() as u8
+        }))
+    }).collect();
+}
+
+// Moves the colours down (or up if negative) by this many scanlines without changing how many there are.
+// The first or last colour fills the gap which makes the first or last row that much longer.
+pub fn shift(colours: Vec<Rgb<u8>>, offset: i32) -> Vec<Rgb<u8>> {
+    let (first, last) = match (colours.first(), colours.last()) {
+        (Some(&first), Some(&last)) => (first, last),
+        _ => return colours
+    };
+    let distance = (offset.unsigned_abs() as usize).min(colours.len());
+
+    if offset < 0 {
+        return colours[distance..].iter().copied().chain(std::iter::repeat(last).take(distance)).collect();
+    }
+    else {
+        return std::iter::repeat(first).take(distance).chain(colours[..colours.len() - distance].iter().copied()).collect();
+    }
+}
+
+// Only keeps every nth colour and repeats it until the next one.
+// The table optimisation then merges these into repeat rows which makes the table a lot smaller.
+pub fn stride(colours: Vec<Rgb<u8>>, stride: usize) -> Vec<Rgb<u8>> {
+    return (0..colours.len()).map(|index| colours[index - index % stride]).collect();
+}
+
+// Blends the last few colours towards the first one so that a gradient which wraps around has got no visible seam.
+// The closer a colour is to the end, the more it gets blended.
+pub fn blend_loop(colours: Vec<Rgb<u8>>, blend_rows: usize) -> Vec<Rgb<u8>> {
+    let first = match colours.first() {
+        Some(&first) => first,
+        None => return colours
+    };
+    let blend_rows = blend_rows.min(colours.len() - 1);
+    let blend_start = colours.len() - blend_rows;
+
+    return colours.iter().enumerate().map(|(index, colour)| {
+        if index < blend_start {
+            return *colour;
+        }
+
+        let weight = (index - blend_start + 1) as f64 / (blend_rows + 1) as f64;
+        Rgb([0, 1, 2].map(|channel| {
+            (colour.0[channel] as f64 + (first.0[channel] as f64 - colour.0[channel] as f64) * weight).round() as u8
+        }))
+    }).collect();
+}
+
+// Every channel is filtered on its own. At the edges, the window simply gets smaller.
+fn median(colours: &[Rgb<u8>], size: usize) -> Vec<Rgb<u8>> {
+    let before = (size - 1) / 2;
+    let after = size / 2;
+
+    return (0..colours.len()).map(|index| {
+        let window = &colours[index.saturating_sub(before)..(index + after + 1).min(colours.len())];
+
+        let mut output = [0u8; 3];
+        for (channel, value) in output.iter_mut().enumerate() {
+            let mut values: Vec<u8> = window.iter().map(|colour| colour.0[channel]).collect();
+            values.sort_unstable();
+            *value = values[values.len() / 2];
+        }
+
+        Rgb(output)
+    }).collect();
+}
+
+// The noise is smoothed out first so that it doesn't count as stops itself.
+// The stops are then found by splitting the gradient at the colour which is the furthest off from a straight line
+// between both ends until every colour is close enough (which is basically the Ramer-Douglas-Peucker algorithm).
+fn reinterpolate_stops(colours: &[Rgb<u8>], tolerance: u8) -> Vec<Rgb<u8>> {
+    if colours.len() < 3 {
+        return colours.to_vec();
+    }
+
+    let smoothed = median(colours, 5);
+    let mut stops = vec![0, smoothed.len() - 1];
+    let mut sections = vec![(0, smoothed.len() - 1)];
+
+    while let Some((start, end)) = sections.pop() {
+        let furthest = (start + 1..end)
+            .map(|index| (index, distance(smoothed[index], interpolate(smoothed[start], smoothed[end], start, end, index))))
+            .max_by_key(|(_, distance)| *distance);
+
+        if let Some((index, distance)) = furthest {
+            if distance > tolerance {
+                stops.push(index);
+                sections.push((start, index));
+                sections.push((index, end));
+            }
+        }
+    }
+
+    stops.sort_unstable();
+    log::debug!("Colour stops: {:?}", stops);
+
+    let mut output = Vec::with_capacity(colours.len());
+    for pair in stops.windows(2) {
+        let (start, end) = (pair[0], pair[1]);
+        output.extend((start..end).map(|index| interpolate(smoothed[start], smoothed[end], start, end, index)));
+    }
+    output.push(smoothed[smoothed.len() - 1]);
+
+    return output;
+}
+
+fn interpolate(first: Rgb<u8>, last: Rgb<u8>, start: usize, end: usize, index: usize) -> Rgb<u8> {
+    let weight = (index - start) as f64 / (end - start) as f64;
+    return Rgb([0, 1, 2].map(|channel| {
+        (first.0[channel] as f64 + (last.0[channel] as f64 - first.0[channel] as f64) * weight).round() as u8
+    }));
+}
+
+// How far apart two colours are in the channel which differs the most.
+fn distance(first: Rgb<u8>, second: Rgb<u8>) -> u8 {
+    return (0..3).map(|channel| (first.0[channel] as i16 - second.0[channel] as i16).unsigned_abs() as u8).max().unwrap_or(0);
+}
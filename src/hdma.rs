@@ -1,7 +1,117 @@
 #[derive(Copy, Clone)]
 pub enum HdmaWriteMode {
     Bytes,
-    Words
+    Words,
+    Longs
+}
+
+impl std::str::FromStr for HdmaWriteMode {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "bytes" => Ok(HdmaWriteMode::Bytes),
+            "words" => Ok(HdmaWriteMode::Words),
+            "longs" => Ok(HdmaWriteMode::Longs),
+            _ => Err(())
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum NumberBase {
+    Hexadecimal,
+    Decimal
+}
+
+// The assembler the data is written for. Only the directives differ, the routines (e.g. of animations or patches)
+// only exist for asar which is why the other assemblers only get the tables.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Syntax {
+    Asar,
+    Ca65,
+    Tass64
+}
+
+// How the numbers in the table are written.
+// The default is the usual asar style: Uppercase hexadecimal numbers prefixed with a dollar sign.
+#[derive(Copy, Clone)]
+pub struct NumberFormat {
+    pub base: NumberBase,
+    pub hex_prefix: &'static str,
+    pub uppercase: bool,
+    pub syntax: Syntax,
+    // Wraps every table in an asar macro which puts a prefix in front of the label.
+    pub macros: bool
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self { base: NumberBase::Hexadecimal, hex_prefix: "$", uppercase: true, syntax: Syntax::Asar, macros: false }
+    }
+}
+
+impl NumberFormat {
+    pub fn byte_directive(&self) -> &'static str {
+        match self.syntax {
+            Syntax::Asar => "db",
+            Syntax::Ca65 | Syntax::Tass64 => ".byte"
+        }
+    }
+
+    pub fn word_directive(&self) -> &'static str {
+        match self.syntax {
+            Syntax::Asar => "dw",
+            Syntax::Ca65 | Syntax::Tass64 => ".word"
+        }
+    }
+
+    // A long is a 24-bit address.
+    pub fn long_directive(&self) -> &'static str {
+        match self.syntax {
+            Syntax::Asar => "dl",
+            Syntax::Ca65 => ".faraddr",
+            Syntax::Tass64 => ".long"
+        }
+    }
+
+    // asar can put several directives on the same line, the others need a new line for each of them.
+    pub fn separator(&self) -> &'static str {
+        match self.syntax {
+            Syntax::Asar => " : ",
+            Syntax::Ca65 | Syntax::Tass64 => "\n"
+        }
+    }
+
+    // 64tass labels are simply put at the start of the line, without a colon.
+    pub fn label(&self, name: &str) -> String {
+        match self.syntax {
+            Syntax::Tass64 => String::from(name),
+            _ => format!("{}:", name)
+        }
+    }
+
+    // Writes a byte, padded to two digits for hexadecimal numbers.
+    pub fn byte(&self, value: usize) -> String {
+        return self.number(value, 2);
+    }
+
+    // Writes a word, padded to four digits for hexadecimal numbers.
+    pub fn word(&self, high: u8, low: u8) -> String {
+        return self.number(((high as usize) << 8) | low as usize, 4);
+    }
+
+    pub fn long(&self, bank: u8, high: u8, low: u8) -> String {
+        return self.number(((bank as usize) << 16) | ((high as usize) << 8) | low as usize, 6);
+    }
+
+    fn number(&self, value: usize, digits: usize) -> String {
+        match (self.base, self.uppercase) {
+            (NumberBase::Decimal, _) => format!("{}", value),
+            (NumberBase::Hexadecimal, true) => format!("{}{:0width$X}", self.hex_prefix, value, width = digits),
+            (NumberBase::Hexadecimal, false) => format!("{}{:0width$x}", self.hex_prefix, value, width = digits)
+        }
+    }
 }
 
 // A rust implementation of the
@@ -71,13 +181,16 @@ impl HdmaRow {
 // Liberties are taken for row_size which can also accept 3
 // (normally, it's only 1, 2 or 4 bytes), total row count (to
 // handle pseudo-tables like big gradients), write mode and
-// table name (display in the ASM file only).
+// table name (display in the ASM file only) along with a suffix
+// to tell apart the tables of several frames.
 pub struct HdmaTable {
     rows: Vec<HdmaRow>,
     row_size: usize,
     max_row_count: usize,
     write_mode: HdmaWriteMode,
-    table_name: &'static str
+    table_name: &'static str,
+    label_prefix: String,
+    label_suffix: String
 }
 
 impl HdmaTable {
@@ -86,7 +199,7 @@ impl HdmaTable {
         if (row_size < 1) | (row_size > 4) {
             panic!("Error: The specified row count is outside of the range.");
         }
-        Self { rows: rows, row_size: row_size, table_name: table_name, write_mode: write_mode, max_row_count: max_row_count }
+        Self { rows: rows, row_size: row_size, table_name: table_name, label_prefix: String::new(), label_suffix: String::new(), write_mode: write_mode, max_row_count: max_row_count }
     }
 
     // An actual HDMA table, total row count is limited to 0x80 lines.
@@ -94,7 +207,7 @@ impl HdmaTable {
         if (row_size < 1) | (row_size > 4) {
             panic!("Error: The specified row count is outside of the range.");
         }
-        Self { rows: rows, row_size: row_size, table_name: table_name, write_mode: write_mode, max_row_count: Self::MAX_REP_ROWS }
+        Self { rows: rows, row_size: row_size, table_name: table_name, label_prefix: String::new(), label_suffix: String::new(), write_mode: write_mode, max_row_count: Self::MAX_REP_ROWS }
     }
 
     // Adds a new HDMA row to the table.
@@ -102,6 +215,173 @@ impl HdmaTable {
         self.rows.push(row);
     }
 
+    pub fn name(self: &Self) -> &'static str {
+        return self.table_name;
+    }
+
+    pub fn set_label_prefix(self: &mut Self, prefix: &str) {
+        self.label_prefix = String::from(prefix);
+    }
+
+    // Overrides how the data is written, e.g. for drivers which expect words instead of bytes.
+    pub fn set_write_mode(self: &mut Self, write_mode: HdmaWriteMode) {
+        self.write_mode = write_mode;
+    }
+
+    // Changes the order of the bytes in each word and of the two words in scanlines with four bytes, e.g. for drivers
+    // which don't use the little endian order of the SNES. Only tables which are written as words have got words.
+    pub fn reorder_words(self: &mut Self, big_endian: bool, swap_words: bool) {
+        if !matches!(self.write_mode, HdmaWriteMode::Words) {
+            return;
+        }
+
+        let row_size = self.row_size;
+        let reorder = |data: &mut [u8; 4]| {
+            if swap_words && row_size > 2 {
+                data.swap(0, 2);
+                data.swap(1, 3);
+            }
+            if big_endian {
+                data.swap(0, 1);
+                data.swap(2, 3);
+            }
+        };
+
+        for row in &mut self.rows {
+            match row {
+                HdmaRow::Repeat { count: _, data } => reorder(data),
+                HdmaRow::Continuous { data } => data.iter_mut().for_each(reorder),
+                HdmaRow::Finish => ()
+            }
+        }
+    }
+
+    pub fn set_label_suffix(self: &mut Self, suffix: &str) {
+        self.label_suffix = String::from(suffix);
+    }
+
+    // The name as it's written in the ASM file.
+    pub fn label(self: &Self) -> String {
+        return format!("{}{}{}", self.label_prefix, self.table_name, self.label_suffix);
+    }
+
+    // How many HDMA rows there are in the table (termination byte excluded).
+    pub fn row_count(self: &Self) -> usize {
+        return self.rows.iter().filter(|row| !matches!(row, HdmaRow::Finish)).count();
+    }
+
+    // How many scanlines the table covers.
+    pub fn scanline_count(self: &Self) -> usize {
+        return self.rows.iter().map(|row| match row {
+            HdmaRow::Repeat { count, data: _ } => *count,
+            HdmaRow::Continuous { data } => data.len(),
+            HdmaRow::Finish => 0
+        }).sum();
+    }
+
+    // How many bytes the table takes up once it's written.
+    // This follows the exact same splitting as write_rows.
+    pub fn byte_size(self: &Self) -> usize {
+        return self.rows.iter().map(|row| self.row_byte_size(row)).sum();
+    }
+
+    // How many bytes of data each scanline has got. Words always write either one or two words, longs either one or
+    // two longs.
+    pub fn scanline_size(self: &Self) -> usize {
+        match self.write_mode {
+            HdmaWriteMode::Bytes => self.row_size,
+            HdmaWriteMode::Words => if self.row_size <= 2 {2} else {4},
+            HdmaWriteMode::Longs => if self.row_size <= 3 {3} else {6}
+        }
+    }
+
+    // The data of a scanline padded with zeroes to the whole words or longs.
+    fn scanline_data(self: &Self, data: &[u8; 4]) -> Vec<u8> {
+        let mut output = data.to_vec();
+        output.resize(self.scanline_size(), 0);
+        return output;
+    }
+
+    fn row_byte_size(self: &Self, row: &HdmaRow) -> usize {
+        let data_size = self.scanline_size();
+        return self.split_counts(row).iter().map(|(_, scanlines)| 1 + scanlines.len() * data_size).sum();
+    }
+
+    // Splits the table into several tables which take up at most max_size bytes each (termination byte included).
+    // The rows are kept as they are unless a single row doesn't fit into a table on its own, then it's split as well.
+    // Each table gets its own termination byte and is numbered after its position, e.g. gradient_table_0.
+    pub fn split(self: Self, max_size: usize) -> Vec<HdmaTable> {
+        let Self { rows, row_size, max_row_count, write_mode, table_name, label_prefix, label_suffix } = self;
+        let new_part = |index: usize| Self {
+            rows: Vec::new(),
+            row_size: row_size,
+            max_row_count: max_row_count,
+            write_mode: write_mode,
+            table_name: table_name,
+            label_prefix: label_prefix.clone(),
+            label_suffix: format!("_{}{}", index, label_suffix)
+        };
+
+        let mut parts = vec![new_part(0)];
+
+        for row in rows {
+            let mut row = match row {
+                HdmaRow::Finish => continue,
+                row => row
+            };
+
+            loop {
+                let part = parts.last_mut().unwrap();
+                let free_size = max_size - part.byte_size() - 1;
+
+                if part.row_byte_size(&row) <= free_size {
+                    part.push(row);
+                    break;
+                }
+
+                // Whatever fits goes into the current table, the rest into the next one.
+                let (head, tail) = part.split_row(row, free_size);
+                if let Some(head) = head {
+                    part.push(head);
+                }
+                part.push(HdmaRow::Finish);
+                parts.push(new_part(parts.len()));
+                row = tail;
+            }
+        }
+
+        parts.last_mut().unwrap().push(HdmaRow::Finish);
+
+        return parts;
+    }
+
+    // Takes as many scanlines from the row as fit into free_size bytes.
+    fn split_row(self: &Self, row: HdmaRow, free_size: usize) -> (Option<HdmaRow>, HdmaRow) {
+        let scanlines = match &row {
+            HdmaRow::Repeat { count, data: _ } => *count,
+            HdmaRow::Continuous { data } => data.len(),
+            HdmaRow::Finish => 0
+        };
+        let fits = (1..scanlines).rev().find(|count| {
+            let head = match &row {
+                HdmaRow::Repeat { count: _, data } => HdmaRow::Repeat { count: *count, data: *data },
+                HdmaRow::Continuous { data } => HdmaRow::Continuous { data: data[..*count].to_vec() },
+                HdmaRow::Finish => HdmaRow::Finish
+            };
+            self.row_byte_size(&head) <= free_size
+        });
+
+        match (fits, row) {
+            (Some(count), HdmaRow::Repeat { count: total, data }) =>
+                (Some(HdmaRow::Repeat { count: count, data: data }), HdmaRow::Repeat { count: total - count, data: data }),
+            (Some(count), HdmaRow::Continuous { mut data }) => {
+                let tail = data.split_off(count);
+                (Some(HdmaRow::Continuous { data: data }), HdmaRow::Continuous { data: tail })
+            }
+            (_, row) => (None, row)
+        }
+    }
+
     // How many rows can exist until we need to break?
     // This is different for repeating and continuous rows.
     const MAX_REP_ROWS: usize = 0x80;
@@ -253,201 +533,230 @@ impl HdmaTable {
 
     // Write the HDMA table.
     // Do note that the actually written HDMA table.
-    pub fn write_table(self: Self) -> String {
+    pub fn write_table(self: Self, format: &NumberFormat) -> String {
         // Put the table name first
-        let mut output = String::from(format!("{}:\n", &self.table_name));
+        // A macro is named after the table and the prefix comes from whoever calls it, e.g. %red_table(level105_).
+        let label = self.label();
+        let mut output = if format.macros {
+            format!("macro {0}(prefix)\n<prefix>{0}:\n", label)
+        }
+        else {
+            format!("{}\n", format.label(&label))
+        };
 
-        output.push_str(&match self.write_mode {
-            HdmaWriteMode::Bytes => self.write_bytes(),
-            HdmaWriteMode::Words => self.write_words()
-        });
+        output.push_str(&self.write_rows(format));
+
+        if format.macros {
+            output.push_str("endmacro\n");
+        }
 
         return output;
     }
 
-    fn write_bytes(self: Self) -> String {
-        let mut output = String::new();
+    // The table as the HDMA unit reads it, e.g. for tools which insert it into the ROM themselves.
+    // This follows the exact same splitting as write_rows (words are little endian anyway).
+    pub fn bytes(self: &Self) -> Vec<u8> {
+        let mut output = Vec::with_capacity(self.byte_size());
 
-        for row in self.rows {
-            match row {
-                // Repeat:
-                // db $xx : db $yy
-                // Where xx is the row count (may not exceed the total row count) and
-                // yy the data (can be up to four bytes)
-                HdmaRow::Repeat { mut count, data } => {
-                    loop {
-                        // Write down the scanline count
-                        // But it cannot exeed more than 0x80.
-                        output.push_str(&format!("db ${:02X}",
-                            if count < self.max_row_count {count} else {self.max_row_count}));
-                        // Write down the bytes (right now, only single bytes)
-                        for i in 0..self.row_size {
-                            output.push_str(&format!(",${:02X}", data[i]));
-                        }
-                        output.push('\n');
-                        // If there are less than the total row count left
-                        if count < self.max_row_count {
-                            break;
-                        }
-                        // Otherwise subtract the remaining row count from the max row count.
-                        count = count - self.max_row_count;
-                    }
+        for row in &self.rows {
+            for (count, scanlines) in self.split_counts(row) {
+                output.push(count as u8);
+                for scanline in scanlines {
+                    output.extend_from_slice(&self.scanline_data(scanline));
                 }
-                // Repeat:
-                // db $xx : db $yy,$zz...
-                // Where xx is the row count (between 0x81 and 0xFF),
-                // followed by xx-0x80 units of data
-                // Note that continuous lacks any length information since that's provided by the vector.
-                HdmaRow::Continuous { data } => {
-                    // How many rows there are to write,
-                    // the current index
-                    // and an iterator
-                    // That loop is quite complicated
-                    let mut total_rows = data.len();
-                    let mut current_byte = 0;
-                    let mut row_iterator = data.iter();
-                    loop {
-                        // Put down the first byte, the scanline count
-                        current_byte += 1;
-                        if current_byte == 1 {
-                            if total_rows > Self::MAX_CONT_ROWS {
-                                output.push_str("db $FF");
-                                total_rows -= Self::MAX_CONT_ROWS;
-                            }
-                            else {
-                                output.push_str(&format!("db ${:02X}", total_rows + Self::CONT_BIT));
-                            }
-                            continue;
-                        }
-                        // If more than 0x80 units of data (including scanline count) have been written
-                        // Put down a new line
-                        else if current_byte > (Self::MAX_CONT_ROWS + 1) {
-                            output.push('\n');
-                            current_byte = 0;
-                            continue;
-                        }
-                        // Otherwise write down so many bytes until the end is reached
-                        match row_iterator.next() {
-                            Some(x) => {
-                                for i in 0..self.row_size {
-                                    output.push_str(&format!(",${:02X}", x[i]));
-                                }
-                            }
-                            None => {
-                                output.push('\n');
-                                break;
-                            }
-                        }
-                    }
+            }
+        }
+
+        return output;
+    }
+
+    // The rows as they're written: The scanline count followed by the data of the scanlines.
+    // A repeat row has got the data once and covers at most max_row_count scanlines, a continuous row has got the data
+    // of every scanline (at most 0x7F of them) and the continuous bit in its count. Longer rows are split into several
+    // rows. The termination byte is a count of 0 without any data.
+    fn split_counts<'a>(self: &Self, row: &'a HdmaRow) -> Vec<(usize, Vec<&'a [u8; 4]>)> {
+        match row {
+            HdmaRow::Repeat { count, data } => {
+                let mut counts = vec![self.max_row_count; count / self.max_row_count];
+                if count % self.max_row_count != 0 {
+                    counts.push(count % self.max_row_count);
                 }
-                HdmaRow::Finish => {
-                    output.push_str("db $00\n")
+                counts.into_iter().map(|count| (count, vec![data])).collect()
+            }
+            HdmaRow::Continuous { data } => data.chunks(Self::MAX_CONT_ROWS)
+                .map(|scanlines| (scanlines.len() + Self::CONT_BIT, scanlines.iter().collect())).collect(),
+            HdmaRow::Finish => vec![(0, Vec::new())]
+        }
+    }
+
+    // Writes every row as a line:
+    // db $xx,$yy,$zz...         (bytes)
+    // db $xx : dw $zzyy : ...   (words)
+    // db $xx : dl $zzyyww : ... (longs)
+    // Where xx is the scanline count and the rest the data of each scanline. A repeat row only has got a single scanline
+    // of data, a continuous one (between 0x81 and 0xFF) has got xx-0x80 of them.
+    fn write_rows(self: Self, format: &NumberFormat) -> String {
+        let row_size = self.row_size;
+        // Words always write either one or two words, longs either one or two longs where the fourth byte goes to the
+        // bottom of the second long.
+        let scanline = |data: &[u8; 4]| match self.write_mode {
+            HdmaWriteMode::Bytes => data[..row_size].iter().map(|value| format!(",{}", format.byte(*value as usize))).collect(),
+            HdmaWriteMode::Words if row_size <= 2 =>
+                format!("{}{} {}", format.separator(), format.word_directive(), format.word(data[1], data[0])),
+            HdmaWriteMode::Words =>
+                format!("{}{} {},{}", format.separator(), format.word_directive(), format.word(data[1], data[0]), format.word(data[3], data[2])),
+            HdmaWriteMode::Longs if row_size <= 3 =>
+                format!("{}{} {}", format.separator(), format.long_directive(), format.long(data[2], data[1], data[0])),
+            HdmaWriteMode::Longs =>
+                format!("{}{} {},{}", format.separator(), format.long_directive(), format.long(data[2], data[1], data[0]), format.long(0, 0, data[3]))
+        };
+
+        let mut output = String::new();
+        for row in &self.rows {
+            for (count, scanlines) in self.split_counts(row) {
+                output.push_str(&format!("{} {}", format.byte_directive(), format.byte(count)));
+                for data in scanlines {
+                    output.push_str(&scanline(data));
                 }
+                output.push('\n');
             }
         }
-    
+
         return output;
     }
+}
 
-    fn write_words(self: Self) -> String {
-        let mut output = String::new();
+#[cfg(test)]
+pub mod tests {
+    use super::*;
 
-        for row in self.rows {
-            match row {
-                // Repeat:
-                // db $xx : dw $zzyy
-                // Where xx is the row count (may not exceed the total row count) and
-                // zzyy the data (can be up to four bytes)
-                HdmaRow::Repeat { mut count, data } => {
-                    loop {
-                        // Write down the scanline count
-                        // But it cannot exeed more than 0x80.
-                        output.push_str(&format!("db ${:02X}",
-                            if count < self.max_row_count {count} else {self.max_row_count}));
-                        
-                        // This gets a bit complex.
-                        // Effectively, there are only two cases: Larger than 2 or at most 2.
-                        // This is why I use the method of using an if-else-condition whereas
-                        // a for-loop would become just too ugly.
-                        // Of course, for row_size != {2, 4}, just don't write more to data
-                        // that you actually have to.
-                        if self.row_size <= 2 {
-                            output.push_str(&format!(" : dw ${:02X}{:02X}",
-                                data[1], data[0]));
-                        }
-                        else {
-                            output.push_str(&format!(" : dw ${:02X}{:02X},${:02X}{:02X}",
-                            data[1], data[0], data[3], data[2]));
-                        }
-
-                        output.push('\n');
-                        // If there are less than the total row count left
-                        if count < self.max_row_count {
-                            break;
-                        }
-                        // Otherwise subtract the remaining row count from the max row count.
-                        count = count - self.max_row_count;
-                    }
-                }
-                // Repeat:
-                // db $xx : db $zzyy,$wwvv...
-                // Where xx is the row count (between 0x81 and 0xFF),
-                // followed by xx-0x80 units of data
-                // Note that continuous lacks any length information since that's provided by the vector.
-                // So we have to use a different solution for that.
-                HdmaRow::Continuous { data } => {
-                    // How many rows there are to write,
-                    // the current index
-                    // and an iterator
-                    // That loop is quite complicated
-                    let mut total_rows = data.len();
-                    let mut current_byte = 0;
-                    let mut row_iterator = data.iter();
-                    loop {
-                        // Put down the first byte, the scanline count
-                        current_byte += 1;
-                        if current_byte == 1 {
-                            if total_rows > Self::MAX_CONT_ROWS {
-                                output.push_str("db $FF");
-                                total_rows -= Self::MAX_CONT_ROWS;
-                            }
-                            else {
-                                output.push_str(&format!("db ${:02X}", total_rows + Self::CONT_BIT));
-                            }
-                            continue;
-                        }
-                        // If more than 0x80 units of data (including scanline count) have been written
-                        // Put down a new line
-                        else if current_byte > (Self::MAX_CONT_ROWS + 1) {
-                            output.push('\n');
-                            current_byte = 0;
-                            continue;
-                        }
-                        // Otherwise write down so many bytes until the end is reached
-                        match row_iterator.next() {
-                            Some(data) => {
-                                if self.row_size <= 2 {
-                                    output.push_str(&format!(" : dw ${:02X}{:02X}",
-                                        data[1], data[0]));
-                                }
-                                else {
-                                    output.push_str(&format!(" : dw ${:02X}{:02X},${:02X}{:02X}",
-                                    data[1], data[0], data[3], data[2]));
-                                }
-                            }
-                            None => {
-                                output.push('\n');
-                                break;
-                            }
-                        }
-                    }
+    // Assembles the tables the way asar would, as long as they're written with the default number format.
+    pub fn assemble(text: &str) -> Vec<u8> {
+        let mut output = Vec::new();
+
+        for line in text.lines().filter(|line| !line.ends_with(':')) {
+            for statement in line.split(" : ") {
+                let (directive, values) = statement.split_once(' ').unwrap();
+                let size = match directive {
+                    "db" => 1,
+                    "dw" => 2,
+                    "dl" => 3,
+                    _ => panic!("Unknown directive {}", directive)
+                };
+                for value in values.split(',') {
+                    let value = u32::from_str_radix(value.trim_start_matches('$'), 16).unwrap();
+                    output.extend_from_slice(&value.to_le_bytes()[..size]);
                 }
-                HdmaRow::Finish => {
-                    output.push_str("db $00\n")
+            }
+        }
+
+        return output;
+    }
+
+    // Reads the bytes back the way the HDMA unit does and gets the data of every scanline.
+    fn scanlines(bytes: &[u8], scanline_size: usize, max_row_count: usize) -> Vec<Vec<u8>> {
+        let mut output = Vec::new();
+        let mut index = 0;
+
+        loop {
+            let count = bytes[index] as usize;
+            index += 1;
+            if count == 0 {
+                assert_eq!(index, bytes.len(), "Data after the termination byte");
+                return output;
+            }
+            if count > 0x80 && max_row_count == HdmaTable::MAX_REP_ROWS {
+                for _ in 0..count - 0x80 {
+                    output.push(bytes[index..index + scanline_size].to_vec());
+                    index += scanline_size;
                 }
             }
+            else {
+                assert!(count <= max_row_count);
+                output.extend(std::iter::repeat(bytes[index..index + scanline_size].to_vec()).take(count));
+                index += scanline_size;
+            }
         }
-    
+    }
+
+    // A repeat row of exactly $80 lines, one which has to be split, a continuous row of exactly $7F lines and one which
+    // has to be split. Neither of the exact ones may get an empty row after them.
+    fn table(row_size: usize, write_mode: HdmaWriteMode) -> HdmaTable {
+        let scanline = |index: usize| [index as u8, 0x11 * row_size as u8, 0x80 | index as u8, 0xFF - index as u8];
+        let mut table = HdmaTable::new_real_table(Vec::new(), row_size, write_mode, "test_table");
+
+        table.push(HdmaRow::new_repeat(0x80, &scanline(0)[..row_size]));
+        table.push(HdmaRow::Continuous { data: (1..0x80).map(scanline).collect() });
+        table.push(HdmaRow::new_repeat(0x123, &scanline(0x80)[..row_size]));
+        table.push(HdmaRow::Continuous { data: (0x81..0x103).map(scanline).collect() });
+        table.push(HdmaRow::Finish);
+
+        return table;
+    }
+
+    fn expected_scanlines(table: &HdmaTable) -> Vec<Vec<u8>> {
+        let mut output = Vec::new();
+
+        for row in &table.rows {
+            match row {
+                HdmaRow::Repeat { count, data } => output.extend(std::iter::repeat(table.scanline_data(data)).take(*count)),
+                HdmaRow::Continuous { data } => output.extend(data.iter().map(|data| table.scanline_data(data))),
+                HdmaRow::Finish => ()
+            }
+        }
+
         return output;
     }
+
+    fn check_round_trip(table: HdmaTable) {
+        let bytes = table.bytes();
+        assert_eq!(bytes.len(), table.byte_size());
+        assert_eq!(scanlines(&bytes, table.scanline_size(), table.max_row_count), expected_scanlines(&table));
+
+        let text = table.write_table(&NumberFormat::default());
+        assert_eq!(assemble(&text), bytes);
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        for row_size in 1..=4 {
+            check_round_trip(table(row_size, HdmaWriteMode::Bytes));
+        }
+    }
+
+    #[test]
+    fn words_round_trip() {
+        for row_size in 1..=4 {
+            check_round_trip(table(row_size, HdmaWriteMode::Words));
+        }
+    }
+
+    #[test]
+    fn longs_round_trip() {
+        for row_size in 1..=4 {
+            check_round_trip(table(row_size, HdmaWriteMode::Longs));
+        }
+    }
+
+    #[test]
+    fn exact_rows_are_not_followed_by_empty_rows() {
+        let table = table(1, HdmaWriteMode::Bytes);
+        let counts: Vec<usize> = table.rows.iter()
+            .flat_map(|row| table.split_counts(row).into_iter().map(|(count, _)| count)).collect();
+
+        assert_eq!(counts, vec![0x80, 0xFF, 0x80, 0x80, 0x23, 0xFF, 0x83, 0x00]);
+    }
+
+    // Big gradients are repeat rows of up to $FF lines.
+    #[test]
+    fn big_gradient_round_trip() {
+        let mut table = HdmaTable::new(Vec::new(), 3, HdmaWriteMode::Bytes, "big_table", 0xFF);
+        table.push(HdmaRow::new_repeat(0xFF, &[1, 2, 3]));
+        table.push(HdmaRow::new_repeat(0x200, &[4, 5, 6]));
+        table.push(HdmaRow::new_repeat(0x81, &[7, 8, 9]));
+        table.push(HdmaRow::Finish);
+
+        assert_eq!(&table.bytes()[..9], &[0xFF, 1, 2, 3, 0xFF, 4, 5, 6, 0xFF]);
+        check_round_trip(table);
+    }
 }
@@ -0,0 +1,262 @@
+use crate::colour::RipSettings;
+use crate::error::RipperError;
+use crate::messages::Message;
+
+use clap::ArgMatches;
+
+// The settings of the header which can be passed to the command line again.
+// The key is both, the name in the header and the long option, followed by the name of the argument.
+const HEADER_OPTIONS: &[(&str, &str)] = &[
+    ("xpos", "xpos"),
+    ("x-end", "x-end"),
+    ("sample-radius", "sample-radius"),
+    ("denoise", "denoise"),
+    ("smooth", "smooth"),
+    ("dither", "dither"),
+    ("max-levels", "max-levels"),
+    ("merge-space", "merge-space"),
+    ("tolerance", "tolerance"),
+    ("gamma", "gamma"),
+    ("brightness", "brightness"),
+    ("contrast", "contrast"),
+    ("saturation", "saturation"),
+    ("resample", "resample"),
+    ("mirror", "mirror"),
+    ("stride", "stride"),
+    ("pad-to", "pad-to"),
+    ("loop", "loop"),
+    ("start", "ystart"),
+    ("end", "yend"),
+    ("range", "range"),
+    ("range-filler", "range-filler"),
+    ("mode", "mode"),
+    ("height", "height"),
+    ("cgram", "cgram")
+];
+
+// Further options which are recorded as they have been entered.
+// Their argument name is the same as their long option.
+const RECORDED_OPTIONS: &[&str] = &[
+    "number-base",
+    "hex-prefix",
+    "hex-case",
+    "alpha",
+    "hold-colour",
+    "tone-map",
+    "exposure",
+    "frame",
+    "hue-variants",
+    "blend-with",
+    "blend",
+    "steps",
+    "fade",
+    "mosaic-layers",
+    "spotlight",
+    "colour-math",
+    "math-layers",
+    "math-layers-off",
+    "threshold",
+    "cgram-columns",
+    "channels",
+    "split-size",
+    "driver",
+    "scroll-routine",
+    "ram-buffer",
+    "math",
+    "backdrop",
+    "regions",
+    "region-fit",
+    "scanlines",
+    "interlace",
+    "line-offset",
+    "reserve-top",
+    "reserve-colour",
+    "syntax",
+    "segment",
+    "write-mode",
+    "label-prefix",
+    "namespace"
+];
+
+// Same as above but for options which don't take a value.
+// They're only recorded if they have been entered.
+const RECORDED_FLAGS: &[&str] = &[
+    "transpose",
+    "extrapolate",
+    "animate",
+    "crossfade",
+    "kmeans",
+    "half",
+    "cgram-pair",
+    "merge-channels",
+    "compensate-half",
+    "overscan",
+    "big-endian",
+    "swap-words"
+];
+
+// The recorded options which have been entered in the command line.
+pub fn recorded_options(matches: &ArgMatches) -> Vec<(&'static str, String)> {
+    let mut options: Vec<(&'static str, String)> = RECORDED_OPTIONS.iter()
+        .filter_map(|option| matches.value_of(option).map(|value| (*option, String::from(value))))
+        .collect();
+
+    options.extend(RECORDED_FLAGS.iter()
+        .filter(|flag| matches.is_present(flag))
+        .map(|flag| (*flag, String::from("yes"))));
+
+    return options;
+}
+
+// The comment block at the top of the ASM file.
+// It records everything the tables have been generated with so that anyone can regenerate them later.
+pub fn write_header(input_name: &str, settings: &RipSettings, options: &[(&str, String)]) -> String {
+    let mut output = format!("; Generated by {} {}\n", crate::TOOL_NAME, crate::TOOL_VERSION);
+
+    output.push_str(&format!("; source: {}\n", input_name));
+    output.push_str(&format!("; xpos: {}\n", settings.x_pos));
+    output.push_str(&format!("; x-end: {}\n", settings.x_end));
+    output.push_str(&format!("; sample-radius: {}\n", settings.sample_radius));
+    output.push_str(&format!("; denoise: {}\n", match settings.denoise {
+        Some(filter) => filter.name(),
+        None => String::from("none")
+    }));
+    output.push_str(&format!("; smooth: {}\n", match settings.smooth {
+        Some(sigma) => sigma.to_string(),
+        None => String::from("none")
+    }));
+    output.push_str(&format!("; dither: {}\n", match settings.dither {
+        Some(dither) => dither.name(),
+        None => "none"
+    }));
+    output.push_str(&format!("; max-levels: {}\n", match settings.max_levels {
+        Some(levels) => levels.to_string(),
+        None => String::from("none")
+    }));
+    output.push_str(&format!("; merge-space: {}\n", match settings.merge_space {
+        Some(space) => space.name(),
+        None => "none"
+    }));
+    if settings.merge_space.is_some() {
+        output.push_str(&format!("; tolerance: {}\n", settings.merge_tolerance));
+    }
+    output.push_str(&format!("; gamma: {}\n", settings.gamma));
+    output.push_str(&format!("; brightness: {}\n", settings.brightness));
+    output.push_str(&format!("; contrast: {}\n", settings.contrast));
+    output.push_str(&format!("; saturation: {}\n", settings.saturation));
+    output.push_str(&format!("; start: {}\n", settings.y_start));
+    output.push_str(&format!("; end: {}\n", settings.y_end));
+    for (y_start, y_end) in &settings.ranges {
+        output.push_str(&format!("; range: {}..{}\n", y_start, y_end));
+    }
+    if !settings.ranges.is_empty() {
+        output.push_str(&format!("; range-filler: {}\n", settings.range_filler));
+    }
+    output.push_str(&format!("; resample: {}\n", settings.resample.name()));
+    output.push_str(&format!("; mirror: {}\n", match settings.mirror {
+        Some(scanline) => scanline.to_string(),
+        None => String::from("none")
+    }));
+    output.push_str(&format!("; stride: {}\n", settings.stride));
+    output.push_str(&format!("; pad-to: {}\n", match settings.pad_to {
+        Some(scanlines) => scanlines.to_string(),
+        None => String::from("none")
+    }));
+    output.push_str(&format!("; loop: {}\n", match settings.loop_blend {
+        Some(blend_rows) => blend_rows.to_string(),
+        None => String::from("none")
+    }));
+    // Only for information, scrolling code needs to know after which row the gradient starts again.
+    if settings.loop_blend.is_some() {
+        output.push_str(&format!("; wrap-row: {}\n", settings.height.max(settings.pad_to.unwrap_or(0))));
+    }
+    let modes: Vec<&str> = std::iter::once(&settings.mode).chain(&settings.extra_modes).map(|mode| mode.name()).collect();
+    output.push_str(&format!("; mode: {}\n", modes.join(",")));
+    output.push_str(&format!("; height: {}\n", settings.height));
+    output.push_str(&format!("; cgram: {}\n", match settings.cgram_index {
+        Some(index) => index.to_string(),
+        None => String::from("none")
+    }));
+    output.push_str(&format!("; optimise: {}\n", if settings.optimise {"yes"} else {"no"}));
+
+    for (option, value) in options {
+        output.push_str(&format!("; {}: {}\n", option, value));
+    }
+
+    output.push('\n');
+
+    return output;
+}
+
+// The comment block of a generated table which lists the parameters it has been generated with.
+pub fn write_effect_header(effect: &str, parameters: &[(&str, String)]) -> String {
+    let mut output = format!("; Generated by {} {}\n", crate::TOOL_NAME, crate::TOOL_VERSION);
+
+    output.push_str(&format!("; effect: {}\n", effect));
+    for (parameter, value) in parameters {
+        output.push_str(&format!("; {}: {}\n", parameter, value));
+    }
+
+    output.push('\n');
+
+    return output;
+}
+
+// Reads the header of an existing ASM file and returns the settings as command line arguments.
+// Settings which have been entered in the command line are skipped so that they can be changed.
+// The file itself becomes the output unless a different one has been entered.
+pub fn regeneration_args(from_name: &str, matches: &ArgMatches) -> Result<Vec<String>, RipperError> {
+    let text = match std::fs::read_to_string(from_name) {
+        Err(why) => return Err(RipperError::BadArguments(tr!(Message::ReadFileFailed, from_name, why))),
+        Ok(text) => text
+    };
+
+    let mut lines = text.lines();
+
+    match lines.next() {
+        Some(line) if line.starts_with(&format!("; Generated by {}", crate::TOOL_NAME)) => {}
+        _ => return Err(RipperError::BadArguments(tr!(Message::NoHeader, from_name)))
+    }
+
+    let mut args = Vec::new();
+
+    // The header ends at the first line which isn't a comment.
+    for line in lines.take_while(|line| line.starts_with(';')) {
+        let (key, value) = match line.trim_start_matches(';').split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue
+        };
+
+        if key == "source" {
+            if !matches.is_present("INPUT") {
+                args.push(String::from(value));
+            }
+            continue;
+        }
+
+        if RECORDED_FLAGS.contains(&key) {
+            if !matches.is_present(key) && value == "yes" {
+                args.push(format!("--{}", key));
+            }
+            continue;
+        }
+
+        let arg_name = match HEADER_OPTIONS.iter().find(|(option, _)| *option == key) {
+            Some((_, arg_name)) => *arg_name,
+            None if RECORDED_OPTIONS.contains(&key) => key,
+            None => continue
+        };
+
+        // Options with an optional value only take it with an equals sign so it's always written like that.
+        if !matches.is_present(arg_name) && value != "none" {
+            args.push(format!("--{}={}", key, value));
+        }
+    }
+
+    if !matches.is_present("output") {
+        args.push(String::from("--output"));
+        args.push(String::from(from_name));
+    }
+
+    return Ok(args);
+}
@@ -0,0 +1,22 @@
+// Tiny helpers for writing JSON by hand.
+// The reports this tool writes are simple enough that a full serialiser would be overkill.
+
+// Puts a string in quotes and escapes everything JSON doesn't allow within strings.
+pub fn string(text: &str) -> String {
+    let mut output = String::from("\"");
+
+    for character in text.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c)
+        }
+    }
+
+    output.push('"');
+    return output;
+}
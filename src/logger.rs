@@ -0,0 +1,50 @@
+use crate::messages::{self, Message};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+// A very simple logger which writes everything to stderr so that it doesn't mix up with the tables
+// (e.g. if they're written to stdout at some point).
+// Warnings and errors are prefixed as such, everything else is written as is.
+struct ConsoleLogger;
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        return metadata.level() <= log::max_level();
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        match record.level() {
+            Level::Error => eprintln!("{}: {}", messages::text(Message::ErrorPrefix), record.args()),
+            Level::Warn => eprintln!("{}: {}", messages::text(Message::WarningPrefix), record.args()),
+            _ => eprintln!("{}", record.args())
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: ConsoleLogger = ConsoleLogger;
+
+// Sets up the logger.
+// By default, only warnings, errors and the success messages are displayed.
+// Every -v displays more information (image info, table sizes, etc.) whereas -q hides the success messages.
+pub fn init(verbosity: u64, quiet: bool) {
+    let level = if quiet {
+        LevelFilter::Warn
+    }
+    else {
+        match verbosity {
+            0 => LevelFilter::Info,
+            1 => LevelFilter::Debug,
+            _ => LevelFilter::Trace
+        }
+    };
+
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
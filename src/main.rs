@@ -1,36 +1,452 @@
-use colour::{HdmaColourMode};
+use colour::{HdmaColourMode, Interlace, OutputDriver, Resample, RipSettings, RippedTables};
+use messages::{Language, Message};
+use hdma::{HdmaWriteMode, NumberBase, NumberFormat, Syntax};
+use error::{ErrorFormat, RipperError};
+use effects::{Shape, Wave};
+use filters::ColourSpace;
+use source::{AlphaPolicy, LoadSettings, SourceColours, ToneMap};
+use target::Target;
+
+#[macro_use]
+mod messages;
 
 pub mod colour;
+mod carray;
+mod codegen;
+mod csv;
+mod effects;
+mod error;
+mod expression;
+mod filters;
 mod hdma;
+mod header;
+mod json;
+mod logger;
+mod palette;
+mod preset;
+mod preview;
+mod source;
+mod target;
+mod verify;
+mod window;
 
 extern crate clap;
 
 use std::{fs::File, io::Write};
-use std::path::Path;
-use image::open;
-use clap::{Arg, App};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::ffi::OsString;
+use clap::{Arg, App, AppSettings, ArgMatches, SubCommand};
+use arboard::Clipboard;
+use sha2::{Digest, Sha256};
+use image::{ImageBuffer, Rgba, RgbaImage};
+use image::codecs::gif::{GifEncoder, Repeat};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+pub const TOOL_NAME: &str = "HDMA Gradient Ripper";
+pub const TOOL_VERSION: &str = "1.0";
 
 const MAX_SCANLINES: u32 = 224; // How large the image can be until the HDMA table must be a
+const PAL_SCANLINES: u32 = 239; // The scanline count of PAL consoles with overscan.
+const OVERSCAN_SCANLINES: u32 = 239; // How many scanlines there are with overscan (on any console).
+const STATUS_BAR_SCANLINES: u32 = 32; // How many scanlines SMW's status bar takes up.
+const SPLIT_SIZE: usize = 0x8000; // A bank in LoROM, big gradients which are larger than it are split.
+const MIN_SPLIT_SIZE: usize = 0x10;
+const OPTIMISE_TABLE: bool = if cfg!(debug_assertions) {false} else {true};
+const LARGE_TABLE_HEIGHT: u32 = 0x1000; // From which output height ripping an image gets its own progress bar.
 
 fn main() {
-    const OPTIMISE_TABLE: bool = if cfg!(debug_assertions) {false} else {true};
+    let matches = match build_app().get_matches_safe() {
+        Ok(matches) => matches,
+        Err(why) => {
+            let why = clap_error(why);
+            why.report(raw_error_format());
+            std::process::exit(why.exit_code());
+        }
+    };
+
+    messages::set_language(match matches.value_of("lang") {
+        Some(code) => Language::from_code(code).unwrap_or(Language::English),
+        None => Language::from_environment()
+    });
+    logger::init(matches.occurrences_of("verbose"), matches.is_present("quiet"));
+
+    let error_format = match matches.value_of("error-format") {
+        Some("json") => ErrorFormat::Json,
+        _ => ErrorFormat::Human
+    };
+
+    // Regenerating a file and presets simply add their settings to the command line
+    // (unless they're overwritten) which then gets parsed again.
+    let result = extra_args(&matches).and_then(|args| {
+        if args.is_empty() {
+            run(&matches)
+        }
+        else {
+            run(&build_app().get_matches_from_safe(std::env::args_os().chain(args.into_iter().map(OsString::from))).map_err(clap_error)?)
+        }
+    });
+
+    if let Err(why) = result {
+        why.report(error_format);
+        std::process::exit(why.exit_code());
+    }
+}
+
+// clap's own errors (e.g. an unknown option) are reported like every other error so that they show up in the JSON
+// diagnostics as well. --help and --version aren't errors though so clap still prints them and exits on its own.
+fn clap_error(error: clap::Error) -> RipperError {
+    if matches!(error.kind, clap::ErrorKind::HelpDisplayed | clap::ErrorKind::VersionDisplayed) {
+        error.exit();
+    }
+
+    // Only the message itself, without clap's prefix and the usage below it.
+    let message = error.message.lines().next().unwrap_or("");
+    return RipperError::BadArguments(String::from(message.strip_prefix("error: ").unwrap_or(message).trim()));
+}
+
+// The error format has to be looked up by hand if clap couldn't read the command line.
+fn raw_error_format() -> ErrorFormat {
+    let args: Vec<String> = std::env::args().collect();
+    let json = args.iter().any(|arg| arg == "--error-format=json")
+        || args.windows(2).any(|pair| pair[0] == "--error-format" && pair[1] == "json");
+    return if json {ErrorFormat::Json} else {ErrorFormat::Human};
+}
+
+// Gets the arguments from --from and --preset.
+// The header of the regenerated file takes priority over the preset.
+fn extra_args(matches: &ArgMatches) -> Result<Vec<String>, RipperError> {
+    let mut args = match matches.value_of("from") {
+        Some(from_name) => header::regeneration_args(from_name, matches)?,
+        None => Vec::new()
+    };
+
+    if let Some(preset_name) = matches.value_of("preset") {
+        let preset_args = preset::preset_args(preset_name, matches, &args)?;
+        args.extend(preset_args);
+    }
 
-    let matches =
-    App::new("HDMA Gradient Ripper")
-        .version("1.0")
+    return Ok(args);
+}
+
+fn build_app() -> App<'static, 'static> {
+    App::new(TOOL_NAME)
+        .version(TOOL_VERSION)
         .author("MarioFanGamer")
         .about("A small tool which allows you to create an HDMA gradient from an image.")
+        // The errors are reported without clap's colours, see clap_error.
+        .global_setting(AppSettings::ColorNever)
         .arg(
             Arg::with_name("INPUT")
-            .help("The image source to be ripped.")
+            .help("The image source to be ripped (a file or a link). Multiple images are ripped one after another.")
             .takes_value(true)
+            .multiple(true)
         )
         .arg(
             Arg::with_name("xpos")
-            .help("The X position of the column to rip (default: 0).")
+            .help("The X position of the column to rip or \"full\" to average the whole width of the image or \"auto\" to pick the cleanest column (default: 0).")
             .short("x")
             .long("xpos")
+            .visible_alias("ypos")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("x-end")
+            .help("The last X position to rip. Each scanline is the average from the X position up to this one (default: X position).")
+            .long("x-end")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("range")
+            .help("A Y range to rip, written as start..end. Multiple ranges are merged into one table, replacing the Y start and end.")
+            .long("range")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+        )
+        .arg(
+            Arg::with_name("range-filler")
+            .help("The amount of rows inserted between two ranges which repeat the last colour of the previous range (default: 0).")
+            .long("range-filler")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("alpha")
+            .help("What happens to transparent pixels: ignore, matte=#RRGGBB to put them on a solid colour, skip to leave them out or hold to repeat the previous colour (default: ignore).")
+            .long("alpha")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("hold-colour")
+            .help("Rows of this colour (written as #RRGGBB) repeat the colour of the previous row instead.")
+            .long("hold-colour")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("tone-map")
+            .help("How 16-bit and HDR images are brought down to eight bits: clamp or reinhard (default: clamp).")
+            .long("tone-map")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("exposure")
+            .help("Brightens (or darkens if negative) 16-bit and HDR images by this many stops before tone mapping (default: 0).")
+            .long("exposure")
+            .takes_value(true)
+            .allow_hyphen_values(true)
+        )
+        .arg(
+            Arg::with_name("frame")
+            .help("The frame of an animated image (GIF, PNG or WebP) or the page of a TIFF to rip, starting at 0 (default: 0).")
+            .long("frame")
+            .takes_value(true)
+            .conflicts_with("animate")
+        )
+        .arg(
+            Arg::with_name("animate")
+            .help("Rips every frame of an animated image (GIF, PNG or WebP) and adds a routine which cycles through them.")
+            .long("animate")
+            .conflicts_with("frame")
+        )
+        .arg(
+            Arg::with_name("sample-radius")
+            .help("Averages the pixels up to this far away from each sampled pixel, e.g. 1 for a 3x3 window (default: 0).")
+            .long("sample-radius")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("denoise")
+            .help("Filters the sampled colours to get rid of single scanline outliers, e.g. \"median:3\", or rebuilds the gradient from its colour stops with \"stops:8\".")
+            .long("denoise")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("smooth")
+            .help("Blurs the sampled colours along the column, the value is the standard deviation in scanlines.")
+            .long("smooth")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("dither")
+            .help("Dithers the colours down the column to hide the banding of slow gradients, e.g. \"diffusion\".")
+            .long("dither")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("max-levels")
+            .help("Limits every colour channel to this many levels, from 2 to 32, for much smaller but less smooth tables.")
+            .long("max-levels")
+            .takes_value(true)
+            .conflicts_with("dither")
+        )
+        .arg(
+            Arg::with_name("kmeans")
+            .help("Chooses the levels of --max-levels by k-means clustering so that they are where most of the colours are.")
+            .long("kmeans")
+            .requires("max-levels")
+        )
+        .arg(
+            Arg::with_name("merge-space")
+            .help("Merges neighbouring scanlines which look the same in this colour space: rgb, lab or oklab.")
+            .long("merge-space")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("tolerance")
+            .help("Merges neighbouring scanlines whose colours differ by less than this delta E (default: 2.3, uses oklab without --merge-space).")
+            .long("tolerance")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("gamma")
+            .help("Gamma corrects the sampled colours, values above 1 brighten and values below 1 darken the gradient (default: 1).")
+            .long("gamma")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("brightness")
+            .help("Brightens (or darkens if negative) the sampled colours by this many percent, from -100 to 100 (default: 0).")
+            .long("brightness")
+            .takes_value(true)
+            .allow_hyphen_values(true)
+        )
+        .arg(
+            Arg::with_name("contrast")
+            .help("Raises (or lowers if negative) the contrast of the sampled colours by this many percent, from -100 to 100 (default: 0).")
+            .long("contrast")
+            .takes_value(true)
+            .allow_hyphen_values(true)
+        )
+        .arg(
+            Arg::with_name("saturation")
+            .help("Raises (or lowers if negative) the saturation of the sampled colours by this many percent, from -100 to 100 (default: 0).")
+            .long("saturation")
+            .takes_value(true)
+            .allow_hyphen_values(true)
+        )
+        .arg(
+            Arg::with_name("blend-with")
+            .help("A second image which is sampled the same way and blended with the first one, e.g. for transitions.")
+            .long("blend-with")
+            .takes_value(true)
+            .conflicts_with("animate")
+        )
+        .arg(
+            Arg::with_name("blend")
+            .help("How much of the second image is blended in, from 0 to 1 (default: 0.5).")
+            .long("blend")
+            .takes_value(true)
+            .requires("blend-with")
+        )
+        .arg(
+            Arg::with_name("steps")
+            .help("Writes this many tables which go from the first to the second image instead of a single blend.")
+            .long("steps")
+            .takes_value(true)
+            .requires("blend-with")
+            .conflicts_with("blend")
+        )
+        .arg(
+            Arg::with_name("crossfade")
+            .help("Adds a routine to the steps which fades between them by a timer.")
+            .long("crossfade")
+            .requires("steps")
+        )
+        .arg(
+            Arg::with_name("mosaic-layers")
+            .help("The BGs which the mosaic mode applies to, e.g. \"12\" for BG1 and BG2 (default: 1234).")
+            .long("mosaic-layers")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("spotlight")
+            .help("A mask for a spotlight: Adds a window table from the mask and a routine which darkens everything outside of it with the fixed colour tables.")
+            .long("spotlight")
+            .takes_value(true)
+            .conflicts_with_all(&["animate", "steps", "fade", "hue-variants"])
+        )
+        .arg(
+            Arg::with_name("backdrop")
+            .help("Adds a backdrop (CG-RAM colour 0) gradient for the main screen to the fixed colour, either from another image or from a column of the same image.")
+            .long("backdrop")
+            .takes_value(true)
+            .value_name("IMAGE|XPOS")
+            .conflicts_with_all(&["animate", "steps", "crossfade", "fade", "hue-variants", "spotlight", "ram-buffer", "math", "driver", "channels", "merge-channels"])
+        )
+        .arg(
+            Arg::with_name("channels")
+            .help("Only rips these fixed colour channels in the single and double mode, e.g. \"rb\" for red and blue (default: rgb).")
+            .long("channels")
+            .takes_value(true)
+            .conflicts_with_all(&["merge-channels", "spotlight"])
+        )
+        .arg(
+            Arg::with_name("merge-channels")
+            .help("Writes fixed colour channels which are the same on every scanline with a single table.")
+            .long("merge-channels")
+            .conflicts_with_all(&["animate", "steps", "spotlight"])
+        )
+        .arg(
+            Arg::with_name("colour-math")
+            .help("Adds a routine which sets up the colour math for the fixed colour: add or subtract.")
+            .long("colour-math")
+            .takes_value(true)
+            .possible_values(&["add", "subtract"])
+            .conflicts_with("spotlight")
+        )
+        .arg(
+            Arg::with_name("half")
+            .help("Halves the result of the colour math.")
+            .long("half")
+        )
+        .arg(
+            Arg::with_name("math")
+            .help("Which colour math the fixed colour is meant for: add (default), subtract (inverts the colours) or both.")
+            .long("math")
+            .takes_value(true)
+            .possible_values(&["add", "subtract", "both"])
+            .conflicts_with("spotlight")
+        )
+        .arg(
+            Arg::with_name("compensate-half")
+            .help("Doubles the sampled colours so that they look like the image with colour math which halves the result.")
+            .long("compensate-half")
+        )
+        .arg(
+            Arg::with_name("math-layers")
+            .help("The layers which the colour math applies to: 1 to 4 for the BGs, o for objects and b for the backdrop (default: b).")
+            .long("math-layers")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("math-layers-off")
+            .help("The layers which the colour math applies to where the mask of the math mode is dark (default: none).")
+            .long("math-layers-off")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("threshold")
+            .help("How bright the mask of the math mode has to be for the colour math to apply, from 0 to 255 (default: 128).")
+            .long("threshold")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("fade")
+            .help("Writes this many brightness tables which fade from black to the gradient and adds routines to fade in and out with them.")
+            .long("fade")
+            .takes_value(true)
+            .conflicts_with_all(&["steps", "animate"])
+        )
+        .arg(
+            Arg::with_name("hue-variants")
+            .help("Adds copies of the tables with the hue shifted by these degrees, separated by commas (e.g. \"30,60,180\").")
+            .long("hue-variants")
+            .takes_value(true)
+            .allow_hyphen_values(true)
+        )
+        .arg(
+            Arg::with_name("resample")
+            .help("How the image is stretched to the output height: nearest, linear or cubic (default: nearest).")
+            .long("resample")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("mirror")
+            .help("Rips the gradient up to this scanline and reflects it below for a symmetric gradient (default: half the height).")
+            .long("mirror")
+            .takes_value(true)
+            .min_values(0)
+            .require_equals(true)
+        )
+        .arg(
+            Arg::with_name("stride")
+            .help("Only samples every nth scanline and holds it for smaller but less smooth tables (default: 1).")
+            .long("stride")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("extrapolate")
+            .help("Continues the gradient past the end of the image instead of stretching it if the height is larger.")
+            .long("extrapolate")
+        )
+        .arg(
+            Arg::with_name("pad-to")
+            .help("Repeats the final colour until the table covers this many scanlines.")
+            .long("pad-to")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("loop")
+            .help("Blends this many scanlines at the bottom into the top so that a scrolling gradient wraps seamlessly (default: an eighth of the height).")
+            .long("loop")
             .takes_value(true)
+            .min_values(0)
+            .require_equals(true)
+        )
+        .arg(
+            Arg::with_name("transpose")
+            .help("Rips a row instead of a column for horizontal gradients. The X and Y positions swap their meaning.")
+            .long("transpose")
         )
         .arg(
             Arg::with_name("ystart")
@@ -48,10 +464,11 @@ fn main() {
         )
         .arg(
             Arg::with_name("output")
-            .help("The name of the ASM file.")
+            .help("The name of the ASM file (output folder if there are multiple images).")
             .short("o")
             .long("output")
             .takes_value(true)
+            .global(true)
         )
         .arg(
             Arg::with_name("height")
@@ -61,8 +478,8 @@ fn main() {
             .takes_value(true)
         )
         .arg(
-            Arg::with_name("hdma_mode")
-            .help("The mode of the HDMA tables.")
+            Arg::with_name("mode")
+            .help("The mode of the HDMA tables. Several modes can be separated by commas, e.g. double,cgram.")
             .short("m")
             .long("mode")
             .takes_value(true)
@@ -74,161 +491,2276 @@ fn main() {
             .long("cgram")
             .takes_value(true)
         )
+        .arg(
+            Arg::with_name("cgram-columns")
+            .help("Rips several colours at once in the cgram mode, each from its own column: XPOS:CGRAM_IND separated by commas (e.g. \"10:33,40:34\").")
+            .long("cgram-columns")
+            .takes_value(true)
+            .conflicts_with("cgram")
+        )
+        .arg(
+            Arg::with_name("cgram-pair")
+            .help("Writes two neighbouring colours per scanline in the cgram mode, the second one from the column(s) right next to XPOS.")
+            .long("cgram-pair")
+            .requires("cgram")
+            .conflicts_with("cgram-columns")
+        )
+        .arg(
+            Arg::with_name("scroll-routine")
+            .help("Adds a routine which builds the visible part of a big gradient from a scroll position. Optionally takes the address of the scroll position and the RAM of the tables (default: $1C,$7FA000).")
+            .long("scroll-routine")
+            .takes_value(true)
+            .min_values(0)
+            .require_equals(true)
+        )
+        .arg(
+            Arg::with_name("ram-buffer")
+            .help("Adds routines which copy the tables into two buffers in RAM and swap them during NMI for tables which are changed at runtime (default RAM: $7FB000).")
+            .long("ram-buffer")
+            .takes_value(true)
+            .min_values(0)
+            .require_equals(true)
+            .conflicts_with_all(&["animate", "steps", "crossfade", "fade", "hue-variants", "spotlight", "scroll-routine"])
+        )
+        .arg(
+            Arg::with_name("driver")
+            .help("How the values are written: hdma (default) for HDMA tables or irq for a plain table and an IRQ handler which writes it.")
+            .long("driver")
+            .takes_value(true)
+            .conflicts_with_all(&["animate", "crossfade", "fade", "spotlight", "cgram-columns", "cgram-pair", "merge-channels", "channels"])
+        )
+        .arg(
+            Arg::with_name("target")
+            .help("The console the gradient is for: snes (default), gba for a table of the backdrop colour for HBlank DMA or md for a table and an H-Int driver for the Mega Drive.")
+            .long("target")
+            .takes_value(true)
+            .possible_values(&["snes", "gba", "md"])
+            .conflicts_with_all(&["animate", "steps", "crossfade", "fade", "hue-variants", "spotlight", "backdrop", "driver", "regions", "ram-buffer", "colour-math", "scroll-routine"])
+        )
+        .arg(
+            Arg::with_name("scanlines")
+            .help("How many scanlines the screen has got: 224 (default) or 239 for games with overscan.")
+            .long("scanlines")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("overscan")
+            .help("Same as --scanlines 239.")
+            .long("overscan")
+            .conflicts_with("scanlines")
+        )
+        .arg(
+            Arg::with_name("regions")
+            .help("Writes a variant of each table for these regions, e.g. ntsc,pal. PAL tables are 239 scanlines tall.")
+            .long("regions")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("region-fit")
+            .help("How the gradient fits the PAL scanlines: scale (default) stretches it, extend repeats the last colour.")
+            .long("region-fit")
+            .takes_value(true)
+            .possible_values(&["scale", "extend"])
+            .requires("regions")
+        )
+        .arg(
+            Arg::with_name("interlace")
+            .help("Samples twice the scanlines for interlaced screens: doubled averages both fields into one table, fields writes a table for each field and a routine which switches between them.")
+            .long("interlace")
+            .takes_value(true)
+            .possible_values(&["doubled", "fields"])
+            .conflicts_with_all(&["animate", "crossfade", "fade", "ram-buffer", "driver", "backdrop", "spotlight"])
+        )
+        .arg(
+            Arg::with_name("line-offset")
+            .help("Moves the table down (or up if negative) by this many scanlines if HDMA starts a line early or late (default: 0).")
+            .long("line-offset")
+            .takes_value(true)
+            .allow_hyphen_values(true)
+        )
+        .arg(
+            Arg::with_name("reserve-top")
+            .help("Keeps this many scanlines at the top free for the status bar and fits the gradient below them (default: 32).")
+            .long("reserve-top")
+            .takes_value(true)
+            .min_values(0)
+            .require_equals(true)
+        )
+        .arg(
+            Arg::with_name("reserve-colour")
+            .help("The colour (written as #RRGGBB) of the reserved scanlines (default: the first colour of the gradient).")
+            .long("reserve-colour")
+            .takes_value(true)
+            .requires("reserve-top")
+        )
+        .arg(
+            Arg::with_name("split-size")
+            .help("How many bytes a big gradient table can take up before it's split into several tables (default: 32768, a LoROM bank).")
+            .long("split-size")
+            .takes_value(true)
+            .value_name("BYTES")
+        )
         .arg(
             Arg::with_name("verbose")
-            .help("Displays the amount of data.")
+            .help("Displays more information like the image data and table sizes (use -vv for even more).")
             .short("v")
             .long("verbose")
+            .multiple(true)
+        )
+        .arg(
+            Arg::with_name("quiet")
+            .help("Only displays warnings and errors.")
+            .short("q")
+            .long("quiet")
+            .conflicts_with("verbose")
+        )
+        .arg(
+            Arg::with_name("format")
+            .help("How the tables are written: asm (default), bin for the raw bytes of every table in a file of its own, incbin for these along with an ASM file which includes them, c for C arrays and a header file, json for the rows of every table, asar-patch for a patch which sets up the HDMA channels or uberasm for a file of the uberASM tool.")
+            .long("format")
             .takes_value(true)
+            .possible_values(&["asm", "bin", "incbin", "c", "json", "asar-patch", "uberasm"])
         )
-        .get_matches();
-    
-    // Get the input values
-    match matches.value_of("INPUT") {
-        Some(x) => {
-            let input_name = String::from(x);
-            let output_name = String::from(matches.value_of("output").unwrap_or("gradient.asm"));
-
-            // Load up the image since we need its data.
-            let image = match open(&input_name) {
-                Err(why) => panic!("Couldn't open {}: {}", &input_name, why),
-                Ok(x) => x.into_rgb8()
-            };
-        
-            let image_height = image.height();
-    
-            // Get the command line input for in- and output (or replace them with default values).
-            let height = match matches.value_of("height") {
-                Some(x) => x.parse().expect("Invalid height!"),
-                None => if image_height < MAX_SCANLINES {MAX_SCANLINES} else {image_height}
-            };
-            let y_start = match matches.value_of("ystart") {
-                Some(x) => x.parse().expect("Invalid Y position!"),
-                None => 0
-            };
-            let y_end = match matches.value_of("yend") {
-                Some(x) => x.parse().expect("Invalid Y position!"),
-                None => image_height
-            };
-            let x_pos = match matches.value_of("xpos") {
-                Some(x) => x.parse().expect("Invalid X position!"),
-                None => 0
-            };
-            let cgram_index = match matches.value_of("cgram") {
-                Some(x) => Some(x.parse().expect("Invalid CG-RAM index!")),
-                None => None
-            };
-
-            let mode = match matches.value_of("hdma_mode").unwrap_or("a") {
-                "s" | "single" => HdmaColourMode::FixedClourThree,
-                "d" | "double" => HdmaColourMode::FixedClourTwo,
-                "b" | "big" => HdmaColourMode::BigGradient,
-                "c" | "cgram" => HdmaColourMode::CgRam,
-                "a" | "auto" => if height > 224 {HdmaColourMode::BigGradient} else {HdmaColourMode::FixedClourTwo},
-                _ => panic!("The entered option is invalid!")
-            };
-        
-            // Handle errors (invalid inputs)
-            if y_start > image_height || y_end > image_height {
-                panic!("The entered Y position is located outside of the image!");
-            }
-            if x_pos > image.width() {
-                panic!("The entered X position is located outside of the image!")
-            }
-
-            // Handle warnings (questionable inputs)
-            if height < MAX_SCANLINES {
-                let warning = format!("Warning: The output height you entered is {} which is smaller than than {max}.
-                I recommend you to use a height of at least {max}.",
-                height, max = MAX_SCANLINES);
-                eprintln!("{}", warning);
-            }
-
-            if (mode != HdmaColourMode::BigGradient) & (height > MAX_SCANLINES) {
-                let warning = format!("Warning: The image height you entered is {} which is larger than {}.
-                I recommend you to use a big gradient instead.",
-                height, MAX_SCANLINES);
-                eprintln!("{}", warning);
-            }
-
-            let output_path = Path::new(&output_name);
-
-            let output_data = colour::write_table(height, x_pos, y_start, y_end, mode, cgram_index, image, OPTIMISE_TABLE);
-            write_file(output_data, output_path)
-        },
-        None => {
-            let mut input_name = String::new();
-
-            println!("Rust Gradient Ripper\n");
-
-            print!("Enter the image to be ripped: ");
-
-            std::io::stdout().flush().unwrap();
-
-            std::io::stdin().read_line(&mut input_name).expect("Error: Couldn't read input.");
-
-            let input_name = input_name.trim();
-
-            // Load up the image since we need its data.
-            let image = match open(input_name) {
-                Err(why) => panic!("Couldn't open {}: {}", input_name, why),
-                Ok(x) => x.into_rgb8()
-            };
-
-            print!("Enter the the name of the ASM file: ");
-
-            std::io::stdout().flush().unwrap();
-
-            let mut output_name = String::new();
-
-            std::io::stdin().read_line(&mut output_name).expect("Error: Couldn't read input.");
-        
-            let image_height = image.height();
-
-            // Get additional data
-            let height = if image_height < MAX_SCANLINES {MAX_SCANLINES} else {image_height};
-            let mode = if height > MAX_SCANLINES {HdmaColourMode::BigGradient} else {HdmaColourMode::FixedClourTwo};
-            let x_pos = 0;
-            let y_start = 0;
-            let y_end = image_height;
-
-            if cfg!(debug_assertions) {
-                println!("Image height: {}", image_height);
-                println!("Output height: {}", height);
-                println!("Input X position: {}", x_pos);
-                println!("Input Y position start: {}", y_start);
-                println!("Input Y position end: {}", y_end);
-                println!("Input height: {}", y_end - y_start);
-                match mode {
-                    HdmaColourMode::FixedClourTwo => println!("Fixed colour, two tables."),
-                    HdmaColourMode::BigGradient => println!("Fixed colour, one big table"),
-                    _ => println!("Some other table (shouldn't ever happen here).")
-                }
-            }
-
-            let output_name = output_name.trim();
-
-            let output_path = Path::new(&output_name);
-
-            let output_data = colour::write_table(height, x_pos, y_start, y_end, mode, None, image, OPTIMISE_TABLE);
-
-            write_file(output_data, output_path);
+        .arg(
+            Arg::with_name("verify-asm")
+            .help("Assembles the tables with asar to make sure that they're valid.")
+            .long("verify-asm")
+        )
+        .arg(
+            Arg::with_name("asar")
+            .help("The asar executable which --verify-asm runs (asar by default).")
+            .long("asar")
+            .takes_value(true)
+            .requires("verify-asm")
+        )
+        .arg(
+            Arg::with_name("asserts")
+            .help("Adds asserts which make asar fail if a table crosses a bank border or exceeds the size budget.")
+            .long("asserts")
+        )
+        .arg(
+            Arg::with_name("size-prints")
+            .help("Adds prints which list the size of every table when asar assembles the file.")
+            .long("size-prints")
+        )
+        .arg(
+            Arg::with_name("size-budget")
+            .help("The most bytes a table of a big gradient may have (for --asserts).")
+            .long("size-budget")
+            .takes_value(true)
+            .requires("asserts")
+        )
+        .arg(
+            Arg::with_name("uberasm-type")
+            .help("Whether the uberASM file is for a level (default) or the overworld.")
+            .long("uberasm-type")
+            .takes_value(true)
+            .possible_values(&["level", "overworld"])
+        )
+        .arg(
+            Arg::with_name("clipboard")
+            .help("Copies the tables to the clipboard (only writes the ASM file if an output name is entered).")
+            .long("clipboard")
+        )
+        .arg(
+            Arg::with_name("error-format")
+            .help("How errors are reported: human (default) or json.")
+            .long("error-format")
+            .takes_value(true)
+            .possible_values(&["human", "json"])
+        )
+        .arg(
+            Arg::with_name("export-colours")
+            .help("Writes the colour of every scanline as CSV to a file (- for stdout), both as it's sampled and as the SNES shows it.")
+            .long("export-colours")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("export-palette")
+            .help("Writes the colours of the gradient to a palette file: .pal for Lunar Magic or .tpl for Tile Layer Pro.")
+            .long("export-palette")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("preview")
+            .help("Renders what the backdrop looks like with the finished tables to a PNG file.")
+            .long("preview")
+            .takes_value(true)
+            .conflicts_with("animate")
+        )
+        .arg(
+            Arg::with_name("scroll-preview")
+            .help("Renders a GIF of the screen scrolling over a big gradient.")
+            .long("scroll-preview")
+            .takes_value(true)
+            .conflicts_with("animate")
+        )
+        .arg(
+            Arg::with_name("compare")
+            .help("Renders the sampled colours next to the colours the SNES can show to a PNG file to see the banding.")
+            .long("compare")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("stats-json")
+            .help("Writes the size of every table as JSON to a file (- for stdout).")
+            .long("stats-json")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("depfile")
+            .help("Writes a Make-style dependency file listing the image of every ASM file.")
+            .long("depfile")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("symbols")
+            .help("Writes the address of every table to a symbol file (WLA and bsnes-plus).")
+            .long("symbols")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("symbol-base")
+            .help("The address of the first table in the symbol file, e.g. where the tables are inserted ($000000 by default).")
+            .long("symbol-base")
+            .takes_value(true)
+            .requires("symbols")
+        )
+        .arg(
+            Arg::with_name("check")
+            .help("Doesn't write anything but fails if the existing ASM files differ from the generated tables.")
+            .long("check")
+        )
+        .arg(
+            Arg::with_name("from")
+            .help("Regenerates an ASM file with the settings from its header (other options overwrite them).")
+            .long("from")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("number-base")
+            .help("Whether the numbers in the table are hexadecimal (hex, default) or decimal (dec).")
+            .long("number-base")
+            .takes_value(true)
+            .possible_values(&["hex", "dec"])
+            .global(true)
+        )
+        .arg(
+            Arg::with_name("hex-prefix")
+            .help("The prefix of hexadecimal numbers: $ (default) or 0x.")
+            .long("hex-prefix")
+            .takes_value(true)
+            .possible_values(&["$", "0x"])
+            .global(true)
+        )
+        .arg(
+            Arg::with_name("hex-case")
+            .help("Whether hexadecimal digits are upper (default) or lower case.")
+            .long("hex-case")
+            .takes_value(true)
+            .possible_values(&["upper", "lower"])
+            .global(true)
+        )
+        .arg(
+            Arg::with_name("syntax")
+            .help("The assembler the tables are written for: asar (default), ca65 or 64tass.")
+            .long("syntax")
+            .takes_value(true)
+            .possible_values(&["asar", "ca65", "64tass"])
+            .global(true)
+        )
+        .arg(
+            Arg::with_name("segment")
+            .help("Puts the tables into this segment (only for ca65).")
+            .long("segment")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("defines")
+            .help("Also writes an include file (e.g. gradient.inc) with the scanline count, length and DMA mode of every table.")
+            .long("defines")
+        )
+        .arg(
+            Arg::with_name("write-mode")
+            .help("Writes the data of every table as bytes (db), words (dw) or longs (dl) instead of what the mode uses.")
+            .long("write-mode")
+            .takes_value(true)
+            .possible_values(&["bytes", "words", "longs"])
+        )
+        .arg(
+            Arg::with_name("big-endian")
+            .help("Writes the high byte of every word in the tables first.")
+            .long("big-endian")
+        )
+        .arg(
+            Arg::with_name("swap-words")
+            .help("Swaps the two words of tables with four bytes per scanline.")
+            .long("swap-words")
+        )
+        .arg(
+            Arg::with_name("label-prefix")
+            .help("Puts this in front of the label of every table, e.g. sunset_ for sunset_red_table.")
+            .long("label-prefix")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("namespace")
+            .help("Puts the tables and routines into a namespace (a scope for ca65 and a block for 64tass).")
+            .long("namespace")
+            .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("macros")
+            .help("Wraps every table in an asar macro which takes a label prefix so that it can be used several times.")
+            .long("macros")
+            .conflicts_with_all(&["animate", "steps", "crossfade", "fade", "interlace", "regions", "spotlight", "backdrop", "ram-buffer",
+                "colour-math", "scroll-routine", "driver", "asserts", "size-prints"])
+        )
+        .arg(
+            Arg::with_name("lang")
+            .help("The language of the messages: en, de or ja (default: language of the system).")
+            .long("lang")
+            .takes_value(true)
+            .possible_values(&["en", "de", "ja"])
+        )
+        .arg(
+            Arg::with_name("preset")
+            .help("Uses the settings of a common setup: smw-background or smw-big (other options overwrite them).")
+            .long("preset")
+            .takes_value(true)
+        )
+        .subcommand(
+            SubCommand::with_name("scroll-wave")
+            .about("Generates a table of BG scroll values which form a wave (e.g. for heat haze or underwater effects).")
+            .arg(
+                Arg::with_name("layer")
+                .help("The BG whose scroll value is written, 1 (default) to 4.")
+                .long("layer")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("vertical")
+                .help("Writes the vertical scroll value instead of the horizontal one.")
+                .long("vertical")
+            )
+            .args(&wave_args())
+        )
+        .subcommand(
+            SubCommand::with_name("wave")
+            .about("Generates a table of any register from a wave.")
+            .arg(
+                Arg::with_name("register")
+                .help("The register the table is written to, from $2100 to $21FF.")
+                .long("register")
+                .takes_value(true)
+                .required(true)
+            )
+            .arg(
+                Arg::with_name("size")
+                .help("Whether a byte (default) or a word is written to the register.")
+                .long("size")
+                .takes_value(true)
+                .possible_values(&["byte", "word"])
+            )
+            .arg(
+                Arg::with_name("offset")
+                .help("The value the wave goes around (default: 0).")
+                .long("offset")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+            )
+            .args(&wave_args())
+        )
+        .subcommand(
+            SubCommand::with_name("csv")
+            .about("Turns a text file with the values of each scanline into a table.")
+            .arg(
+                Arg::with_name("INPUT")
+                .help("The text file, one scanline per line with the values separated by commas or spaces.")
+                .takes_value(true)
+                .required(true)
+            )
+            .arg(
+                Arg::with_name("row-size")
+                .help("How many bytes each scanline has got, from 1 to 4 (default: as many as the first scanline).")
+                .long("row-size")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("write-mode")
+                .help("Whether each value is a byte (bytes, default), a word (words) or a long (longs).")
+                .long("write-mode")
+                .takes_value(true)
+                .possible_values(&["bytes", "words", "longs"])
+            )
+        )
+        .subcommand(
+            SubCommand::with_name("mode7")
+            .about("Generates the Mode 7 matrix of a floor in perspective.")
+            .arg(
+                Arg::with_name("horizon")
+                .help("The scanline where the floor starts (default: 0).")
+                .long("horizon")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("scale")
+                .help("The zoom at the bottom of the table, 1 being the size of the tilemap (default: 1).")
+                .long("scale")
+                .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("angle")
+                .help("Rotates the floor by the angle in degrees and writes the whole matrix instead of only A and D.")
+                .long("angle")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+            )
+            .arg(
+                Arg::with_name("height")
+                .help("The height of the table (default: 224).")
+                .long("height")
+                .takes_value(true)
+            )
+        )
+}
+
+// The parameters of a generated wave.
+fn wave_args() -> Vec<Arg<'static, 'static>> {
+    return vec![
+        Arg::with_name("shape")
+        .help("The shape of the wave: sine (default), triangle or sawtooth.")
+        .long("shape")
+        .takes_value(true),
+        Arg::with_name("expression")
+        .help("A formula which is used as the shape of the wave instead (t is the position in periods, y the scanline).")
+        .long("expression")
+        .takes_value(true)
+        .allow_hyphen_values(true)
+        .conflicts_with("shape"),
+        Arg::with_name("amplitude")
+        .help("How many pixels the wave goes to each side (default: 4).")
+        .long("amplitude")
+        .takes_value(true)
+        .allow_hyphen_values(true),
+        Arg::with_name("wavelength")
+        .help("How many scanlines a period of the wave takes up (default: 32).")
+        .long("wavelength")
+        .takes_value(true),
+        Arg::with_name("phase")
+        .help("Where the wave starts in degrees (default: 0).")
+        .long("phase")
+        .takes_value(true)
+        .allow_hyphen_values(true),
+        Arg::with_name("start")
+        .help("The first scanline of the wave (default: 0).")
+        .long("start")
+        .takes_value(true),
+        Arg::with_name("end")
+        .help("The scanline after the last one of the wave (default: height of the table).")
+        .long("end")
+        .takes_value(true),
+        Arg::with_name("height")
+        .help("The height of the table (default: 224).")
+        .long("height")
+        .takes_value(true)
+    ];
+}
+
+fn run(matches: &ArgMatches) -> Result<(), RipperError> {
+    // Generated tables don't need an image.
+    match matches.subcommand() {
+        ("scroll-wave", Some(sub_matches)) => return run_scroll_wave(sub_matches),
+        ("mode7", Some(sub_matches)) => return run_mode7(sub_matches),
+        ("wave", Some(sub_matches)) => return run_wave(sub_matches),
+        ("csv", Some(sub_matches)) => return run_csv(sub_matches),
+        _ => {}
+    }
+
+    // Get the input values
+    match matches.values_of("INPUT") {
+        Some(x) => {
+            let input_names: Vec<&str> = x.collect();
+
+            // The tables are always written to a file unless they're only copied to the clipboard
+            // (i.e. no output name has been entered).
+            // Checking only compares the tables to the existing files without changing anything.
+            let check_only = matches.is_present("check");
+            let copy_to_clipboard = matches.is_present("clipboard") && !check_only;
+            let write_output = check_only || !copy_to_clipboard || matches.is_present("output");
+
+            let mut outdated_files = Vec::new();
+            let mut clipboard_data = String::new();
+            let mut file_stats = Vec::new();
+            let mut manifest_entries = Vec::new();
+            let mut dependencies = String::new();
+            let target = parse_arg(matches, "target", Message::ValueTarget)?.unwrap_or(Target::Snes);
+            let mut symbols = String::from("[labels]\n");
+            let mut symbol_address = parse_address(matches, "symbol-base", Message::ValueSymbolBase)?.unwrap_or(0);
+
+            // Only display the progress bars if there is a lot to do.
+            let progress = if matches.is_present("quiet") {
+                MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+            }
+            else {
+                MultiProgress::new()
+            };
+            let file_progress = if input_names.len() > 1 {
+                progress.add(new_progress_bar(input_names.len()))
+            }
+            else {
+                ProgressBar::hidden()
+            };
+
+            for input_name in &input_names {
+                file_progress.set_message(input_name.to_string());
+
+                let (tables, image_data) = rip_image(input_name, matches, check_only, &progress)?;
+                if matches.is_present("verify-asm") {
+                    verify_tables(input_name, &tables.text, matches)?;
+                }
+
+                // A single image is written to the output name as is.
+                // Multiple images (e.g. several files dropped onto the executable) get an ASM file each
+                // which is named after the image.
+                let output_path = if !write_output {
+                    None
+                }
+                else if input_names.len() == 1 {
+                    Some(PathBuf::from(matches.value_of("output").unwrap_or("gradient.asm")))
+                }
+                else {
+                    Some(batch_output_path(input_name, matches.value_of("output")))
+                };
+
+                let mut written_files = match &output_path {
+                    Some(path) => output_files(path, &tables, matches.value_of("format"), target),
+                    None => Vec::new()
+                };
+                // The defines go next to the tables, e.g. gradient.inc for gradient.asm.
+                if let (Some(path), true) = (&output_path, matches.is_present("defines")) {
+                    written_files.push((path.with_extension("inc"), table_defines(&tables, &number_format(matches)).into_bytes()));
+                }
+                for (path, data) in &written_files {
+                    if check_only {
+                        if !is_up_to_date(data, path) {
+                            outdated_files.push(path.display().to_string());
+                        }
+                    }
+                    else {
+                        write_file(data, path)?;
+                    }
+                }
+
+                if copy_to_clipboard {
+                    clipboard_data.push_str(&tables.text);
+                }
+
+                symbols.push_str(&symbol_entries(&tables.stats, &mut symbol_address));
+                file_stats.push(stats_to_json(input_name, output_path.as_deref(), &tables));
+
+                // The manifest is only written for batches.
+                if let (Some(path), true) = (&output_path, input_names.len() > 1 && !check_only) {
+                    manifest_entries.push(manifest_entry(input_name, &image_data, path, &tables));
+                }
+                // Links can't be tracked by Make.
+                if !source::is_url(input_name) {
+                    for (path, _) in &written_files {
+                        dependencies.push_str(&format!("{}: {}\n", escape_make_path(&path.to_string_lossy()), escape_make_path(input_name)));
+                    }
+                }
+
+                file_progress.inc(1);
+            }
+
+            file_progress.finish_and_clear();
+
+            if copy_to_clipboard {
+                write_clipboard(clipboard_data)?;
+            }
+
+            if check_only {
+                if !outdated_files.is_empty() {
+                    return Err(RipperError::OutdatedOutput(tr!(Message::FilesOutdated, outdated_files.join(", "))));
+                }
+                log::info!("{}", tr!(Message::TablesUpToDate));
+            }
+
+            // Batches get a list of everything which has been generated.
+            if input_names.len() > 1 && !manifest_entries.is_empty() && !check_only {
+                let manifest_path = Path::new(matches.value_of("output").unwrap_or(".")).join("manifest.json");
+                write_report(&format!("{{\"files\":[{}]}}\n", manifest_entries.join(",")), &manifest_path.to_string_lossy())?;
+            }
+
+            if let Some(depfile_name) = matches.value_of("depfile") {
+                if !check_only {
+                    write_report(&dependencies, depfile_name)?;
+                }
+            }
+
+            if let Some(symbols_name) = matches.value_of("symbols") {
+                if !check_only {
+                    write_report(&symbols, symbols_name)?;
+                }
+            }
+
+            if let Some(stats_name) = matches.value_of("stats-json") {
+                if !check_only {
+                    write_report(&format!("{{\"files\":[{}]}}\n", file_stats.join(",")), stats_name)?;
+                }
+            }
+
+            return Ok(());
+        },
+        None => {
+            let mut input_name = String::new();
+
+            println!("Rust Gradient Ripper\n");
+
+            print!("{}", tr!(Message::PromptImage));
+
+            std::io::stdout().flush().unwrap();
+
+            read_line(&mut input_name)?;
+
+            let input_name = input_name.trim();
+
+            // Load up the image since we need its data.
+            let image = source::load_image(input_name, &LoadSettings::default())?;
+
+            print!("{}", tr!(Message::PromptOutput));
+
+            std::io::stdout().flush().unwrap();
+
+            let mut output_name = String::new();
+
+            read_line(&mut output_name)?;
+        
+            let image_height = image.height();
+
+            // Get additional data
+            let height = if image_height < MAX_SCANLINES {MAX_SCANLINES} else {image_height};
+            let settings = RipSettings {
+                height: height,
+                scanlines: MAX_SCANLINES,
+                interlace: None,
+                line_offset: 0,
+                reserve_top: 0,
+                reserve_colour: None,
+                x_pos: 0,
+                x_end: 0,
+                sample_radius: 0,
+                denoise: None,
+                smooth: None,
+                dither: None,
+                max_levels: None,
+                cluster_levels: false,
+                palette: None,
+                merge_space: None,
+                merge_tolerance: filters::JUST_NOTICEABLE_DIFFERENCE,
+                resample: Resample::Nearest,
+                mirror: None,
+                stride: 1,
+                extrapolate: false,
+                pad_to: None,
+                loop_blend: None,
+                hold_colour: None,
+                hold_transparent: false,
+                gamma: 1.0,
+                compensate_half: false,
+                invert: false,
+                hue_shift: 0,
+                brightness: 0,
+                contrast: 0,
+                saturation: 0,
+                y_start: 0,
+                y_end: image_height,
+                ranges: Vec::new(),
+                range_filler: 0,
+                mode: if height > MAX_SCANLINES {HdmaColourMode::BigGradient} else {HdmaColourMode::FixedClourTwo},
+                extra_modes: Vec::new(),
+                cgram_index: None,
+                cgram_columns: Vec::new(),
+                cgram_pair: false,
+                brightness_scale: 1.0,
+                mosaic_layers: 0x0F,
+                merge_channels: false,
+                merge_grayscale: true,
+                channels: 0xE0,
+                math_values: (0x20, 0x00),
+                math_threshold: 0x80,
+                split_size: SPLIT_SIZE,
+                driver: OutputDriver::Hdma,
+                scroll_routine: None,
+                optimise: OPTIMISE_TABLE,
+                number_format: NumberFormat::default(),
+                write_mode: None,
+                big_endian: false,
+                swap_words: false,
+                label_prefix: String::new(),
+                label_suffix: String::new()
+            };
+
+            log_settings(input_name, image_height, &settings);
+
+            let output_name = output_name.trim();
+
+            let output_path = Path::new(&output_name);
+
+            let output_data = colour::write_table(&settings, image, &ProgressBar::hidden());
+
+            return write_file((header::write_header(input_name, &settings, &[]) + &output_data.text).as_bytes(), output_path);
+        }
+    }
+}
+
+// Rips a single image with the settings from the command line.
+// When only checking, the reports and images which come along with the tables aren't written either.
+// The data of the image is returned as well so that links don't have to be downloaded again, e.g. for the manifest.
+fn rip_image(input_name: &str, matches: &ArgMatches, check_only: bool, progress: &MultiProgress) -> Result<(RippedTables, Vec<u8>), RipperError> {
+    // Load up the image since we need its data.
+    let image_data = source::read_source(input_name)?;
+    let load_settings = LoadSettings {
+        alpha: parse_arg(matches, "alpha", Message::ValueAlpha)?.unwrap_or(AlphaPolicy::Ignore),
+        tone_map: parse_arg(matches, "tone-map", Message::ValueToneMap)?.unwrap_or(ToneMap::Clamp),
+        exposure: parse_arg(matches, "exposure", Message::ValueExposure)?.unwrap_or(0.0),
+        frame: parse_arg(matches, "frame", Message::ValueFrame)?
+    };
+    let alpha = load_settings.alpha;
+    let animate = matches.is_present("animate");
+    let (frames, source_colours) = if animate {
+        source::load_frames(input_name, &image_data, &load_settings)?
+    }
+    else {
+        let (image, source_colours) = source::decode_image(input_name, &image_data, &load_settings)?;
+        (vec![(image, 0)], source_colours)
+    };
+
+    // Horizontal gradients are simply flipped so that the row becomes a column.
+    let frames: Vec<_> = if matches.is_present("transpose") {
+        frames.iter().map(|(image, delay)| (source::transpose(image), *delay)).collect()
+    }
+    else {
+        frames
+    };
+
+    // Every frame has got the same size so the first one is enough for checking the input.
+    // An animation without any frames has got nothing to rip though, everything below relies on the first frame.
+    let image = match frames.first() {
+        Some((image, _)) => image,
+        None => return Err(RipperError::BadArguments(tr!(Message::NoFrames, input_name)))
+    };
+
+    let image_height = image.height();
+
+    // Get the command line input for in- and output (or replace them with default values).
+    // Other consoles only get the colours of a single table, the SNES specific options don't apply to them.
+    let target = parse_arg(matches, "target", Message::ValueTarget)?.unwrap_or(Target::Snes);
+    if target != Target::Snes && (!["asm", "bin", "c", "json"].contains(&matches.value_of("format").unwrap_or("asm"))
+        || matches.value_of("mode").is_some_and(|modes| modes.contains(','))
+        || ["verify-asm", "asserts", "size-prints", "macros", "namespace", "big-endian", "swap-words", "write-mode", "interlace", "overscan",
+            "blend-with", "math", "preview", "scroll-preview", "defines", "dither", "max-levels"].iter().any(|name| matches.is_present(name))) {
+        return Err(RipperError::BadArguments(tr!(Message::TargetNeedsSnes)));
+    }
+    let scanlines = parse_scanlines(matches, target)?;
+    // An interlaced image has got two lines per scanline, one for each field.
+    let interlace = match matches.value_of("interlace") {
+        Some("doubled") => Some(Interlace::Doubled),
+        Some("fields") => Some(Interlace::Even),
+        _ => None
+    };
+    let lines = if interlace.is_some() {scanlines * 2} else {scanlines};
+    let height = parse_arg(matches, "height", Message::ValueHeight)?
+        .unwrap_or(if image_height < lines {lines} else {image_height});
+    let ranges = parse_ranges(matches)?;
+
+    // Multiple ranges replace the Y start and end.
+    let (y_start, y_end) = if ranges.is_empty() {
+        (
+            parse_arg(matches, "ystart", Message::ValueYPosition)?.unwrap_or(0),
+            parse_arg(matches, "yend", Message::ValueYPosition)?.unwrap_or(image_height)
+        )
+    }
+    else {
+        (
+            ranges.iter().map(|range| range.0).min().unwrap_or(0),
+            ranges.iter().map(|range| range.1).max().unwrap_or(image_height)
+        )
+    };
+    let cgram_index = parse_arg(matches, "cgram", Message::ValueCgramIndex)?;
+    let hold_colour = match matches.value_of("hold-colour") {
+        Some(text) => match colour::parse_hex_colour(text) {
+            Some(colour) => Some(colour),
+            None => return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueHoldColour), text)))
+        },
+        None => None
+    };
+
+    // Several modes are separated by commas. The first one is the main mode which every option refers to, the others
+    // are simply written after it.
+    let mut modes = Vec::new();
+    for text in matches.value_of("mode").unwrap_or("a").split(',') {
+        let mode = parse_mode(text.trim(), height, lines)?;
+        if modes.contains(&mode) {
+            return Err(RipperError::BadArguments(tr!(Message::ModeTwice, mode.name())));
+        }
+        modes.push(mode);
+    }
+    let mode = modes[0];
+    let extra_modes = modes[1..].to_vec();
+    source::report_conversion(input_name, &source_colours, mode);
+    // The options which don't work with a mode don't work with it as an extra mode either.
+    let any_mode = |check: fn(HdmaColourMode) -> bool| modes.iter().any(|mode| check(*mode));
+
+    // Big gradients are scrolled over which doesn't go together with switching the tables every frame.
+    if interlace == Some(Interlace::Even) && (mode == HdmaColourMode::BigGradient || !extra_modes.is_empty()) {
+        return Err(RipperError::BadArguments(tr!(Message::FieldsNeedTables)));
+    }
+
+    let channels = parse_channels(matches)?;
+    if channels != 0xE0 && mode != HdmaColourMode::FixedClourThree && mode != HdmaColourMode::FixedClourTwo {
+        return Err(RipperError::BadArguments(tr!(Message::ChannelsNeedFixedColour)));
+    }
+    let cgram_columns = parse_cgram_columns(matches)?;
+    if !cgram_columns.is_empty() && mode != HdmaColourMode::CgRam {
+        return Err(RipperError::BadArguments(tr!(Message::CgramColumnsNeedCgram)));
+    }
+    if cgram_columns.iter().any(|(x_pos, _)| *x_pos >= image.width()) {
+        return Err(RipperError::OutOfRange(tr!(Message::XOutsideImage)));
+    }
+    let cgram_pair = matches.is_present("cgram-pair");
+    if cgram_pair && mode != HdmaColourMode::CgRam {
+        return Err(RipperError::BadArguments(tr!(Message::CgramColumnsNeedCgram)));
+    }
+
+    // An IRQ only knows the registers of the colour itself.
+    let driver = parse_arg(matches, "driver", Message::ValueDriver)?.unwrap_or(OutputDriver::Hdma);
+    if driver == OutputDriver::Irq && (any_mode(|mode| mode == HdmaColourMode::Window)
+        || (any_mode(|mode| mode == HdmaColourMode::CgRam) && cgram_index.is_none())) {
+        return Err(RipperError::BadArguments(tr!(Message::IrqDriverMode)));
+    }
+
+    // Big gradients and IRQ tables aren't read by HDMA so there's nothing to swap.
+    let ram_buffer = if matches.is_present("ram-buffer") {
+        Some(parse_address(matches, "ram-buffer", Message::ValueRamBuffer)?.unwrap_or(0x7FB000))
+    }
+    else {
+        None
+    };
+    if ram_buffer.is_some() && (any_mode(|mode| mode == HdmaColourMode::BigGradient) || driver == OutputDriver::Irq) {
+        return Err(RipperError::BadArguments(tr!(Message::RamBufferMode)));
+    }
+
+    // Patches and uberASM files set up the channels of the main tables themselves which is why these have to be plain
+    // HDMA tables.
+    let patch = matches!(matches.value_of("format"), Some("asar-patch") | Some("uberasm"));
+    if patch && (any_mode(|mode| mode == HdmaColourMode::BigGradient) || driver == OutputDriver::Irq || animate
+        || matches.is_present("spotlight") || matches.is_present("backdrop")) {
+        return Err(RipperError::BadArguments(tr!(Message::PatchNeedsHdmaTables)));
+    }
+
+    let asserts = matches.is_present("asserts");
+    let size_prints = matches.is_present("size-prints");
+    if (asserts || size_prints) && (!["asm", "asar-patch", "uberasm"].contains(&matches.value_of("format").unwrap_or("asm"))
+        || matches.value_of("syntax").unwrap_or("asar") != "asar") {
+        return Err(RipperError::BadArguments(tr!(Message::AssertsNeedAsar)));
+    }
+    let size_budget = parse_arg(matches, "size-budget", Message::ValueSizeBudget)?;
+
+    if matches.is_present("scroll-preview") && mode != HdmaColourMode::BigGradient {
+        return Err(RipperError::BadArguments(tr!(Message::ScrollPreviewNeedsBig)));
+    }
+
+    let scroll_routine = parse_scroll_routine(matches)?;
+    if scroll_routine.is_some() && mode != HdmaColourMode::BigGradient {
+        return Err(RipperError::BadArguments(tr!(Message::ScrollRoutineNeedsBig)));
+    }
+
+    // Every part of a split table needs room for at least a few scanlines.
+    let split_size = parse_arg(matches, "split-size", Message::ValueSplitSize)?.unwrap_or(SPLIT_SIZE);
+    if split_size < MIN_SPLIT_SIZE {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueSplitSize), split_size)));
+    }
+
+    // Everything else (e.g. the routines of a patch) would refer to labels which only exist once the macros are called.
+    if matches.is_present("macros") && (matches.value_of("syntax").unwrap_or("asar") != "asar"
+        || matches.value_of("format").unwrap_or("asm") != "asm" || any_mode(|mode| mode == HdmaColourMode::BigGradient)) {
+        return Err(RipperError::BadArguments(tr!(Message::MacrosNeedPlainTables)));
+    }
+
+    // HDMA can't read padded scanlines so whatever sets up the channels would read the tables wrong.
+    if matches.is_present("write-mode") && (patch || ["spotlight", "backdrop", "defines"].iter().any(|name| matches.is_present(name))) {
+        return Err(RipperError::BadArguments(tr!(Message::WriteModeNeedsPlainTables)));
+    }
+
+    // Only tables which are written as words have got words to reorder. Without a write mode, these are the CG-RAM
+    // tables (IRQ tables and the window table of a spotlight are bytes).
+    let write_mode: Option<HdmaWriteMode> = parse_arg(matches, "write-mode", Message::ValueWriteMode)?;
+    let word_tables = match write_mode {
+        Some(write_mode) => matches!(write_mode, HdmaWriteMode::Words),
+        None => !any_mode(|mode| mode != HdmaColourMode::CgRam) && driver != OutputDriver::Irq && !matches.is_present("spotlight")
+    };
+    if (matches.is_present("big-endian") || matches.is_present("swap-words")) && !word_tables {
+        return Err(RipperError::BadArguments(tr!(Message::WordOrderNeedsWords)));
+    }
+
+    // The other assemblers only get the tables themselves (and the routine of a split big gradient).
+    if matches.value_of("syntax").unwrap_or("asar") != "asar" && (patch || animate || driver == OutputDriver::Irq
+        || interlace == Some(Interlace::Even) || ram_buffer.is_some() || scroll_routine.is_some()
+        || ["crossfade", "fade", "regions", "spotlight", "backdrop", "colour-math"].iter().any(|name| matches.is_present(name))) {
+        return Err(RipperError::BadArguments(tr!(Message::RoutinesNeedAsar)));
+    }
+
+    if matches.is_present("segment") && matches.value_of("syntax") != Some("ca65") {
+        return Err(RipperError::BadArguments(tr!(Message::SegmentNeedsCa65)));
+    }
+
+    // Handle errors (invalid inputs)
+    for (y_start, y_end) in ranges.iter().chain(std::iter::once(&(y_start, y_end))) {
+        if *y_start > image_height || *y_end > image_height {
+            return Err(RipperError::OutOfRange(tr!(Message::YOutsideImage)));
+        }
+        if y_start >= y_end {
+            return Err(RipperError::OutOfRange(tr!(Message::YStartBelowEnd)));
+        }
+    }
+
+    // "full" is just a shorthand for averaging from the very left to the very right.
+    // "auto" needs a valid Y range which is why the X position is only read after it has been checked.
+    // Both pick the columns by themselves so an X end would just be ignored.
+    let x_keyword = matches.value_of("xpos") == Some("full") || matches.value_of("xpos") == Some("auto");
+    if x_keyword && matches.is_present("x-end") {
+        return Err(RipperError::BadArguments(tr!(Message::XEndWithoutXPosition)));
+    }
+    let (x_pos, x_end) = match matches.value_of("xpos") {
+        Some("full") => (0, image.width() - 1),
+        Some("auto") => {
+            let x_pos = colour::find_cleanest_column(image, y_start, y_end);
+            log::info!("{}", tr!(Message::ColumnChosen, x_pos));
+            (x_pos, x_pos)
+        }
+        _ => {
+            let x_pos = parse_arg(matches, "xpos", Message::ValueXPosition)?.unwrap_or(0);
+            (x_pos, parse_arg(matches, "x-end", Message::ValueXPosition)?.unwrap_or(x_pos))
+        }
+    };
+
+    if x_pos >= image.width() || x_end >= image.width() {
+        return Err(RipperError::OutOfRange(tr!(Message::XOutsideImage)));
+    }
+    if x_end < x_pos {
+        return Err(RipperError::OutOfRange(tr!(Message::XEndBeforeStart)));
+    }
+    // The second colour of a pair is sampled right after the first one.
+    if cgram_pair && x_end + (x_end - x_pos + 1) >= image.width() {
+        return Err(RipperError::OutOfRange(tr!(Message::XOutsideImage)));
+    }
+
+    // Without a scanline, the gradient is mirrored in the middle.
+    let mirror = if matches.is_present("mirror") {
+        Some(parse_arg(matches, "mirror", Message::ValueMirror)?.unwrap_or(height / 2))
+    }
+    else {
+        None
+    };
+
+    if mirror.is_some_and(|scanline| scanline == 0 || scanline > height) {
+        return Err(RipperError::OutOfRange(tr!(Message::MirrorOutsideTable)));
+    }
+
+    // SMW's status bar takes up the top 32 scanlines. There has to be at least a scanline left for the gradient.
+    let reserve_top = if matches.is_present("reserve-top") {
+        parse_arg(matches, "reserve-top", Message::ValueReserveTop)?.unwrap_or(STATUS_BAR_SCANLINES)
+    }
+    else {
+        0
+    };
+    if reserve_top >= scanlines {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueReserveTop), reserve_top)));
+    }
+    let reserve_colour = match matches.value_of("reserve-colour") {
+        Some(text) => match colour::parse_hex_colour(text) {
+            Some(colour) => Some(colour),
+            None => return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueReserveColour), text)))
+        },
+        None => None
+    };
+
+    // The table can't be moved out of the screen entirely.
+    let line_offset: i32 = parse_arg(matches, "line-offset", Message::ValueLineOffset)?.unwrap_or(0);
+    if line_offset.unsigned_abs() >= height {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueLineOffset), line_offset)));
+    }
+
+    let stride = parse_arg(matches, "stride", Message::ValueStride)?.unwrap_or(1);
+    if stride == 0 {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueStride), stride)));
+    }
+
+    let hue_variants = parse_hue_variants(matches)?;
+    let math_variants = parse_math_variants(matches);
+    if math_variants.iter().any(|(_, invert)| *invert) && mode != HdmaColourMode::FixedClourThree
+        && mode != HdmaColourMode::FixedClourTwo && mode != HdmaColourMode::BigGradient {
+        return Err(RipperError::BadArguments(tr!(Message::MathVariantsNeedFixedColour)));
+    }
+    let blend_steps = parse_blend_steps(matches)?;
+    let fade_steps = parse_fade_steps(matches)?;
+
+    // The backdrop is either a column of the same image or another image which has to be just as large.
+    let backdrop = match matches.value_of("backdrop") {
+        Some(backdrop) => {
+            if mode != HdmaColourMode::FixedClourThree && mode != HdmaColourMode::FixedClourTwo {
+                return Err(RipperError::BadArguments(tr!(Message::BackdropNeedsFixedColour)));
+            }
+
+            match backdrop.parse::<u32>() {
+                Ok(x_pos) if x_pos >= image.width() => return Err(RipperError::OutOfRange(tr!(Message::XOutsideImage))),
+                Ok(x_pos) => Some((image.clone(), Some(x_pos))),
+                Err(_) => {
+                    let backdrop_image = source::load_image(backdrop, &load_settings)?;
+                    let backdrop_image = if matches.is_present("transpose") {source::transpose(&backdrop_image)} else {backdrop_image};
+
+                    if backdrop_image.dimensions() != image.dimensions() {
+                        return Err(RipperError::BadArguments(tr!(Message::SizeMismatch, backdrop, input_name)));
+                    }
+                    Some((backdrop_image, None))
+                }
+            }
+        }
+        None => None
+    };
+
+    // The mask is read as is, the window is always horizontal.
+    let spotlight_mask = match matches.value_of("spotlight") {
+        Some(mask_name) => {
+            if mode != HdmaColourMode::FixedClourThree && mode != HdmaColourMode::FixedClourTwo {
+                return Err(RipperError::BadArguments(tr!(Message::SpotlightNeedsFixedColour)));
+            }
+
+            let mask = source::load_image(mask_name, &LoadSettings::default())?;
+            if mask.dimensions() != image.dimensions() {
+                return Err(RipperError::BadArguments(tr!(Message::SizeMismatch, mask_name, input_name)));
+            }
+            Some(mask)
+        }
+        None => None
+    };
+    // The math mode uses the colour math settings for the table (and only adds the routine if it's been asked for).
+    let colour_math = codegen::ColourMath {
+        subtract: matches.value_of("colour-math") == Some("subtract"),
+        half: matches.is_present("half"),
+        layers: parse_math_layers(matches, "math-layers", "b")?
+    };
+    let math_layers_off = parse_math_layers(matches, "math-layers-off", "")?;
+    if matches.is_present("colour-math") && mode != HdmaColourMode::FixedClourThree && mode != HdmaColourMode::FixedClourTwo
+        && mode != HdmaColourMode::BigGradient && mode != HdmaColourMode::ColourMath {
+        return Err(RipperError::BadArguments(tr!(Message::ColourMathNeedsFixedColour)));
+    }
+    if fade_steps.len() > 1 && mode != HdmaColourMode::Brightness {
+        return Err(RipperError::BadArguments(tr!(Message::FadeNeedsBrightness)));
+    }
+
+    // The second image is sampled at the same position which is why it has to be just as large.
+    let blend_image = match matches.value_of("blend-with") {
+        Some(blend_name) => {
+            let blend_image = source::load_image(blend_name, &load_settings)?;
+            let blend_image = if matches.is_present("transpose") {source::transpose(&blend_image)} else {blend_image};
+
+            if blend_image.dimensions() != image.dimensions() {
+                return Err(RipperError::BadArguments(tr!(Message::SizeMismatch, blend_name, input_name)));
+            }
+            Some(blend_image)
+        }
+        None => None
+    };
+
+    let smooth = parse_arg(matches, "smooth", Message::ValueSmooth)?;
+    if smooth == Some(0) {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueSmooth), 0)));
+    }
+
+    let max_levels = parse_arg(matches, "max-levels", Message::ValueMaxLevels)?;
+    if let Some(levels) = max_levels {
+        if !(2..=32).contains(&levels) {
+            return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueMaxLevels), levels)));
+        }
+    }
+
+    // A tolerance on its own compares the colours the way they're perceived.
+    let merge_space = match parse_arg(matches, "merge-space", Message::ValueMergeSpace)? {
+        None if matches.is_present("tolerance") => Some(ColourSpace::OkLab),
+        space => space
+    };
+    // Without a tolerance, RGB only merges the colours which are exactly the same on the SNES.
+    let default_tolerance = if merge_space == Some(ColourSpace::Rgb) {0.0} else {filters::JUST_NOTICEABLE_DIFFERENCE};
+    let merge_tolerance: f64 = parse_arg(matches, "tolerance", Message::ValueTolerance)?.unwrap_or(default_tolerance);
+    if !(merge_tolerance >= 0.0 && merge_tolerance.is_finite()) {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueTolerance), merge_tolerance)));
+    }
+
+    let gamma: f32 = parse_arg(matches, "gamma", Message::ValueGamma)?.unwrap_or(1.0);
+    if !(gamma > 0.0 && gamma.is_finite()) {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueGamma), gamma)));
+    }
+
+    // Without a row count, an eighth of the table is blended.
+    let loop_blend = if matches.is_present("loop") {
+        Some(parse_arg(matches, "loop", Message::ValueLoop)?.unwrap_or((height / 8).max(1)))
+    }
+    else {
+        None
+    };
+
+    // Handle warnings (questionable inputs)
+    if height < lines {
+        log::warn!("{}", tr!(Message::HeightTooSmall, height, lines));
+    }
+
+    if (mode != HdmaColourMode::BigGradient) & (height > lines) {
+        log::warn!("{}", tr!(Message::HeightTooLarge, height, lines));
+    }
+
+    let mut settings = RipSettings {
+        height: height,
+        scanlines: scanlines,
+        interlace: interlace,
+        line_offset: line_offset,
+        reserve_top: reserve_top,
+        reserve_colour: reserve_colour,
+        x_pos: x_pos,
+        x_end: x_end,
+        sample_radius: parse_arg(matches, "sample-radius", Message::ValueSampleRadius)?.unwrap_or(0),
+        denoise: parse_arg(matches, "denoise", Message::ValueDenoise)?,
+        smooth: smooth,
+        dither: parse_arg(matches, "dither", Message::ValueDither)?,
+        max_levels: max_levels,
+        cluster_levels: matches.is_present("kmeans"),
+        palette: match source_colours {
+            SourceColours::Indexed(palette) => Some(palette),
+            _ => None
+        },
+        merge_space: merge_space,
+        merge_tolerance: merge_tolerance,
+        resample: parse_arg(matches, "resample", Message::ValueResample)?.unwrap_or(Resample::Nearest),
+        mirror: mirror,
+        stride: stride,
+        extrapolate: matches.is_present("extrapolate"),
+        pad_to: parse_arg(matches, "pad-to", Message::ValuePadTo)?,
+        loop_blend: loop_blend,
+        hold_colour: hold_colour,
+        hold_transparent: alpha == AlphaPolicy::Hold,
+        gamma: gamma,
+        compensate_half: matches.is_present("compensate-half"),
+        invert: false,
+        hue_shift: 0,
+        brightness: parse_percentage(matches, "brightness", Message::ValueBrightness)?,
+        contrast: parse_percentage(matches, "contrast", Message::ValueContrast)?,
+        saturation: parse_percentage(matches, "saturation", Message::ValueSaturation)?,
+        y_start: y_start,
+        y_end: y_end,
+        ranges: ranges,
+        range_filler: parse_arg(matches, "range-filler", Message::ValueRangeFiller)?.unwrap_or(0),
+        mode: mode,
+        extra_modes: extra_modes,
+        cgram_index: cgram_index,
+        cgram_columns: cgram_columns,
+        cgram_pair: cgram_pair,
+        brightness_scale: 1.0,
+        mosaic_layers: parse_mosaic_layers(matches)?,
+        merge_channels: matches.is_present("merge-channels"),
+        merge_grayscale: !["animate", "steps", "spotlight", "backdrop"].iter().any(|name| matches.is_present(name)),
+        channels: channels,
+        math_values: (colour_math.cgadsub(colour_math.layers), colour_math.cgadsub(math_layers_off)),
+        math_threshold: parse_arg(matches, "threshold", Message::ValueThreshold)?.unwrap_or(0x80),
+        split_size: split_size,
+        driver: driver,
+        scroll_routine: scroll_routine,
+        optimise: OPTIMISE_TABLE,
+        number_format: number_format(matches),
+        write_mode: write_mode,
+        big_endian: matches.is_present("big-endian"),
+        swap_words: matches.is_present("swap-words"),
+        label_prefix: String::from(matches.value_of("label-prefix").unwrap_or("")),
+        label_suffix: String::new()
+    };
+
+    log_settings(input_name, image_height, &settings);
+
+    // Very tall tables take a while so display how far they are.
+    let table_progress = if height >= LARGE_TABLE_HEIGHT {
+        progress.add(new_progress_bar(0))
+    }
+    else {
+        ProgressBar::hidden()
+    };
+
+    let header = header::write_header(input_name, &settings, &header::recorded_options(matches));
+    let header_length = header.len();
+
+    let delays: Vec<u32> = frames.iter().map(|(_, delay)| *delay).collect();
+    if animate && codegen::animation_too_long(&delays) {
+        log::warn!("{}", tr!(Message::AnimationTooLong));
+    }
+
+    // The colours are the ones of the main table, i.e. without any of the variants.
+    if let Some(csv_name) = matches.value_of("export-colours").filter(|_| !check_only) {
+        write_report(&colour::colours_to_csv(&settings, &frames[0].0), csv_name)?;
+    }
+    if let Some(compare_name) = matches.value_of("compare").filter(|_| !check_only) {
+        let (original, colours) = colour::compared_colours(&settings, &frames[0].0);
+        let image = preview::render_comparison(&original, &colours);
+        if let Err(why) = image.save(compare_name) {
+            return Err(RipperError::WriteFailure(tr!(Message::WriteFileFailed, compare_name, why)));
+        }
+    }
+    if let Some(palette_name) = matches.value_of("export-palette").filter(|_| !check_only) {
+        write_palette(&colour::cgram_colours(&settings, &frames[0].0), palette_name)?;
+    }
+
+    if target != Target::Snes {
+        let label = format!("{}gradient_table", settings.label_prefix);
+        let (text, stats) = if target == Target::Gba {
+            target::gba_table(&label, &colour::cgram_colours(&settings, &frames[0].0))
+        }
+        else {
+            target::md_table(&label, &colour::sampled_colours(&settings, &frames[0].0))
+        };
+        table_progress.finish_and_clear();
+        return Ok((RippedTables { text: target.comments(&header) + &text, mode: settings.mode, stats: vec![stats] }, image_data));
+    }
+
+    // The original gradient comes first, followed by its recoloured copies. Each of them comes in every colour math
+    // variant and for every region.
+    let regions = parse_regions(matches, settings.height, settings.pad_to)?;
+    let variants: Vec<(i32, (&str, bool), Region)> = std::iter::once(0).chain(hue_variants)
+        .flat_map(|hue_shift| math_variants.iter().map(move |variant| (hue_shift, *variant)))
+        .flat_map(|(hue_shift, math_variant)| regions.iter().map(move |region| (hue_shift, math_variant, *region)))
+        .collect();
+    let mut patch_channels = Vec::new();
+    let mut output_data = RippedTables { text: header, mode: settings.mode, stats: Vec::new() };
+    if let Some(segment) = matches.value_of("segment") {
+        output_data.text.push_str(&format!(".segment \"{}\"\n\n", segment));
+    }
+    for (index, (hue_shift, (math_suffix, invert), (region_suffix, region_height, region_pad_to))) in variants.into_iter().enumerate() {
+        let hue_suffix = if hue_shift == 0 {String::new()} else {format!("_hue{}", hue_shift)};
+        let suffix = format!("{}{}{}", hue_suffix, math_suffix, region_suffix);
+        settings.hue_shift = hue_shift;
+        settings.invert = invert;
+        settings.height = region_height;
+        settings.pad_to = region_pad_to;
+
+        if animate {
+            let tables = rip_animation(&frames, &mut settings, &suffix, &table_progress);
+            if index > 0 {
+                output_data.text.push('\n');
+            }
+            output_data.text.push_str(&tables.text);
+            output_data.stats.extend(tables.stats);
+            continue;
+        }
+
+        // Blending and fading can't be combined so only one of them has got more than one step.
+        let mut labels = Vec::new();
+        for (step, ((blend_suffix, factor), (fade_suffix, scale))) in blend_steps.iter().flat_map(|blend_step| {
+            fade_steps.iter().map(move |fade_step| (blend_step, fade_step))
+        }).enumerate() {
+            let field_suffix = if interlace == Some(Interlace::Even) {"_even"} else {""};
+            settings.label_suffix = format!("{}{}{}{}", suffix, blend_suffix, fade_suffix, field_suffix);
+            settings.brightness_scale = *scale;
+            let blend = blend_image.as_ref().map(|blend_image| (blend_image, *factor));
+            let tables = colour::write_blended_table(&settings, &frames[0].0, blend, &table_progress);
+
+            if index > 0 || step > 0 {
+                output_data.text.push('\n');
+            }
+            output_data.text.push_str(&tables.text);
+            labels.push(tables.stats.iter().map(|stats| stats.name.clone()).collect());
+
+            // The preview is of the main tables, not any of their variants.
+            if index == 0 && step == 0 {
+                patch_channels = table_channels(&tables.stats);
+            }
+            if let (Some(preview_name), 0, 0) = (matches.value_of("preview").filter(|_| !check_only), index, step) {
+                write_preview(settings.mode, &tables.stats, preview_name)?;
+            }
+            if let (Some(preview_name), 0, 0) = (matches.value_of("scroll-preview").filter(|_| !check_only), index, step) {
+                write_scroll_preview(&tables.stats, settings.scanlines, preview_name)?;
+            }
+            output_data.stats.extend(tables.stats);
+
+            // The odd field gets its own copy of the tables which the driver switches to every other frame.
+            if interlace == Some(Interlace::Even) {
+                let odd_suffix = format!("{}{}{}", suffix, blend_suffix, fade_suffix);
+                let odd_settings = RipSettings {
+                    interlace: Some(Interlace::Odd),
+                    label_suffix: format!("{}_odd", odd_suffix),
+                    ..settings.clone()
+                };
+                let odd_tables = colour::write_blended_table(&odd_settings, &frames[0].0, blend, &table_progress);
+                let even_labels: Vec<String> = labels.last().cloned().unwrap_or_default();
+                let odd_labels: Vec<String> = odd_tables.stats.iter().map(|stats| stats.name.clone()).collect();
+
+                output_data.text.push('\n');
+                output_data.text.push_str(&odd_tables.text);
+                output_data.text.push('\n');
+                output_data.text.push_str(&codegen::field_driver(&even_labels, &odd_labels, &odd_suffix));
+                output_data.stats.extend(odd_tables.stats);
+            }
+
+            // The tables of the other modes are suffixed with the mode since e.g. single and double both have got a red table.
+            for extra_mode in &settings.extra_modes {
+                let extra_settings = RipSettings {
+                    mode: *extra_mode,
+                    label_suffix: format!("{}_{}", settings.label_suffix, extra_mode.name()),
+                    ..settings.clone()
+                };
+                let tables = colour::write_blended_table(&extra_settings, &frames[0].0, blend, &table_progress);
+                output_data.text.push('\n');
+                output_data.text.push_str(&tables.text);
+                if index == 0 && step == 0 {
+                    patch_channels.extend(table_channels(&tables.stats));
+                }
+                output_data.stats.extend(tables.stats);
+            }
+        }
+
+        if matches.is_present("crossfade") {
+            output_data.text.push('\n');
+            output_data.text.push_str(&codegen::crossfade_driver(&labels, &suffix, &settings.number_format));
+        }
+        if fade_steps.len() > 1 {
+            output_data.text.push('\n');
+            output_data.text.push_str(&codegen::fade_driver(&labels, &suffix, &settings.number_format));
+        }
+    }
+
+    if regions.len() > 1 {
+        output_data.text.push('\n');
+        output_data.text.push_str(&codegen::region_define());
+    }
+
+    if let Some(ram) = ram_buffer {
+        let tables: Vec<(String, usize)> = output_data.stats.iter().map(|stats| (stats.name.clone(), stats.byte_size)).collect();
+        output_data.text.push('\n');
+        output_data.text.push_str(&codegen::double_buffer(&tables, ram, &settings.number_format));
+    }
+
+    if matches.is_present("colour-math") {
+        output_data.text.push('\n');
+        output_data.text.push_str(&codegen::colour_math_init(&colour_math, ""));
+    }
+
+    if let Some((backdrop_image, x_pos)) = backdrop {
+        output_data.text.push('\n');
+        output_data.text.push_str(&rip_backdrop(&backdrop_image, x_pos, &settings, &mut output_data.stats, &table_progress));
+    }
+
+    if let Some(mask) = spotlight_mask {
+        output_data.text.push('\n');
+        output_data.text.push_str(&rip_spotlight(&mask, &mut settings, &mut output_data.stats, &table_progress));
+    }
+
+    let table_sizes: Vec<(String, usize)> = output_data.stats.iter().map(|stats| (stats.name.clone(), stats.data.len())).collect();
+    if asserts {
+        // Only big gradients are checked against the budget, the other tables mustn't cross a bank border.
+        let tables: Vec<(String, usize, bool)> = output_data.stats.iter()
+            .map(|stats| (stats.name.clone(), stats.data.len(), stats.mode == HdmaColourMode::BigGradient)).collect();
+        output_data.text.push('\n');
+        output_data.text.push_str(&codegen::size_asserts(&tables, size_budget));
+    }
+    if size_prints {
+        output_data.text.push('\n');
+        output_data.text.push_str(&codegen::size_prints(&table_sizes));
+    }
+
+    // The header stays at the top, everything else goes into the patch.
+    if patch {
+        let tables = output_data.text.split_off(header_length);
+        if matches.value_of("format") == Some("uberasm") {
+            let overworld = matches.value_of("uberasm-type") == Some("overworld");
+            output_data.text.push_str(&codegen::uberasm(&patch_channels, &tables, overworld));
+        }
+        else {
+            output_data.text.push_str(&codegen::asar_patch(&patch_channels, &tables));
+        }
+    }
+
+    // The namespace goes around everything but the header, including the patch.
+    if let Some(namespace) = matches.value_of("namespace") {
+        let body = output_data.text.split_off(header_length);
+        output_data.text.push_str(&codegen::namespace(namespace, &body, &settings.number_format));
+    }
+
+    table_progress.finish_and_clear();
+
+    return Ok((output_data, image_data));
+}
+
+// How each table is transferred, e.g. for the patch which sets up their channels.
+// Fixed colour tables with two bytes write both of them to $2132 and CG-RAM tables with four bytes write the index
+// twice to $2121 followed by the colour. Big gradients and IRQ tables aren't read by HDMA directly.
+fn table_channels(stats: &[colour::TableStats]) -> Vec<codegen::Channel> {
+    return stats.iter().filter_map(|stats| {
+        let (transfer_mode, register) = table_transfer(stats)?;
+        Some(codegen::Channel { label: stats.name.clone(), transfer_mode: transfer_mode, register: register })
+    }).collect();
+}
+
+// The transfer mode and the register of a single table.
+fn table_transfer(stats: &colour::TableStats) -> Option<(u8, u8)> {
+    return match (stats.mode, stats.scanline_size?) {
+        (HdmaColourMode::FixedClourThree, 1) | (HdmaColourMode::FixedClourTwo, 1) => Some((0x00, 0x32)),
+        (HdmaColourMode::FixedClourThree, _) | (HdmaColourMode::FixedClourTwo, _) => Some((0x02, 0x32)),
+        (HdmaColourMode::CgRam, 4) => Some((0x03, 0x21)),
+        (HdmaColourMode::CgRam, _) => Some((0x02, 0x22)),
+        (HdmaColourMode::Brightness, _) => Some((0x00, 0x00)),
+        (HdmaColourMode::Mosaic, _) => Some((0x00, 0x06)),
+        (HdmaColourMode::Window, _) => Some((0x01, 0x26)),
+        (HdmaColourMode::ColourMath, _) => Some((0x00, 0x31)),
+        (HdmaColourMode::BigGradient, _) => None
+    };
+}
+
+// The scanline count, the length and the transfer mode of every table for the defines file.
+fn table_defines(tables: &RippedTables, format: &NumberFormat) -> String {
+    let defines: Vec<(String, usize, usize, Option<u8>)> = tables.stats.iter().map(|stats| (stats.name.clone(),
+        stats.scanline_values().len(), stats.data.len(), table_transfer(stats).map(|(transfer_mode, _)| transfer_mode))).collect();
+    return codegen::table_defines(&defines, format);
+}
+
+// Adds the window table of a spotlight to the colour tables which have already been ripped along with its init routine.
+fn rip_spotlight(mask: &ImageBuffer<Rgba<u8>, Vec<u8>>, settings: &mut RipSettings, stats: &mut Vec<colour::TableStats>, progress: &ProgressBar) -> String {
+    // The tables of the extra modes are set up along with the fixed colour tables.
+    let mut channels = table_channels(stats);
+
+    let colour_mode = settings.mode;
+    settings.mode = HdmaColourMode::Window;
+    let window = colour::write_table(settings, mask.clone(), progress);
+    settings.mode = colour_mode;
+
+    // Both window positions are written at once.
+    channels.extend(window.stats.iter().map(|stats| codegen::Channel { label: stats.name.clone(), transfer_mode: 0x01, register: 0x26 }));
+    stats.extend(window.stats);
+
+    return format!("{}\n{}", window.text, codegen::spotlight_init(&channels, &settings.label_suffix));
+}
+
+// Adds the backdrop table of a layered sky to the fixed colour tables which have already been ripped along with its
+// init routine. Without a column, the backdrop is sampled at the same position as the fixed colour.
+fn rip_backdrop(image: &ImageBuffer<Rgba<u8>, Vec<u8>>, x_pos: Option<u32>, settings: &RipSettings, stats: &mut Vec<colour::TableStats>, progress: &ProgressBar) -> String {
+    // The tables of the extra modes are set up along with the fixed colour tables.
+    let mut channels = table_channels(stats);
+
+    // The backdrop is on the main screen so it's neither doubled nor inverted.
+    let backdrop_settings = RipSettings {
+        mode: HdmaColourMode::CgRam,
+        cgram_index: Some(0),
+        x_pos: x_pos.unwrap_or(settings.x_pos),
+        x_end: x_pos.unwrap_or(settings.x_end),
+        compensate_half: false,
+        invert: false,
+        label_suffix: format!("{}_backdrop", settings.label_suffix),
+        ..settings.clone()
+    };
+    let backdrop = colour::write_table(&backdrop_settings, image.clone(), progress);
+
+    // The colour index is written twice to $2121, followed by the colour which is written twice to $2122.
+    channels.extend(backdrop.stats.iter().map(|stats| codegen::Channel { label: stats.name.clone(), transfer_mode: 0x03, register: 0x21 }));
+    stats.extend(backdrop.stats);
+
+    return format!("{}\n{}", backdrop.text, codegen::backdrop_init(&channels, &settings.label_suffix));
+}
+
+// Rips every frame into its own set of tables (suffixed with the frame number) and adds the driver which switches between them.
+fn rip_animation(frames: &[(RgbaImage, u32)], settings: &mut RipSettings, suffix: &str, progress: &ProgressBar) -> RippedTables {
+    let delays: Vec<u32> = frames.iter().map(|(_, delay)| *delay).collect();
+    let mut output = RippedTables { text: String::new(), mode: settings.mode, stats: Vec::new() };
+    let mut labels = Vec::new();
+
+    for (index, (image, _)) in frames.iter().enumerate() {
+        settings.label_suffix = format!("{}_frame{}", suffix, index);
+        let tables = colour::write_table(settings, image.clone(), progress);
+
+        output.text.push_str(&format!("{}\n", tables.text));
+        labels.push(tables.stats.iter().map(|stats| stats.name.clone()).collect());
+        output.stats.extend(tables.stats);
+    }
+
+    output.text.push_str(&codegen::animation_driver(&labels, &delays, suffix, &settings.number_format));
+    return output;
+}
+
+// Writes a table of BG scroll values.
+fn run_scroll_wave(matches: &ArgMatches) -> Result<(), RipperError> {
+    let layer = parse_arg(matches, "layer", Message::ValueLayer)?.unwrap_or(1);
+    if !(1..=4).contains(&layer) {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueLayer), layer)));
+    }
+    let vertical = matches.is_present("vertical");
+    let (wave, height) = parse_wave(matches)?;
+
+    let mut parameters = vec![
+        ("layer", layer.to_string()),
+        ("vertical", String::from(if vertical {"yes"} else {"no"}))
+    ];
+    parameters.extend(wave_parameters(&wave, height));
+
+    let mut output = header::write_effect_header("scroll-wave", &parameters);
+    output.push_str(&effects::write_effect_table(effects::create_scroll_wave_table(&wave, layer, vertical, height),
+        OPTIMISE_TABLE, &number_format(matches)));
+
+    return write_file(output.as_bytes(), Path::new(matches.value_of("output").unwrap_or("scroll_wave.asm")));
+}
+
+// Writes a wave to any register.
+fn run_wave(matches: &ArgMatches) -> Result<(), RipperError> {
+    let text = matches.value_of("register").unwrap_or("");
+    let register = match u16::from_str_radix(text.trim_start_matches('$').trim_start_matches("0x"), 16) {
+        // HDMA can only write to the registers from $2100 to $21FF.
+        Ok(register) if register >> 8 == 0x21 => register,
+        _ => return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueRegister), text)))
+    };
+    let words = matches.value_of("size") == Some("word");
+    let offset: f64 = parse_arg(matches, "offset", Message::ValueOffset)?.unwrap_or(0.0);
+    if !offset.is_finite() {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueOffset), offset)));
+    }
+    let (wave, height) = parse_wave(matches)?;
+
+    let mut parameters = vec![
+        ("register", format!("${:04X}", register)),
+        ("size", String::from(if words {"word"} else {"byte"})),
+        ("offset", offset.to_string())
+    ];
+    parameters.extend(wave_parameters(&wave, height));
+
+    let mut output = header::write_effect_header("wave", &parameters);
+    output.push_str(&effects::write_effect_table(effects::create_wave_table(&wave, offset, register, words, height),
+        OPTIMISE_TABLE, &number_format(matches)));
+
+    return write_file(output.as_bytes(), Path::new(matches.value_of("output").unwrap_or("wave.asm")));
+}
+
+// Writes a table with the values from a text file.
+fn run_csv(matches: &ArgMatches) -> Result<(), RipperError> {
+    let input_name = matches.value_of("INPUT").unwrap_or("");
+    let row_size = parse_arg(matches, "row-size", Message::ValueRowSize)?;
+    if let Some(size) = row_size.filter(|size| !(1..=4).contains(size)) {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueRowSize), size)));
+    }
+    let write_mode = parse_arg(matches, "write-mode", Message::ValueWriteMode)?.unwrap_or(HdmaWriteMode::Bytes);
+    // Words always write two or four bytes per scanline so an odd row size would put padding bytes into the table which
+    // HDMA reads as the next row.
+    if matches!(write_mode, HdmaWriteMode::Words) && row_size.is_some_and(|size| size % 2 != 0) {
+        return Err(RipperError::BadArguments(tr!(Message::WordsNeedEvenRowSize)));
+    }
+
+    let text = match std::fs::read_to_string(input_name) {
+        Ok(text) => text,
+        Err(why) => return Err(RipperError::UnreadableImage(tr!(Message::ReadFileFailed, input_name, why)))
+    };
+    let table = csv::create_csv_table(input_name, &text, row_size, write_mode)?;
+
+    let mut output = header::write_effect_header("csv", &[
+        ("source", String::from(input_name)),
+        ("row-size", match row_size {
+            Some(size) => size.to_string(),
+            None => String::from("auto")
+        }),
+        ("write-mode", String::from(matches.value_of("write-mode").unwrap_or("bytes")))
+    ]);
+    output.push_str(&effects::write_generated_table(table, OPTIMISE_TABLE, &number_format(matches)));
+
+    let output_name = match matches.value_of("output") {
+        Some(output_name) => PathBuf::from(output_name),
+        None => Path::new(input_name).with_extension("asm")
+    };
+    return write_file(output.as_bytes(), &output_name);
+}
+
+// The parameters of a wave as they're listed in the header.
+fn wave_parameters(wave: &Wave, height: u32) -> Vec<(&'static str, String)> {
+    return vec![
+        (if let Shape::Expression(_) = wave.shape {"expression"} else {"shape"}, String::from(wave.shape.name())),
+        ("amplitude", wave.amplitude.to_string()),
+        ("wavelength", wave.wavelength.to_string()),
+        ("phase", wave.phase.to_string()),
+        ("start", wave.start.to_string()),
+        ("end", wave.end.to_string()),
+        ("height", height.to_string())
+    ];
+}
+
+// Writes the Mode 7 matrix of a perspective.
+fn run_mode7(matches: &ArgMatches) -> Result<(), RipperError> {
+    let height = parse_arg(matches, "height", Message::ValueHeight)?.unwrap_or(MAX_SCANLINES);
+    let horizon = parse_arg(matches, "horizon", Message::ValueHorizon)?.unwrap_or(0);
+    if horizon >= height {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueHorizon), horizon)));
+    }
+    let scale: f64 = parse_arg(matches, "scale", Message::ValueScale)?.unwrap_or(1.0);
+    if !scale.is_finite() || scale <= 0.0 {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueScale), scale)));
+    }
+    let angle: Option<f64> = parse_arg(matches, "angle", Message::ValueAngle)?;
+    if let Some(degrees) = angle.filter(|degrees| !degrees.is_finite()) {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueAngle), degrees)));
+    }
+
+    let mut output = header::write_effect_header("mode7", &[
+        ("horizon", horizon.to_string()),
+        ("scale", scale.to_string()),
+        ("angle", match angle {
+            Some(degrees) => degrees.to_string(),
+            None => String::from("none")
+        }),
+        ("height", height.to_string())
+    ]);
+    if angle.is_none() {
+        output.push_str("; The table is used for both, M7A ($211B) and M7D ($211E).\n");
+    }
+
+    let format = number_format(matches);
+    let perspective = effects::Perspective { horizon: horizon, scale: scale, angle: angle };
+    for table in effects::create_mode7_tables(&perspective, height) {
+        output.push_str(&effects::write_effect_table(table, OPTIMISE_TABLE, &format));
+        output.push('\n');
+    }
+
+    return write_file(output.as_bytes(), Path::new(matches.value_of("output").unwrap_or("mode7.asm")));
+}
+
+// Reads the parameters of a wave along with the height of the table.
+fn parse_wave(matches: &ArgMatches) -> Result<(Wave, u32), RipperError> {
+    let height = parse_arg(matches, "height", Message::ValueHeight)?.unwrap_or(MAX_SCANLINES);
+    let shape = match matches.value_of("expression") {
+        Some(text) => match text.parse() {
+            Ok(expression) => Shape::Expression(expression),
+            Err(_) => return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueExpression), text)))
+        },
+        None => parse_arg(matches, "shape", Message::ValueShape)?.unwrap_or(Shape::Sine)
+    };
+    let amplitude: f64 = parse_arg(matches, "amplitude", Message::ValueAmplitude)?.unwrap_or(4.0);
+    if !amplitude.is_finite() {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueAmplitude), amplitude)));
+    }
+    let wavelength: f64 = parse_arg(matches, "wavelength", Message::ValueWavelength)?.unwrap_or(32.0);
+    if !wavelength.is_finite() || wavelength <= 0.0 {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueWavelength), wavelength)));
+    }
+    let phase: f64 = parse_arg(matches, "phase", Message::ValuePhase)?.unwrap_or(0.0);
+    if !phase.is_finite() {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValuePhase), phase)));
+    }
+    let start = parse_arg(matches, "start", Message::ValueYPosition)?.unwrap_or(0);
+    let end = parse_arg(matches, "end", Message::ValueYPosition)?.unwrap_or(height);
+    if start >= end {
+        return Err(RipperError::OutOfRange(tr!(Message::YStartBelowEnd)));
+    }
+
+    return Ok((Wave { shape: shape, amplitude: amplitude, wavelength: wavelength, phase: phase, start: start, end: end }, height));
+}
+
+// How the numbers in the tables are written.
+fn number_format(matches: &ArgMatches) -> NumberFormat {
+    return NumberFormat {
+        base: match matches.value_of("number-base") {
+            Some("dec") => NumberBase::Decimal,
+            _ => NumberBase::Hexadecimal
+        },
+        hex_prefix: match matches.value_of("hex-prefix") {
+            Some("0x") => "0x",
+            _ => "$"
+        },
+        uppercase: matches.value_of("hex-case") != Some("lower"),
+        syntax: match matches.value_of("syntax") {
+            Some("ca65") => Syntax::Ca65,
+            Some("64tass") => Syntax::Tass64,
+            _ => Syntax::Asar
+        },
+        macros: matches.is_present("macros")
+    };
+}
+
+// Parses an optional value from the command line.
+fn parse_arg<T: FromStr>(matches: &ArgMatches, name: &str, description: Message) -> Result<Option<T>, RipperError> {
+    match matches.value_of(name) {
+        Some(x) => match x.parse() {
+            Ok(value) => Ok(Some(value)),
+            Err(_) => Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(description), x)))
+        },
+        None => Ok(None)
+    }
+}
+
+// Parses an adjustment from -100 to 100 percent which is 0 if it hasn't been entered.
+fn parse_percentage(matches: &ArgMatches, name: &str, description: Message) -> Result<i32, RipperError> {
+    let percentage = parse_arg(matches, name, description)?.unwrap_or(0);
+    if !(-100..=100).contains(&percentage) {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(description), percentage)));
+    }
+
+    return Ok(percentage);
+}
+
+// Gets the label suffix and blend factor of each table which is blended with --blend-with.
+// --steps goes from the first image to the second one (both included) whereas --blend only writes a single table.
+fn parse_blend_steps(matches: &ArgMatches) -> Result<Vec<(String, f32)>, RipperError> {
+    if let Some(steps) = parse_arg::<u32>(matches, "steps", Message::ValueSteps)? {
+        if steps < 2 {
+            return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueSteps), steps)));
+        }
+        return Ok((0..steps).map(|step| (format!("_step{}", step), step as f32 / (steps - 1) as f32)).collect());
+    }
+
+    let factor: f32 = parse_arg(matches, "blend", Message::ValueBlend)?.unwrap_or(0.5);
+    if !(0.0..=1.0).contains(&factor) {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueBlend), factor)));
+    }
+
+    return Ok(vec![(String::new(), factor)]);
+}
+
+// Gets the label suffix and brightness of each step of --fade, going from black to full brightness.
+fn parse_fade_steps(matches: &ArgMatches) -> Result<Vec<(String, f32)>, RipperError> {
+    match parse_arg::<u32>(matches, "fade", Message::ValueFade)? {
+        Some(steps) if steps < 2 => Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueFade), steps))),
+        Some(steps) => Ok((0..steps).map(|step| (format!("_fade{}", step), step as f32 / (steps - 1) as f32)).collect()),
+        None => Ok(vec![(String::new(), 1.0)])
+    }
+}
+
+// A hexadecimal address like $7FA000 (the dollar sign is optional).
+fn parse_address(matches: &ArgMatches, name: &str, description: Message) -> Result<Option<u32>, RipperError> {
+    match matches.value_of(name) {
+        Some(text) => match u32::from_str_radix(text.trim_start_matches('$').trim_start_matches("0x"), 16) {
+            Ok(address) if address <= 0xFFFFFF => Ok(Some(address)),
+            _ => Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(description), text)))
+        },
+        None => Ok(None)
+    }
+}
+
+// The scroll position and the RAM of the tables for --scroll-routine, both as hexadecimal addresses.
+// By default, it's the layer 1 Y position of SMW and free RAM.
+fn parse_scroll_routine(matches: &ArgMatches) -> Result<Option<(u32, u32)>, RipperError> {
+    if !matches.is_present("scroll-routine") {
+        return Ok(None);
+    }
+
+    let text = matches.value_of("scroll-routine").unwrap_or("$1C,$7FA000");
+    let addresses = text.split(',')
+        .map(|address| u32::from_str_radix(address.trim().trim_start_matches('$').trim_start_matches("0x"), 16).ok().filter(|address| *address <= 0xFFFFFF))
+        .collect::<Option<Vec<u32>>>();
+
+    match addresses.as_deref() {
+        Some([scroll]) => Ok(Some((*scroll, 0x7FA000))),
+        Some([scroll, ram]) => Ok(Some((*scroll, *ram))),
+        _ => Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueScrollRoutine), text)))
+    }
+}
+
+// Auto picks a big gradient for tables which are taller than the screen.
+fn parse_mode(text: &str, height: u32, scanlines: u32) -> Result<HdmaColourMode, RipperError> {
+    match text {
+        "s" | "single" => Ok(HdmaColourMode::FixedClourThree),
+        "d" | "double" => Ok(HdmaColourMode::FixedClourTwo),
+        "b" | "big" => Ok(HdmaColourMode::BigGradient),
+        "c" | "cgram" => Ok(HdmaColourMode::CgRam),
+        "i" | "brightness" => Ok(HdmaColourMode::Brightness),
+        "m" | "mosaic" => Ok(HdmaColourMode::Mosaic),
+        "w" | "window" => Ok(HdmaColourMode::Window),
+        "h" | "math" => Ok(HdmaColourMode::ColourMath),
+        "a" | "auto" => Ok(if height > scanlines {HdmaColourMode::BigGradient} else {HdmaColourMode::FixedClourTwo}),
+        x => Err(RipperError::BadArguments(tr!(Message::InvalidMode, x)))
+    }
+}
+
+// Turns the channels of --channels into their colour bits for COLDATA.
+fn parse_channels(matches: &ArgMatches) -> Result<u8, RipperError> {
+    let text = matches.value_of("channels").unwrap_or("rgb");
+
+    let mut channels = 0;
+    for character in text.chars() {
+        channels |= match character {
+            'r' => 0x20,
+            'g' => 0x40,
+            'b' => 0x80,
+            _ => return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueChannels), text)))
+        };
+    }
+    if channels == 0 {
+        return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueChannels), text)));
+    }
+
+    return Ok(channels);
+}
+
+// Turns the BG numbers of --mosaic-layers into the enable bits of $2106.
+fn parse_mosaic_layers(matches: &ArgMatches) -> Result<u8, RipperError> {
+    let text = matches.value_of("mosaic-layers").unwrap_or("1234");
+
+    let mut layers = 0;
+    for character in text.chars() {
+        match character.to_digit(10) {
+            Some(layer @ 1..=4) => layers |= 1 << (layer - 1),
+            _ => return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueMosaicLayers), text)))
+        }
+    }
+
+    return Ok(layers);
+}
+
+// Reads the pairs of x position and colour index of --cgram-columns which are separated by commas.
+fn parse_cgram_columns(matches: &ArgMatches) -> Result<Vec<(u32, u8)>, RipperError> {
+    let text = match matches.value_of("cgram-columns") {
+        Some(text) => text,
+        None => return Ok(Vec::new())
+    };
+
+    return text.split(',').map(|pair| {
+        match pair.split_once(':').and_then(|(x_pos, index)| Some((x_pos.trim().parse().ok()?, index.trim().parse().ok()?))) {
+            Some(column) => Ok(column),
+            None => Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueCgramColumns), pair)))
         }
+    }).collect();
+}
+
+// Turns the layers of --math-layers (and --math-layers-off) into the enable bits of $2131.
+fn parse_math_layers(matches: &ArgMatches, name: &str, default: &str) -> Result<u8, RipperError> {
+    let text = matches.value_of(name).unwrap_or(default);
+
+    let mut layers = 0;
+    for character in text.chars() {
+        layers |= match character {
+            '1'..='4' => 1 << (character as u8 - b'1'),
+            'o' => 0x10,
+            'b' => 0x20,
+            _ => return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueMathLayers), text)))
+        };
+    }
+
+    return Ok(layers);
+}
+
+// How many scanlines the screen has got: 224 by default and 239 with overscan.
+// The other consoles only have got a single option.
+fn parse_scanlines(matches: &ArgMatches, target: Target) -> Result<u32, RipperError> {
+    if matches.is_present("overscan") {
+        return Ok(OVERSCAN_SCANLINES);
+    }
+
+    match parse_arg(matches, "scanlines", Message::ValueScanlines)? {
+        None => Ok(target.scanlines().unwrap_or(MAX_SCANLINES)),
+        Some(scanlines) if target.scanlines() == Some(scanlines) => Ok(scanlines),
+        Some(scanlines) if target.scanlines().is_some() => Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueScanlines), scanlines))),
+        Some(scanlines) if scanlines == MAX_SCANLINES || scanlines == OVERSCAN_SCANLINES => Ok(scanlines),
+        Some(scanlines) => Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueScanlines), scanlines)))
     }
+}
 
-    fn write_file(text_data: String, output_path: &Path) {
+// The label suffix, height and padding of a region of --regions.
+type Region = (&'static str, u32, Option<u32>);
 
-        // Load the path
-        let display = output_path.display();
-    
-        let mut file = match File::create(&output_path) {
-            Err(why) => panic!("Couldn't create {}, {}", display, why),
-            Ok(file) => file,
+// Gets each region of --regions.
+// PAL has got 15 more scanlines which the gradient is either stretched over (the default) or extended to with the last
+// colour.
+fn parse_regions(matches: &ArgMatches, height: u32, pad_to: Option<u32>) -> Result<Vec<Region>, RipperError> {
+    let text = match matches.value_of("regions") {
+        Some(text) => text,
+        None => return Ok(vec![("", height, pad_to)])
+    };
+    let extend = matches.value_of("region-fit") == Some("extend");
+
+    let mut regions = Vec::new();
+    for region in text.split(',') {
+        let region = match region.trim() {
+            "ntsc" => ("_ntsc", height, pad_to),
+            "pal" if extend => ("_pal", height, Some(pad_to.unwrap_or(height) + PAL_SCANLINES - MAX_SCANLINES)),
+            "pal" => ("_pal", height * PAL_SCANLINES / MAX_SCANLINES, pad_to.map(|scanlines| scanlines * PAL_SCANLINES / MAX_SCANLINES)),
+            _ => return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueRegions), text)))
         };
+        if !regions.contains(&region) {
+            regions.push(region);
+        }
+    }
+
+    return Ok(regions);
+}
+
+// The label suffix of each colour math variant and whether its colours are inverted.
+// Only a single variant keeps the labels as they are, with both of them, they're told apart by their suffix.
+fn parse_math_variants(matches: &ArgMatches) -> Vec<(&'static str, bool)> {
+    match matches.value_of("math").unwrap_or("add") {
+        "subtract" => vec![("_subtract", true)],
+        "both" => vec![("_add", false), ("_subtract", true)],
+        _ => vec![("", false)]
+    }
+}
+
+// Reads the hue shifts of --hue-variants (in degrees) which are separated by commas.
+// Each shift is brought into 1 to 359 (so that it can be used in a label) and only counted once.
+fn parse_hue_variants(matches: &ArgMatches) -> Result<Vec<i32>, RipperError> {
+    let text = match matches.value_of("hue-variants") {
+        Some(text) => text,
+        None => return Ok(Vec::new())
+    };
+
+    let mut hue_variants = Vec::new();
+    for value in text.split(',') {
+        match value.trim().parse::<i32>().map(|degrees| degrees.rem_euclid(360)) {
+            Ok(degrees) if degrees != 0 => if !hue_variants.contains(&degrees) {
+                hue_variants.push(degrees);
+            },
+            _ => return Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueHueVariants), value)))
+        }
+    }
+
+    return Ok(hue_variants);
+}
+
+// Reads every --range which are written as start..end (the end itself is excluded just like Y_END).
+fn parse_ranges(matches: &ArgMatches) -> Result<Vec<(u32, u32)>, RipperError> {
+    let values = match matches.values_of("range") {
+        Some(values) => values,
+        None => return Ok(Vec::new())
+    };
+
+    return values.map(|text| {
+        match text.split_once("..").and_then(|(start, end)| Some((start.trim().parse().ok()?, end.trim().parse().ok()?))) {
+            Some(range) => Ok(range),
+            None => Err(RipperError::BadArguments(tr!(Message::InvalidValue, messages::text(Message::ValueRange), text)))
+        }
+    }).collect();
+}
+
+fn read_line(line: &mut String) -> Result<(), RipperError> {
+    match std::io::stdin().read_line(line) {
+        Err(why) => Err(RipperError::BadArguments(tr!(Message::ReadInputFailed, why))),
+        Ok(_) => Ok(())
+    }
+}
+
+fn new_progress_bar(length: usize) -> ProgressBar {
+    let progress_bar = ProgressBar::new(length as u64);
+    progress_bar.set_style(
+        ProgressStyle::with_template("{bar:40} {pos}/{len} {msg}")
+        .unwrap_or_else(|_| ProgressStyle::default_bar())
+    );
+    return progress_bar;
+}
+
+// Reports the image and the settings it's ripped with.
+fn log_settings(input_name: &str, image_height: u32, settings: &RipSettings) {
+    log::debug!("Image: {}", input_name);
+    log::debug!("Image height: {}", image_height);
+    log::debug!("Output height: {}", settings.height);
+    log::debug!("Screen scanlines: {}", settings.scanlines);
+    if settings.interlace == Some(Interlace::Doubled) {
+        log::debug!("Interlaced: both fields averaged");
+    }
+    else if settings.interlace.is_some() {
+        log::debug!("Interlaced: a table per field");
+    }
+    log::debug!("Input X position: {}", settings.x_pos);
+    log::debug!("Input X position end: {}", settings.x_end);
+    log::debug!("Sample radius: {}", settings.sample_radius);
+    for extra_mode in &settings.extra_modes {
+        log::debug!("Additional mode: {}", extra_mode.name());
+    }
+    log::debug!("Resampling: {}", settings.resample.name());
+    if let Some(scanline) = settings.mirror {
+        log::debug!("Mirrored at scanline: {}", scanline);
+    }
+    if let Some(filter) = settings.denoise {
+        log::debug!("Denoise filter: {}", filter.name());
+    }
+    if let Some(sigma) = settings.smooth {
+        log::debug!("Smoothed over scanlines: {}", sigma);
+    }
+    if let Some(dither) = settings.dither {
+        log::debug!("Dithering: {}", dither.name());
+    }
+    if settings.mode == HdmaColourMode::ColourMath {
+        log::debug!("Colour math: ${:02X} above, ${:02X} below threshold {}", settings.math_values.0, settings.math_values.1, settings.math_threshold);
+    }
+    if let Some(levels) = settings.max_levels {
+        log::debug!("Levels per channel: {} ({})", levels, if settings.cluster_levels {"k-means"} else {"uniform"});
+    }
+    if let Some(space) = settings.merge_space {
+        log::debug!("Merge colour space: {} (tolerance: {})", space.name(), settings.merge_tolerance);
+    }
+    log::debug!("Merge equal channels: {}", settings.merge_channels);
+    log::debug!("Merge grayscale gradients: {}", settings.merge_grayscale);
+    log::debug!("Fixed colour channels: ${:02X}", settings.channels);
+    log::debug!("Compensate half colour math: {}", settings.compensate_half);
+    log::debug!("Split size: {} bytes", settings.split_size);
+    log::debug!("Driver: {}", settings.driver.name());
+    if let Some((scroll, ram)) = settings.scroll_routine {
+        log::debug!("Scroll routine: ${:04X}, tables at ${:06X}", scroll, ram);
+    }
+    log::debug!("Gamma: {}", settings.gamma);
+    log::debug!("Brightness: {}%", settings.brightness);
+    log::debug!("Contrast: {}%", settings.contrast);
+    log::debug!("Saturation: {}%", settings.saturation);
+    log::debug!("Hue shift: {}", settings.hue_shift);
+    log::debug!("Line offset: {}", settings.line_offset);
+    if settings.reserve_top > 0 {
+        log::debug!("Reserved scanlines at the top: {}", settings.reserve_top);
+    }
+    log::debug!("Stride: {}", settings.stride);
+    log::debug!("Extrapolate: {}", settings.extrapolate);
+    if let Some(scanlines) = settings.pad_to {
+        log::debug!("Padded to scanlines: {}", scanlines);
+    }
+    if let Some(blend_rows) = settings.loop_blend {
+        log::debug!("Loop blended over scanlines: {}", blend_rows);
+    }
+    if settings.cgram_pair {
+        log::debug!("Two colours per scanline");
+    }
+    for (x_pos, cgram_index) in &settings.cgram_columns {
+        log::debug!("CG-RAM column: {} to colour {}", x_pos, cgram_index);
+    }
+    log::debug!("Input Y position start: {}", settings.y_start);
+    log::debug!("Input Y position end: {}", settings.y_end);
+    log::debug!("Input height: {}", settings.y_end - settings.y_start);
+    for (y_start, y_end) in &settings.ranges {
+        log::debug!("Input range: {}..{}", y_start, y_end);
+    }
+    if !settings.ranges.is_empty() {
+        log::debug!("Filler rows between ranges: {}", settings.range_filler);
+    }
+    match settings.mode {
+        HdmaColourMode::FixedClourThree => log::debug!("Fixed colour, three tables."),
+        HdmaColourMode::FixedClourTwo => log::debug!("Fixed colour, two tables."),
+        HdmaColourMode::BigGradient => log::debug!("Fixed colour, one big table."),
+        HdmaColourMode::CgRam => log::debug!("CG-RAM, one table."),
+        HdmaColourMode::Brightness => log::debug!("Brightness, one table."),
+        HdmaColourMode::Mosaic => log::debug!("Mosaic, one table."),
+        HdmaColourMode::Window => log::debug!("Window, one table."),
+        HdmaColourMode::ColourMath => log::debug!("Colour math, one table.")
+    }
+}
+
+// Gets the name of the ASM file of an image if there are multiple images.
+// The ASM file is put next to the image unless an output folder has been specified.
+fn batch_output_path(input_name: &str, output_folder: Option<&str>) -> PathBuf {
+    let file_name = Path::new(source::file_name(input_name)).with_extension("asm");
+
+    // Links have no folder so they're put in the current folder instead.
+    match output_folder {
+        Some(folder) => Path::new(folder).join(file_name),
+        None if source::is_url(input_name) => file_name,
+        None => Path::new(input_name).with_extension("asm")
+    }
+}
+
+// Which files the tables of an image are written to, usually just the ASM file.
+// Binary tables, C arrays and JSON are written as they are, without the header or any of the routines.
+fn output_files(output_path: &Path, tables: &RippedTables, format: Option<&str>, target: Target) -> Vec<(PathBuf, Vec<u8>)> {
+    let binary_files = tables.stats.iter().map(|stats| (binary_path(output_path, &stats.name), stats.data.clone()));
+
+    match format {
+        Some("bin") => binary_files.collect(),
+        // The stub which includes the binary files goes to the output path instead of the ASM file.
+        Some("incbin") => {
+            let stub = codegen::incbin_stub(&tables.stats.iter().map(|stats| {
+                let file_name = binary_path(output_path, &stats.name).file_name().map_or(String::new(), |name| name.to_string_lossy().into_owned());
+                (stats.name.clone(), file_name, stats.data.len())
+            }).collect::<Vec<_>>());
+            binary_files.chain(std::iter::once((output_path.to_path_buf(), stub.into_bytes()))).collect()
+        }
+        // The source and header file are named after the output file, e.g. gradient.c and gradient.h.
+        Some("c") => {
+            let header_path = output_path.with_extension("h");
+            let header_name = header_path.file_name().map_or(String::from("gradient.h"), |name| name.to_string_lossy().into_owned());
+            let arrays: Vec<(String, &[u8])> = tables.stats.iter().map(|stats| (stats.name.clone(), stats.data.as_slice())).collect();
+            vec![
+                (output_path.with_extension("c"), carray::write_source(&arrays, &header_name, target).into_bytes()),
+                (header_path, carray::write_header(&arrays, &header_name, target).into_bytes())
+            ]
+        }
+        Some("json") => {
+            let tables: Vec<String> = tables.stats.iter().map(|stats| stats.table_to_json()).collect();
+            vec![(output_path.with_extension("json"), format!("{{\"tables\":[{}]}}\n", tables.join(",")).into_bytes())]
+        }
+        _ => vec![(output_path.to_path_buf(), tables.text.clone().into_bytes())]
+    }
+}
+
+// Binary tables get a file each which is named after the output file and the table, e.g. gradient_red_table.bin.
+fn binary_path(output_path: &Path, label: &str) -> PathBuf {
+    let stem = output_path.file_stem().map_or(String::from("gradient"), |stem| stem.to_string_lossy().into_owned());
+    return output_path.with_file_name(format!("{}_{}.bin", stem, label));
+}
+
+fn write_file(data: &[u8], output_path: &Path) -> Result<(), RipperError> {
+
+    // Load the path
+    let display = output_path.display();
+
+    let mut file = match File::create(&output_path) {
+        Err(why) => return Err(RipperError::WriteFailure(tr!(Message::CreateFileFailed, display, why))),
+        Ok(file) => file,
+    };
+
+    match file.write_all(data) {
+        Err(why) => Err(RipperError::WriteFailure(tr!(Message::WriteFileFailed, display, why))),
+        Ok(_) => {
+            log::info!("{}", tr!(Message::TableGenerated));
+            Ok(())
+        }
+    }
+}
+
+// Lists the size of every table of an image.
+fn stats_to_json(input_name: &str, output_path: Option<&Path>, tables: &RippedTables) -> String {
+    let output_name = match output_path {
+        Some(path) => json::string(&path.to_string_lossy()),
+        None => String::from("null")
+    };
+    let table_stats: Vec<String> = tables.stats.iter().map(|stats| stats.to_json()).collect();
+
+    return format!("{{\"input\":{},\"output\":{},\"mode\":{},\"tables\":[{}]}}",
+        json::string(input_name), output_name, json::string(tables.mode.name()), table_stats.join(","));
+}
+
+// The tables are placed one after another from the base address on, just like the binary files are included by the
+// incbin stub. Routines aren't listed since they're part of the tables only in the ASM files.
+fn symbol_entries(stats: &[colour::TableStats], address: &mut u32) -> String {
+    let mut output = String::new();
+    for stats in stats {
+        output.push_str(&format!("{:02x}:{:04x} {}\n", *address >> 16, *address & 0xFFFF, stats.name));
+        *address += stats.data.len() as u32;
+    }
+
+    return output;
+}
+
+// Lists a generated file in the manifest together with the hash of the image it has been generated from.
+fn manifest_entry(input_name: &str, image_data: &[u8], output_path: &Path, tables: &RippedTables) -> String {
+    let labels: Vec<String> = tables.stats.iter().map(|stats| json::string(&stats.name)).collect();
+    let byte_size: usize = tables.stats.iter().map(|stats| stats.byte_size).sum();
+
+    return format!("{{\"file\":{},\"source\":{},\"source_sha256\":\"{:x}\",\"labels\":[{}],\"bytes\":{}}}",
+        json::string(&output_path.to_string_lossy()), json::string(input_name), Sha256::digest(image_data),
+        labels.join(","), byte_size);
+}
+
+fn write_preview(mode: HdmaColourMode, stats: &[colour::TableStats], preview_name: &str) -> Result<(), RipperError> {
+    let image = match preview::render_tables(mode, stats) {
+        Some(image) => image,
+        None => {
+            log::warn!("{}", tr!(Message::PreviewUnsupported, mode.name()));
+            return Ok(());
+        }
+    };
+
+    match image.save(preview_name) {
+        Err(why) => Err(RipperError::WriteFailure(tr!(Message::WriteFileFailed, preview_name, why))),
+        Ok(_) => Ok(())
+    }
+}
+
+fn write_scroll_preview(stats: &[colour::TableStats], scanlines: u32, preview_name: &str) -> Result<(), RipperError> {
+    let image = match preview::render_tables(HdmaColourMode::BigGradient, stats) {
+        Some(image) => image,
+        None => return Ok(())
+    };
+
+    let file = match File::create(preview_name) {
+        Err(why) => return Err(RipperError::WriteFailure(tr!(Message::CreateFileFailed, preview_name, why))),
+        Ok(file) => file
+    };
+    let mut encoder = GifEncoder::new(file);
+    let result = encoder.set_repeat(Repeat::Infinite).and_then(|_| encoder.encode_frames(preview::scroll_frames(&image, scanlines)));
+
+    match result {
+        Err(why) => Err(RipperError::WriteFailure(tr!(Message::WriteFileFailed, preview_name, why))),
+        Ok(_) => Ok(())
+    }
+}
+
+// Only what asar can read is verified, i.e. ASM files (including the patches and uberASM files) in the asar syntax.
+fn verify_tables(input_name: &str, text: &str, matches: &ArgMatches) -> Result<(), RipperError> {
+    let format = matches.value_of("format").unwrap_or("asm");
+    if !["asm", "asar-patch", "uberasm"].contains(&format) || matches.value_of("syntax").unwrap_or("asar") != "asar" {
+        return Err(RipperError::BadArguments(tr!(Message::VerifyNeedsAsar)));
+    }
+
+    let asar = matches.value_of("asar").unwrap_or("asar");
+    match verify::assemble(text, asar) {
+        Err(verify::AsarError::NotRun(why)) => Err(RipperError::AssemblyFailed(tr!(Message::AsarFailed, asar, why))),
+        Err(verify::AsarError::Errors(errors)) => Err(RipperError::AssemblyFailed(tr!(Message::AssemblyFailed, input_name, errors))),
+        Ok(_) => Ok(())
+    }
+}
+
+// The format depends on the extension of the palette file.
+fn write_palette(colours: &[u16], palette_name: &str) -> Result<(), RipperError> {
+    let colours = palette::gradient_colours(colours);
+    let data = match Path::new(palette_name).extension().and_then(|extension| extension.to_str()) {
+        Some("pal") => palette::write_pal(&colours),
+        Some("tpl") => palette::write_tpl(&colours),
+        _ => return Err(RipperError::BadArguments(tr!(Message::UnknownPaletteFormat, palette_name)))
+    };
+
+    match std::fs::write(palette_name, data) {
+        Err(why) => Err(RipperError::WriteFailure(tr!(Message::WriteFileFailed, palette_name, why))),
+        Ok(_) => Ok(())
+    }
+}
+
+fn write_report(report: &str, report_name: &str) -> Result<(), RipperError> {
+    if report_name == "-" {
+        print!("{}", report);
+        return Ok(());
+    }
+
+    match std::fs::write(report_name, report) {
+        Err(why) => Err(RipperError::WriteFailure(tr!(Message::WriteFileFailed, report_name, why))),
+        Ok(_) => Ok(())
+    }
+}
+
+// Make splits dependencies at spaces and treats some characters specially so these have to be escaped.
+fn escape_make_path(path: &str) -> String {
+    let mut output = String::new();
+
+    for character in path.chars() {
+        match character {
+            ' ' | '#' => {
+                output.push('\\');
+                output.push(character);
+            }
+            '$' => output.push_str("$$"),
+            c => output.push(c)
+        }
+    }
+
+    return output;
+}
+
+// Whether the existing file has got exactly the same content as the generated one.
+fn is_up_to_date(data: &[u8], output_path: &Path) -> bool {
+    match std::fs::read(output_path) {
+        Ok(existing_data) => existing_data == data,
+        Err(_) => false
+    }
+}
+
+fn write_clipboard(text_data: String) -> Result<(), RipperError> {
+    let mut clipboard = match Clipboard::new() {
+        Err(why) => return Err(RipperError::WriteFailure(tr!(Message::ClipboardAccessFailed, why))),
+        Ok(clipboard) => clipboard,
+    };
 
-        match file.write_all(text_data.as_bytes()) {
-            Err(why) => panic!("Couldn't write to {}, {}", display, why),
-            Ok(_) => println!("HDMA table successfully generated!"),
+    match clipboard.set_text(text_data) {
+        Err(why) => Err(RipperError::WriteFailure(tr!(Message::ClipboardCopyFailed, why))),
+        Ok(_) => {
+            log::info!("{}", tr!(Message::TableCopied));
+            Ok(())
         }
     }
 }
@@ -0,0 +1,630 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// All the languages the messages have been translated to.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Language {
+    English = 0,
+    German = 1,
+    Japanese = 2
+}
+
+impl Language {
+    // Gets the language from a language code like "de" or a locale like "ja_JP.UTF-8".
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.get(0..2).map(|prefix| prefix.to_ascii_lowercase()).as_deref() {
+            Some("en") => Some(Language::English),
+            Some("de") => Some(Language::German),
+            Some("ja") => Some(Language::Japanese),
+            _ => None
+        }
+    }
+
+    // Uses the locale of the system (if there is one which has been translated).
+    pub fn from_environment() -> Self {
+        for variable in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+            if let Some(language) = std::env::var(variable).ok().and_then(|code| Self::from_code(&code)) {
+                return language;
+            }
+        }
+
+        return Language::English;
+    }
+}
+
+static LANGUAGE: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        1 => Language::German,
+        2 => Language::Japanese,
+        _ => Language::English
+    }
+}
+
+// Every message which the user gets to see.
+// Placeholders are written as {0}, {1}, etc. since the order of the values can differ between languages.
+#[derive(Copy, Clone)]
+pub enum Message {
+    ErrorPrefix,
+    WarningPrefix,
+    PromptImage,
+    PromptOutput,
+    TableGenerated,
+    TableCopied,
+    TablesUpToDate,
+    FilesOutdated,
+    HeightTooSmall,
+    HeightTooLarge,
+    InvalidValue,
+    ValueHeight,
+    ValueYPosition,
+    ValueXPosition,
+    ValueCgramIndex,
+    ValueSampleRadius,
+    ValueDenoise,
+    ValueSmooth,
+    ValueDither,
+    ValueMaxLevels,
+    ValueMergeSpace,
+    ValueTolerance,
+    ValueResample,
+    ValueMirror,
+    ValueRange,
+    ValueRangeFiller,
+    ValueStride,
+    ValuePadTo,
+    ValueLoop,
+    ValueAlpha,
+    ValueHoldColour,
+    ValueToneMap,
+    ValueExposure,
+    ValueFrame,
+    ValueGamma,
+    ValueHueVariants,
+    ValueBlend,
+    ValueSteps,
+    ValueFade,
+    ValueMosaicLayers,
+    ValueBrightness,
+    ValueContrast,
+    ValueSaturation,
+    ValueLayer,
+    ValueAmplitude,
+    ValueWavelength,
+    ValuePhase,
+    ValueHorizon,
+    ValueScale,
+    ValueAngle,
+    ValueShape,
+    ValueExpression,
+    ValueRegister,
+    ValueOffset,
+    ValueRowSize,
+    ValueMathLayers,
+    ValueThreshold,
+    ValueCgramColumns,
+    ValueChannels,
+    ValueSplitSize,
+    ValueDriver,
+    ValueScrollRoutine,
+    ValueRamBuffer,
+    ValueSymbolBase,
+    ValueRegions,
+    ValueScanlines,
+    ValueLineOffset,
+    ValueSizeBudget,
+    ValueWriteMode,
+    ValueTarget,
+    ValueReserveTop,
+    ValueReserveColour,
+    InvalidMode,
+    YOutsideImage,
+    YStartBelowEnd,
+    XOutsideImage,
+    XEndBeforeStart,
+    XEndWithoutXPosition,
+    MirrorOutsideTable,
+    AllRowsTransparent,
+    GrayscaleConverted,
+    IndexedConverted,
+    GrayscaleLevels,
+    IndexedSnapped,
+    FrameOutOfRange,
+    NoFrames,
+    UnsupportedTiffPage,
+    AnimationTooLong,
+    SizeMismatch,
+    FadeNeedsBrightness,
+    SpotlightNeedsFixedColour,
+    ColourMathNeedsFixedColour,
+    CgramColumnsNeedCgram,
+    ChannelsNeedFixedColour,
+    ChannelsMerged,
+    TableSplit,
+    IrqDriverMode,
+    ScrollRoutineNeedsBig,
+    ScrollRoutineSplit,
+    RamBufferMode,
+    MathVariantsNeedFixedColour,
+    BackdropNeedsFixedColour,
+    ModeTwice,
+    FieldsNeedTables,
+    SegmentNeedsCa65,
+    PaletteTooLong,
+    UnknownPaletteFormat,
+    PreviewUnsupported,
+    ScrollPreviewNeedsBig,
+    PatchNeedsHdmaTables,
+    VerifyNeedsAsar,
+    AsarFailed,
+    AssemblyFailed,
+    AssertsNeedAsar,
+    MacrosNeedPlainTables,
+    RoutinesNeedAsar,
+    WriteModeNeedsPlainTables,
+    WordOrderNeedsWords,
+    TargetNeedsSnes,
+    InvalidCsvLine,
+    EmptyCsv,
+    WordsNeedEvenRowSize,
+    ColumnChosen,
+    ReadInputFailed,
+    ReadFileFailed,
+    CreateFileFailed,
+    WriteFileFailed,
+    ClipboardAccessFailed,
+    ClipboardCopyFailed,
+    NoHeader,
+    UnknownPreset,
+    DownloadFailed,
+    OpenImageFailed,
+}
+
+fn english(message: Message) -> &'static str {
+    match message {
+        Message::ErrorPrefix => "Error",
+        Message::WarningPrefix => "Warning",
+        Message::PromptImage => "Enter the image to be ripped: ",
+        Message::PromptOutput => "Enter the name of the ASM file: ",
+        Message::TableGenerated => "HDMA table successfully generated!",
+        Message::TableCopied => "HDMA table successfully copied to the clipboard!",
+        Message::TablesUpToDate => "All HDMA tables are up to date.",
+        Message::FilesOutdated => "The following files are out of date: {0}",
+        Message::HeightTooSmall => "The output height you entered is {0} which is smaller than {1}.\nI recommend you to use a height of at least {1}.",
+        Message::HeightTooLarge => "The image height you entered is {0} which is larger than {1}.\nI recommend you to use a big gradient instead.",
+        Message::InvalidValue => "Invalid {0}: {1}",
+        Message::ValueHeight => "height",
+        Message::ValueYPosition => "Y position",
+        Message::ValueXPosition => "X position",
+        Message::ValueCgramIndex => "CG-RAM index",
+        Message::ValueSampleRadius => "sample radius",
+        Message::ValueDenoise => "denoise filter",
+        Message::ValueSmooth => "blur strength",
+        Message::ValueDither => "dither method",
+        Message::ValueMaxLevels => "level count",
+        Message::ValueMergeSpace => "colour space",
+        Message::ValueTolerance => "tolerance",
+        Message::ValueResample => "resampling algorithm",
+        Message::ValueMirror => "mirror scanline",
+        Message::ValueRange => "range",
+        Message::ValueRangeFiller => "range filler",
+        Message::ValueStride => "stride",
+        Message::ValuePadTo => "scanline count to pad to",
+        Message::ValueLoop => "loop blend",
+        Message::ValueAlpha => "alpha policy",
+        Message::ValueHoldColour => "hold colour",
+        Message::ValueToneMap => "tone mapping",
+        Message::ValueExposure => "exposure",
+        Message::ValueFrame => "frame",
+        Message::ValueGamma => "gamma",
+        Message::ValueHueVariants => "hue variant",
+        Message::ValueBlend => "blend factor",
+        Message::ValueSteps => "step count",
+        Message::ValueFade => "fade step count",
+        Message::ValueMosaicLayers => "mosaic layers",
+        Message::ValueBrightness => "brightness",
+        Message::ValueContrast => "contrast",
+        Message::ValueSaturation => "saturation",
+        Message::ValueLayer => "layer",
+        Message::ValueAmplitude => "amplitude",
+        Message::ValueWavelength => "wavelength",
+        Message::ValuePhase => "phase",
+        Message::ValueHorizon => "horizon",
+        Message::ValueScale => "scale",
+        Message::ValueAngle => "angle",
+        Message::ValueShape => "shape",
+        Message::ValueExpression => "expression",
+        Message::ValueRegister => "register",
+        Message::ValueOffset => "offset",
+        Message::ValueRowSize => "row size",
+        Message::ValueMathLayers => "colour math layers",
+        Message::ValueThreshold => "threshold",
+        Message::ValueCgramColumns => "CG-RAM column",
+        Message::ValueChannels => "channels",
+        Message::ValueSplitSize => "split size",
+        Message::ValueDriver => "driver",
+        Message::ValueScrollRoutine => "scroll routine addresses",
+        Message::ValueRamBuffer => "RAM buffer address",
+        Message::ValueSymbolBase => "symbol base address",
+        Message::ValueRegions => "regions",
+        Message::ValueScanlines => "scanline count",
+        Message::ValueLineOffset => "line offset",
+        Message::ValueSizeBudget => "size budget",
+        Message::ValueWriteMode => "write mode",
+        Message::ValueTarget => "target",
+        Message::ValueReserveTop => "reserved scanlines",
+        Message::ValueReserveColour => "reserved colour",
+        Message::InvalidMode => "The entered mode {0} is invalid!",
+        Message::YOutsideImage => "The entered Y position is located outside of the image!",
+        Message::YStartBelowEnd => "The entered Y start must be above the Y end!",
+        Message::XOutsideImage => "The entered X position is located outside of the image!",
+        Message::XEndBeforeStart => "The entered X end must not be left of the X position!",
+        Message::XEndWithoutXPosition => "--x-end needs a numeric X position, \"full\" and \"auto\" already pick the columns themselves!",
+        Message::MirrorOutsideTable => "The entered mirror scanline must be between 1 and the output height!",
+        Message::AllRowsTransparent => "Every sampled row is transparent so the gradient is black.",
+        Message::GrayscaleConverted => "{0} is a grayscale image, its brightness is used for all three colour channels.",
+        Message::IndexedConverted => "{0} is an indexed image, the colours of its palette are used.",
+        Message::GrayscaleLevels => "{0} is a grayscale image, its grey levels are directly turned into the levels of the table.",
+        Message::IndexedSnapped => "{0} is an indexed image, the sampled colours are snapped to the closest colour of its palette (the earlier one if two are just as close).",
+        Message::FrameOutOfRange => "{0} has only got {1} frame(s)!",
+        Message::NoFrames => "{0} hasn't got any frames!",
+        Message::UnsupportedTiffPage => "The page of {0} has got an unsupported colour type: {1}",
+        Message::AnimationTooLong => "The animation is longer than 256 frames and is cut off when the frame counter wraps around.",
+        Message::SizeMismatch => "{0} doesn't have the same size as {1}!",
+        Message::FadeNeedsBrightness => "Fades only work with the brightness mode!",
+        Message::SpotlightNeedsFixedColour => "Spotlights only work with the single and double mode!",
+        Message::ColourMathNeedsFixedColour => "The colour math setup only works with the single, double, big and math mode!",
+        Message::CgramColumnsNeedCgram => "Several CG-RAM columns only work with the cgram mode!",
+        Message::ChannelsNeedFixedColour => "Selecting channels only works with the single and double mode!",
+        Message::ChannelsMerged => "{0} writes {1} channels at once which saves {2} bytes and {3} HDMA channel(s).",
+        Message::TableSplit => "{0} takes up more than {2} bytes and has been split into {1} tables.",
+        Message::IrqDriverMode => "The IRQ driver doesn't work with the window mode and needs a colour index in the cgram mode!",
+        Message::ScrollRoutineNeedsBig => "The scroll routine only works with the big mode!",
+        Message::ScrollRoutineSplit => "{0} has been split so there is no scroll routine. Use the continue routine instead.",
+        Message::RamBufferMode => "The RAM buffers only work with HDMA tables, i.e. neither with big gradients nor the IRQ driver!",
+        Message::MathVariantsNeedFixedColour => "Subtractive colour math variants only work with the single, double and big mode!",
+        Message::BackdropNeedsFixedColour => "The backdrop only works with the single and double mode!",
+        Message::ModeTwice => "The mode {0} has been entered twice!",
+        Message::FieldsNeedTables => "Field tables only work with a single mode which isn't a big gradient!",
+        Message::SegmentNeedsCa65 => "Segments only exist in the ca65 syntax!",
+        Message::PaletteTooLong => "The gradient has got {0} colours but a palette only holds {1} so some of them are left out.",
+        Message::UnknownPaletteFormat => "{0} is neither a .pal nor a .tpl file!",
+        Message::PreviewUnsupported => "The tables of the {0} mode can't be previewed, only HDMA tables which change a colour can.",
+        Message::ScrollPreviewNeedsBig => "The scrolling preview only works with the big mode!",
+        Message::PatchNeedsHdmaTables => "Patches and uberASM files only set up plain HDMA tables: No big gradients, IRQ tables, animations, spotlights or backdrops!",
+        Message::VerifyNeedsAsar => "Only ASM files in the syntax of asar can be verified, not binary files or other syntaxes!",
+        Message::AsarFailed => "Couldn't run asar ({0}): {1}",
+        Message::AssemblyFailed => "The tables of {0} don't assemble:\n{1}",
+        Message::AssertsNeedAsar => "Asserts and prints only exist in ASM files in the syntax of asar!",
+        Message::MacrosNeedPlainTables => "Macros only work for plain HDMA tables in ASM files in the syntax of asar, not for big gradients or other formats!",
+        Message::RoutinesNeedAsar => "The routines (e.g. of animations, fades, IRQs or patches) are only written in the syntax of asar!",
+        Message::WriteModeNeedsPlainTables => "A different write mode changes the size of every scanline so the channels can't be set up for it (patches, uberASM files, spotlights, backdrops and defines)!",
+        Message::WordOrderNeedsWords => "--big-endian and --swap-words only work for tables which are written as words (CG-RAM tables or --write-mode words)!",
+        Message::TargetNeedsSnes => "Other consoles only get a single table as ASM, binary, C or JSON file, the options for the SNES don't apply to them!",
+        Message::InvalidCsvLine => "Line {0} of {1} is invalid: {2}",
+        Message::EmptyCsv => "{0} doesn't contain any values!",
+        Message::WordsNeedEvenRowSize => "Tables which are written as words need a row size of 2 or 4 bytes!",
+        Message::ColumnChosen => "Ripping the cleanest column at X position {0}.",
+        Message::ReadInputFailed => "Couldn't read input: {0}",
+        Message::ReadFileFailed => "Couldn't read {0}: {1}",
+        Message::CreateFileFailed => "Couldn't create {0}, {1}",
+        Message::WriteFileFailed => "Couldn't write to {0}, {1}",
+        Message::ClipboardAccessFailed => "Couldn't access the clipboard, {0}",
+        Message::ClipboardCopyFailed => "Couldn't copy to the clipboard, {0}",
+        Message::NoHeader => "{0} has got no header to regenerate it from.",
+        Message::UnknownPreset => "There is no preset called {0}. Available presets: {1}",
+        Message::DownloadFailed => "Couldn't download {0}: {1}",
+        Message::OpenImageFailed => "Couldn't open {0}: {1}",
+    }
+}
+
+fn german(message: Message) -> &'static str {
+    match message {
+        Message::ErrorPrefix => "Fehler",
+        Message::WarningPrefix => "Warnung",
+        Message::PromptImage => "Gib das Bild ein, aus dem der Farbverlauf gelesen werden soll: ",
+        Message::PromptOutput => "Gib den Namen der ASM-Datei ein: ",
+        Message::TableGenerated => "HDMA-Tabelle erfolgreich erstellt!",
+        Message::TableCopied => "HDMA-Tabelle erfolgreich in die Zwischenablage kopiert!",
+        Message::TablesUpToDate => "Alle HDMA-Tabellen sind aktuell.",
+        Message::FilesOutdated => "Die folgenden Dateien sind veraltet: {0}",
+        Message::HeightTooSmall => "Die eingegebene Ausgabehöhe ist {0} und damit kleiner als {1}.\nIch empfehle eine Höhe von mindestens {1}.",
+        Message::HeightTooLarge => "Die eingegebene Bildhöhe ist {0} und damit größer als {1}.\nIch empfehle stattdessen einen großen Farbverlauf (big).",
+        Message::InvalidValue => "Ungültiger Wert für {0}: {1}",
+        Message::ValueHeight => "die Höhe",
+        Message::ValueYPosition => "die Y-Position",
+        Message::ValueXPosition => "die X-Position",
+        Message::ValueCgramIndex => "den CG-RAM-Index",
+        Message::ValueSampleRadius => "den Abtastradius",
+        Message::ValueDenoise => "den Rauschfilter",
+        Message::ValueSmooth => "die Weichzeichnerstärke",
+        Message::ValueDither => "die Dithering-Methode",
+        Message::ValueMaxLevels => "die Stufenanzahl",
+        Message::ValueMergeSpace => "der Farbraum",
+        Message::ValueTolerance => "die Toleranz",
+        Message::ValueResample => "den Skalierungsalgorithmus",
+        Message::ValueMirror => "die Spiegelzeile",
+        Message::ValueRange => "den Bereich",
+        Message::ValueRangeFiller => "die Füllzeilen",
+        Message::ValueStride => "die Schrittweite",
+        Message::ValuePadTo => "die aufzufüllende Zeilenanzahl",
+        Message::ValueLoop => "die Übergangszeilen der Schleife",
+        Message::ValueAlpha => "die Behandlung der Transparenz",
+        Message::ValueHoldColour => "die Haltefarbe",
+        Message::ValueToneMap => "das Tone-Mapping",
+        Message::ValueExposure => "die Belichtung",
+        Message::ValueFrame => "das Einzelbild",
+        Message::ValueGamma => "das Gamma",
+        Message::ValueHueVariants => "die Farbtonvariante",
+        Message::ValueBlend => "der Mischfaktor",
+        Message::ValueSteps => "die Schrittanzahl",
+        Message::ValueFade => "die Anzahl der Überblendschritte",
+        Message::ValueMosaicLayers => "die Mosaikebenen",
+        Message::ValueBrightness => "die Helligkeit",
+        Message::ValueContrast => "der Kontrast",
+        Message::ValueSaturation => "die Sättigung",
+        Message::ValueLayer => "die Ebene",
+        Message::ValueAmplitude => "die Amplitude",
+        Message::ValueWavelength => "die Wellenlänge",
+        Message::ValuePhase => "die Phase",
+        Message::ValueHorizon => "den Horizont",
+        Message::ValueScale => "die Skalierung",
+        Message::ValueAngle => "den Winkel",
+        Message::ValueShape => "die Form",
+        Message::ValueExpression => "den Ausdruck",
+        Message::ValueRegister => "das Register",
+        Message::ValueOffset => "den Versatz",
+        Message::ValueRowSize => "die Zeilengröße",
+        Message::ValueMathLayers => "die Farbmathematikebenen",
+        Message::ValueThreshold => "den Schwellenwert",
+        Message::ValueCgramColumns => "die CG-RAM-Spalte",
+        Message::ValueChannels => "die Farbkanäle",
+        Message::ValueSplitSize => "die Aufteilungsgröße",
+        Message::ValueDriver => "den Treiber",
+        Message::ValueScrollRoutine => "die Adressen der Scroll-Routine",
+        Message::ValueRamBuffer => "die Adresse des RAM-Puffers",
+        Message::ValueSymbolBase => "die Startadresse der Symbole",
+        Message::ValueRegions => "die Regionen",
+        Message::ValueScanlines => "die Zeilenanzahl",
+        Message::ValueLineOffset => "die Zeilenverschiebung",
+        Message::ValueSizeBudget => "das Größenbudget",
+        Message::ValueWriteMode => "die Schreibweise",
+        Message::ValueTarget => "die Zielkonsole",
+        Message::ValueReserveTop => "die reservierten Zeilen",
+        Message::ValueReserveColour => "die reservierte Farbe",
+        Message::InvalidMode => "Der eingegebene Modus {0} ist ungültig!",
+        Message::YOutsideImage => "Die eingegebene Y-Position liegt außerhalb des Bildes!",
+        Message::YStartBelowEnd => "Der eingegebene Y-Anfang muss über dem Y-Ende liegen!",
+        Message::XOutsideImage => "Die eingegebene X-Position liegt außerhalb des Bildes!",
+        Message::XEndBeforeStart => "Das eingegebene X-Ende darf nicht links von der X-Position liegen!",
+        Message::XEndWithoutXPosition => "--x-end braucht eine Zahl als X-Position, \"full\" und \"auto\" wählen die Spalten bereits selbst!",
+        Message::MirrorOutsideTable => "Die eingegebene Spiegelzeile muss zwischen 1 und der Ausgabehöhe liegen!",
+        Message::AllRowsTransparent => "Alle ausgelesenen Zeilen sind transparent, daher ist der Farbverlauf schwarz.",
+        Message::GrayscaleConverted => "{0} ist ein Graustufenbild, seine Helligkeit wird für alle drei Farbkanäle verwendet.",
+        Message::IndexedConverted => "{0} ist ein indiziertes Bild, die Farben seiner Palette werden verwendet.",
+        Message::GrayscaleLevels => "{0} ist ein Graustufenbild, seine Graustufen werden direkt in die Stufen der Tabelle umgewandelt.",
+        Message::IndexedSnapped => "{0} ist ein indiziertes Bild, die ausgelesenen Farben werden auf die nächste Farbe seiner Palette gerundet (die frühere, wenn zwei gleich nah sind).",
+        Message::FrameOutOfRange => "{0} hat nur {1} Einzelbild(er)!",
+        Message::NoFrames => "{0} hat keine Einzelbilder!",
+        Message::UnsupportedTiffPage => "Die Seite von {0} hat einen nicht unterstützten Farbtyp: {1}",
+        Message::AnimationTooLong => "Die Animation ist länger als 256 Einzelbilder und wird abgeschnitten, wenn der Bildzähler überläuft.",
+        Message::SizeMismatch => "{0} hat nicht dieselbe Größe wie {1}!",
+        Message::FadeNeedsBrightness => "Überblendungen funktionieren nur mit dem Helligkeitsmodus!",
+        Message::SpotlightNeedsFixedColour => "Scheinwerfer funktionieren nur mit dem Single- und Double-Modus!",
+        Message::ColourMathNeedsFixedColour => "Die Farbmathematik kann nur mit dem Single-, Double-, Big- und Math-Modus eingerichtet werden!",
+        Message::CgramColumnsNeedCgram => "Mehrere CG-RAM-Spalten funktionieren nur mit dem CG-RAM-Modus!",
+        Message::ChannelsNeedFixedColour => "Farbkanäle können nur mit dem Single- und Double-Modus ausgewählt werden!",
+        Message::ChannelsMerged => "{0} schreibt {1} Kanäle auf einmal, was {2} Bytes und {3} HDMA-Kanal/-Kanäle spart.",
+        Message::TableSplit => "{0} ist größer als {2} Bytes und wurde in {1} Tabellen aufgeteilt.",
+        Message::IrqDriverMode => "Der IRQ-Treiber funktioniert nicht mit dem Fenster-Modus und braucht im CG-RAM-Modus einen Farbindex!",
+        Message::ScrollRoutineNeedsBig => "Die Scroll-Routine funktioniert nur mit dem Big-Modus!",
+        Message::ScrollRoutineSplit => "{0} wurde aufgeteilt, daher gibt es keine Scroll-Routine. Verwende stattdessen die Continue-Routine.",
+        Message::RamBufferMode => "Die RAM-Puffer funktionieren nur mit HDMA-Tabellen, also weder mit großen Farbverläufen noch mit dem IRQ-Treiber!",
+        Message::MathVariantsNeedFixedColour => "Varianten für subtraktive Farbmathematik funktionieren nur mit dem Single-, Double- und Big-Modus!",
+        Message::BackdropNeedsFixedColour => "Der Hintergrund funktioniert nur mit dem Single- und Double-Modus!",
+        Message::ModeTwice => "Der Modus {0} wurde zweimal eingegeben!",
+        Message::FieldsNeedTables => "Halbbildtabellen funktionieren nur mit einem einzigen Modus, der kein großer Farbverlauf ist!",
+        Message::SegmentNeedsCa65 => "Segmente gibt es nur in der ca65-Syntax!",
+        Message::PaletteTooLong => "Der Farbverlauf hat {0} Farben, eine Palette fasst aber nur {1}, weshalb einige davon weggelassen werden.",
+        Message::UnknownPaletteFormat => "{0} ist weder eine .pal- noch eine .tpl-Datei!",
+        Message::PreviewUnsupported => "Die Tabellen des Modus {0} können nicht angezeigt werden, nur HDMA-Tabellen, die eine Farbe ändern.",
+        Message::ScrollPreviewNeedsBig => "Die scrollende Vorschau funktioniert nur mit dem Big-Modus!",
+        Message::PatchNeedsHdmaTables => "Patches und uberASM-Dateien richten nur einfache HDMA-Tabellen ein: Keine großen Farbverläufe, IRQ-Tabellen, Animationen, Spotlights oder Hintergründe!",
+        Message::VerifyNeedsAsar => "Nur ASM-Dateien in der Syntax von asar können überprüft werden, keine Binärdateien oder andere Syntaxen!",
+        Message::AsarFailed => "asar ({0}) konnte nicht ausgeführt werden: {1}",
+        Message::AssemblyFailed => "Die Tabellen von {0} lassen sich nicht assemblieren:\n{1}",
+        Message::AssertsNeedAsar => "Asserts und Prints gibt es nur in ASM-Dateien in der Syntax von asar!",
+        Message::MacrosNeedPlainTables => "Makros funktionieren nur mit einfachen HDMA-Tabellen in ASM-Dateien in der Syntax von asar, nicht mit großen Farbverläufen oder anderen Formaten!",
+        Message::RoutinesNeedAsar => "Die Routinen (z. B. von Animationen, Fades, IRQs oder Patches) werden nur in der Syntax von asar geschrieben!",
+        Message::WriteModeNeedsPlainTables => "Eine andere Schreibweise ändert die Größe jeder Scanline, daher können die Kanäle nicht dafür eingerichtet werden (Patches, uberASM-Dateien, Spotlights, Hintergründe und Defines)!",
+        Message::WordOrderNeedsWords => "--big-endian und --swap-words funktionieren nur mit Tabellen, die als Words geschrieben werden (CG-RAM-Tabellen oder --write-mode words)!",
+        Message::TargetNeedsSnes => "Andere Konsolen bekommen nur eine einzige Tabelle als ASM-, Binär-, C- oder JSON-Datei, die Optionen für das SNES gelten für sie nicht!",
+        Message::InvalidCsvLine => "Zeile {0} von {1} ist ungültig: {2}",
+        Message::EmptyCsv => "{0} enthält keine Werte!",
+        Message::WordsNeedEvenRowSize => "Tabellen, die als Words geschrieben werden, brauchen eine Zeilengröße von 2 oder 4 Bytes!",
+        Message::ColumnChosen => "Die sauberste Spalte an der X-Position {0} wird ausgelesen.",
+        Message::ReadInputFailed => "Die Eingabe konnte nicht gelesen werden: {0}",
+        Message::ReadFileFailed => "{0} konnte nicht gelesen werden: {1}",
+        Message::CreateFileFailed => "{0} konnte nicht erstellt werden, {1}",
+        Message::WriteFileFailed => "{0} konnte nicht geschrieben werden, {1}",
+        Message::ClipboardAccessFailed => "Auf die Zwischenablage konnte nicht zugegriffen werden, {0}",
+        Message::ClipboardCopyFailed => "In die Zwischenablage konnte nicht kopiert werden, {0}",
+        Message::NoHeader => "{0} hat keinen Kopf, aus dem die Datei neu erstellt werden kann.",
+        Message::UnknownPreset => "Es gibt keine Voreinstellung namens {0}. Verfügbare Voreinstellungen: {1}",
+        Message::DownloadFailed => "{0} konnte nicht heruntergeladen werden: {1}",
+        Message::OpenImageFailed => "{0} konnte nicht geöffnet werden: {1}",
+    }
+}
+
+fn japanese(message: Message) -> &'static str {
+    match message {
+        Message::ErrorPrefix => "エラー",
+        Message::WarningPrefix => "警告",
+        Message::PromptImage => "グラデーションを取り出す画像を入力してください: ",
+        Message::PromptOutput => "ASMファイルの名前を入力してください: ",
+        Message::TableGenerated => "HDMAテーブルを生成しました！",
+        Message::TableCopied => "HDMAテーブルをクリップボードにコピーしました！",
+        Message::TablesUpToDate => "すべてのHDMAテーブルは最新です。",
+        Message::FilesOutdated => "次のファイルは最新ではありません: {0}",
+        Message::HeightTooSmall => "入力した出力の高さ {0} は {1} より小さいです。\n{1} 以上の高さをおすすめします。",
+        Message::HeightTooLarge => "入力した画像の高さ {0} は {1} より大きいです。\n代わりにビッググラデーション (big) をおすすめします。",
+        Message::InvalidValue => "{0}が無効です: {1}",
+        Message::ValueHeight => "高さ",
+        Message::ValueYPosition => "Y座標",
+        Message::ValueXPosition => "X座標",
+        Message::ValueCgramIndex => "CG-RAMインデックス",
+        Message::ValueSampleRadius => "サンプル半径",
+        Message::ValueDenoise => "ノイズ除去フィルター",
+        Message::ValueSmooth => "ぼかしの強さ",
+        Message::ValueDither => "ディザリング方式",
+        Message::ValueMaxLevels => "階調数",
+        Message::ValueMergeSpace => "色空間",
+        Message::ValueTolerance => "許容値",
+        Message::ValueResample => "リサンプリング方法",
+        Message::ValueMirror => "反転するスキャンライン",
+        Message::ValueRange => "範囲",
+        Message::ValueRangeFiller => "範囲間の行数",
+        Message::ValueStride => "サンプリング間隔",
+        Message::ValuePadTo => "埋めるスキャンライン数",
+        Message::ValueLoop => "ループのブレンド行数",
+        Message::ValueAlpha => "透明度の処理",
+        Message::ValueHoldColour => "保持する色",
+        Message::ValueToneMap => "トーンマッピング",
+        Message::ValueExposure => "露出",
+        Message::ValueFrame => "フレーム",
+        Message::ValueGamma => "ガンマ",
+        Message::ValueHueVariants => "色相バリエーション",
+        Message::ValueBlend => "ブレンド率",
+        Message::ValueSteps => "ステップ数",
+        Message::ValueFade => "フェードのステップ数",
+        Message::ValueMosaicLayers => "モザイクのレイヤー",
+        Message::ValueBrightness => "明るさ",
+        Message::ValueContrast => "コントラスト",
+        Message::ValueSaturation => "彩度",
+        Message::ValueLayer => "レイヤー",
+        Message::ValueAmplitude => "振幅",
+        Message::ValueWavelength => "波長",
+        Message::ValuePhase => "位相",
+        Message::ValueHorizon => "地平線",
+        Message::ValueScale => "拡大率",
+        Message::ValueAngle => "角度",
+        Message::ValueShape => "形",
+        Message::ValueExpression => "式",
+        Message::ValueRegister => "レジスタ",
+        Message::ValueOffset => "オフセット",
+        Message::ValueRowSize => "行のサイズ",
+        Message::ValueMathLayers => "カラー演算のレイヤー",
+        Message::ValueThreshold => "しきい値",
+        Message::ValueCgramColumns => "CG-RAMの列",
+        Message::ValueChannels => "色チャンネル",
+        Message::ValueSplitSize => "分割サイズ",
+        Message::ValueDriver => "ドライバー",
+        Message::ValueScrollRoutine => "スクロールルーチンのアドレス",
+        Message::ValueRamBuffer => "RAMバッファのアドレス",
+        Message::ValueSymbolBase => "シンボルの開始アドレス",
+        Message::ValueRegions => "地域",
+        Message::ValueScanlines => "走査線数",
+        Message::ValueLineOffset => "ラインオフセット",
+        Message::ValueSizeBudget => "サイズの上限",
+        Message::ValueWriteMode => "書き込み方式",
+        Message::ValueTarget => "対象のゲーム機",
+        Message::ValueReserveTop => "予約する走査線",
+        Message::ValueReserveColour => "予約部分の色",
+        Message::InvalidMode => "入力したモード {0} は無効です！",
+        Message::YOutsideImage => "入力したY座標は画像の外にあります！",
+        Message::YStartBelowEnd => "Yの開始位置はYの終了位置より上にしてください！",
+        Message::XOutsideImage => "入力したX座標は画像の外にあります！",
+        Message::XEndBeforeStart => "Xの終了位置はX座標より左にはできません！",
+        Message::XEndWithoutXPosition => "--x-endには数値のX座標が必要です。「full」と「auto」は自分で列を選びます！",
+        Message::MirrorOutsideTable => "反転するスキャンラインは1から出力の高さまでにしてください！",
+        Message::AllRowsTransparent => "取り出したすべての行が透明なので、グラデーションは黒になります。",
+        Message::GrayscaleConverted => "{0} はグレースケール画像なので、明るさを3つの色チャンネルすべてに使います。",
+        Message::IndexedConverted => "{0} はインデックスカラー画像なので、パレットの色を使います。",
+        Message::GrayscaleLevels => "{0} はグレースケール画像なので、灰色の明るさをそのままテーブルの段階にします。",
+        Message::IndexedSnapped => "{0} はインデックスカラー画像なので、取り出した色をパレットの一番近い色に合わせます（同じ近さなら前の色）。",
+        Message::FrameOutOfRange => "{0} には {1} フレームしかありません！",
+        Message::NoFrames => "{0} にはフレームがありません！",
+        Message::UnsupportedTiffPage => "{0} のページはサポートされていない色形式です: {1}",
+        Message::AnimationTooLong => "アニメーションが256フレームより長いため、フレームカウンターが一周すると途中で切れます。",
+        Message::SizeMismatch => "{0} は {1} と同じサイズではありません！",
+        Message::FadeNeedsBrightness => "フェードは明るさモードでのみ使用できます！",
+        Message::SpotlightNeedsFixedColour => "スポットライトはsingleモードとdoubleモードでのみ使用できます！",
+        Message::ColourMathNeedsFixedColour => "カラー演算の設定はsingleモード、doubleモード、bigモード、mathモードでのみ使用できます！",
+        Message::CgramColumnsNeedCgram => "複数のCG-RAMの列はcgramモードでのみ使用できます！",
+        Message::ChannelsNeedFixedColour => "色チャンネルの選択はsingleモードとdoubleモードでのみ使用できます！",
+        Message::ChannelsMerged => "{0} は{1}つの色チャンネルを同時に書き込むため、{2}バイトとHDMAチャンネル{3}個を節約します。",
+        Message::TableSplit => "{0} は{2}バイトを超えるため、{1}個のテーブルに分割しました。",
+        Message::IrqDriverMode => "IRQドライバーはwindowモードでは使用できず、cgramモードでは色インデックスが必要です！",
+        Message::ScrollRoutineNeedsBig => "スクロールルーチンはbigモードでのみ使用できます！",
+        Message::ScrollRoutineSplit => "{0} は分割されたため、スクロールルーチンはありません。代わりにcontinueルーチンを使用してください。",
+        Message::RamBufferMode => "RAMバッファはHDMAテーブルでのみ使用できます（ビッググラデーションやIRQドライバーでは使用できません）！",
+        Message::MathVariantsNeedFixedColour => "減算カラー演算のバリエーションはsingleモード、doubleモード、bigモードでのみ使用できます！",
+        Message::BackdropNeedsFixedColour => "バックドロップはsingleモードとdoubleモードでのみ使用できます！",
+        Message::ModeTwice => "モード {0} が2回入力されました！",
+        Message::FieldsNeedTables => "フィールドテーブルはビッググラデーション以外の単一モードでのみ使えます！",
+        Message::SegmentNeedsCa65 => "セグメントはca65の構文でのみ使えます！",
+        Message::PaletteTooLong => "グラデーションには{0}色ありますが、パレットには{1}色しか入らないため一部の色は省かれます。",
+        Message::UnknownPaletteFormat => "{0} は .pal ファイルでも .tpl ファイルでもありません！",
+        Message::PreviewUnsupported => "{0} モードのテーブルはプレビューできません。プレビューできるのは色を変えるHDMAテーブルだけです。",
+        Message::ScrollPreviewNeedsBig => "スクロールのプレビューはbigモードでのみ使用できます！",
+        Message::PatchNeedsHdmaTables => "パッチとuberASMファイルが設定するのは通常のHDMAテーブルだけです。ビッググラデーション、IRQテーブル、アニメーション、スポットライト、背景には使えません！",
+        Message::VerifyNeedsAsar => "検証できるのはasarの構文のASMファイルだけです。バイナリファイルや他の構文には使えません！",
+        Message::AsarFailed => "asar ({0}) を実行できませんでした: {1}",
+        Message::AssemblyFailed => "{0} のテーブルはアセンブルできません:\n{1}",
+        Message::AssertsNeedAsar => "assertとprintはasarの構文のASMファイルにしかありません！",
+        Message::MacrosNeedPlainTables => "マクロはasarの構文のASMファイルの通常のHDMAテーブルにしか使えません。ビッググラデーションや他の形式には使えません！",
+        Message::RoutinesNeedAsar => "ルーチン（アニメーション、フェード、IRQ、パッチなど）はasarの構文でしか書けません！",
+        Message::WriteModeNeedsPlainTables => "書き込み方式を変えると各スキャンラインのサイズが変わるため、チャンネルを設定できません（パッチ、uberASMファイル、スポットライト、背景、define）！",
+        Message::WordOrderNeedsWords => "--big-endianと--swap-wordsはワードで書かれるテーブル（CG-RAMテーブルまたは--write-mode words）にしか使えません！",
+        Message::TargetNeedsSnes => "他のゲーム機にはASM、バイナリ、C、JSONファイルのテーブルが一つだけ出力されます。SNES用のオプションは使えません！",
+        Message::InvalidCsvLine => "{1} の{0}行目が無効です: {2}",
+        Message::EmptyCsv => "{0} には値がありません！",
+        Message::WordsNeedEvenRowSize => "ワードで書き出すテーブルの行サイズは2か4バイトにしてください！",
+        Message::ColumnChosen => "最もきれいなX座標 {0} の列を取り出します。",
+        Message::ReadInputFailed => "入力を読み込めませんでした: {0}",
+        Message::ReadFileFailed => "{0} を読み込めませんでした: {1}",
+        Message::CreateFileFailed => "{0} を作成できませんでした: {1}",
+        Message::WriteFileFailed => "{0} に書き込めませんでした: {1}",
+        Message::ClipboardAccessFailed => "クリップボードにアクセスできませんでした: {0}",
+        Message::ClipboardCopyFailed => "クリップボードにコピーできませんでした: {0}",
+        Message::NoHeader => "{0} には再生成に使えるヘッダーがありません。",
+        Message::UnknownPreset => "{0} というプリセットはありません。使用できるプリセット: {1}",
+        Message::DownloadFailed => "{0} をダウンロードできませんでした: {1}",
+        Message::OpenImageFailed => "{0} を開けませんでした: {1}",
+    }
+}
+
+// Gets the message in the current language.
+pub fn text(message: Message) -> &'static str {
+    match language() {
+        Language::English => english(message),
+        Language::German => german(message),
+        Language::Japanese => japanese(message)
+    }
+}
+
+// Gets the message in the current language and fills in the placeholders.
+pub fn format(message: Message, args: &[&dyn Display]) -> String {
+    let mut output = String::from(text(message));
+
+    for (index, arg) in args.iter().enumerate() {
+        output = output.replace(&format!("{{{}}}", index), &arg.to_string());
+    }
+
+    return output;
+}
+
+// Shorthand for messages::format.
+macro_rules! tr {
+    ($message:expr) => {
+        $crate::messages::format($message, &[])
+    };
+    ($message:expr, $($arg:expr),+) => {
+        $crate::messages::format($message, &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
@@ -0,0 +1,50 @@
+// Palette files of the ripped colours so that they can be used in tile palettes as well.
+// The colours are SNES colours (as they're written to CGRAM) which are only converted back to 24-bit colours for
+// the formats which need them.
+use crate::messages::Message;
+
+// A full palette has got 16 rows of 16 colours each.
+pub const PALETTE_SIZE: usize = 256;
+
+// The gradient without the scanlines which repeat the colour before them.
+// Gradients with too many colours are thinned out evenly so that both ends stay in the palette.
+pub fn gradient_colours(colours: &[u16]) -> Vec<u16> {
+    let mut unique: Vec<u16> = Vec::new();
+    for colour in colours {
+        if unique.last() != Some(colour) {
+            unique.push(*colour);
+        }
+    }
+
+    if unique.len() <= PALETTE_SIZE {
+        return unique;
+    }
+
+    log::warn!("{}", tr!(Message::PaletteTooLong, unique.len(), PALETTE_SIZE));
+
+    return (0..PALETTE_SIZE).map(|index| unique[index * (unique.len() - 1) / (PALETTE_SIZE - 1)]).collect();
+}
+
+// The format of Lunar Magic (and YY-CHR): 256 colours with a byte each for red, green and blue.
+// The palette is filled up with black.
+pub fn write_pal(colours: &[u16]) -> Vec<u8> {
+    let mut output: Vec<u8> = colours.iter().flat_map(|colour| IntoIterator::into_iter(to_rgb(*colour))).collect();
+    output.resize(PALETTE_SIZE * 3, 0);
+    return output;
+}
+
+// Tile Layer Pro palettes start with "TPL" and the colour format (2 for SNES) followed by the colours as they're
+// written to CGRAM.
+pub fn write_tpl(colours: &[u16]) -> Vec<u8> {
+    let mut output = vec![b'T', b'P', b'L', 0x02];
+    output.extend(colours.iter().flat_map(|colour| IntoIterator::into_iter(colour.to_le_bytes())));
+    return output;
+}
+
+// Five bits per channel, the upper bits are repeated at the bottom so that white stays white.
+fn to_rgb(colour: u16) -> [u8; 3] {
+    return [0, 5, 10].map(|shift| {
+        let value = ((colour >> shift) & 0x1F) as u8;
+        (value << 3) | (value >> 2)
+    });
+}
@@ -0,0 +1,59 @@
+use crate::error::RipperError;
+use crate::messages::Message;
+
+use clap::ArgMatches;
+
+// A preset is just a list of options which are added to the command line.
+// Every entry is the long option, the name of the argument and the value.
+struct Preset {
+    name: &'static str,
+    options: &'static [(&'static str, &'static str, &'static str)]
+}
+
+const PRESETS: &[Preset] = &[
+    // A regular SMW background gradient: As large as the screen and in the optimised two channel mode.
+    // The gradient starts below the status bar.
+    Preset {
+        name: "smw-background",
+        options: &[
+            ("height", "height", "224"),
+            ("mode", "mode", "double"),
+            ("reserve-top", "reserve-top", "32")
+        ]
+    },
+    // A scrollable gradient for "Scrollable" HDMA Gradients, as tall as the image.
+    Preset {
+        name: "smw-big",
+        options: &[
+            ("mode", "mode", "big")
+        ]
+    }
+];
+
+pub fn preset_names() -> Vec<&'static str> {
+    return PRESETS.iter().map(|preset| preset.name).collect();
+}
+
+// Gets the options of a preset as command line arguments.
+// Options which have been entered in the command line (or before) are skipped so they can be changed.
+pub fn preset_args(preset_name: &str, matches: &ArgMatches, previous_args: &[String]) -> Result<Vec<String>, RipperError> {
+    let preset = match PRESETS.iter().find(|preset| preset.name == preset_name) {
+        Some(preset) => preset,
+        None => return Err(RipperError::BadArguments(tr!(Message::UnknownPreset,
+            preset_name, preset_names().join(", "))))
+    };
+
+    let mut args = Vec::new();
+
+    for (option, arg_name, value) in preset.options {
+        let long_option = format!("--{}", option);
+        let entered = previous_args.iter()
+            .any(|arg| arg == &long_option || arg.starts_with(&format!("{}=", long_option)));
+
+        if !matches.is_present(arg_name) && !entered {
+            args.push(format!("{}={}", long_option, value));
+        }
+    }
+
+    return Ok(args);
+}
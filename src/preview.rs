@@ -0,0 +1,126 @@
+// Images of what the SNES displays once the tables are in use.
+// They're rendered from the bytes of the finished tables so the optimisation and the five bits per channel are
+// already included.
+use crate::colour::{HdmaColourMode, TableStats};
+
+use image::{Delay, Frame, Rgb, RgbImage, Rgba, RgbaImage};
+
+// As wide as the screen.
+pub const PREVIEW_WIDTH: u32 = 256;
+
+// How far the screen moves in each frame of a scrolling preview and how long a frame is shown.
+// GIFs only count in hundredths of a second so this is about 30 frames per second.
+const SCROLL_STEP: u32 = 2;
+const FRAME_DELAY: u32 = 30;
+
+// Renders the backdrop as it looks with the tables, each scanline is a row of the image.
+// Only the modes which change a colour can be previewed.
+pub fn render_tables(mode: HdmaColourMode, stats: &[TableStats]) -> Option<RgbImage> {
+    let tables: Vec<Vec<&[u8]>> = stats.iter().map(|stats| stats.scanline_values()).collect();
+    let height = tables.iter().map(|values| values.len()).max().unwrap_or(0);
+    if height == 0 {
+        return None;
+    }
+
+    let colours: Vec<Rgb<u8>> = match mode {
+        HdmaColourMode::FixedClourThree | HdmaColourMode::FixedClourTwo | HdmaColourMode::BigGradient => {
+            // Every byte which is written to $2132 sets the channels of its upper three bits.
+            let mut fixed_colour = [0u8; 3];
+            (0..height).map(|scanline| {
+                for values in &tables {
+                    for value in values.get(scanline).copied().unwrap_or(&[]) {
+                        for channel in 0..3 {
+                            if value & (0x20 << channel) != 0 {
+                                fixed_colour[channel] = value & 0x1F;
+                            }
+                        }
+                    }
+                }
+                Rgb(fixed_colour.map(expand_level))
+            }).collect()
+        }
+        // The colour comes last in every scanline (after the colour index if there is one).
+        // Only the first table is previewed if there are several colours.
+        HdmaColourMode::CgRam => tables[0].iter().map(|values| match values {
+            [.., low, high] => from_cgram_colour(u16::from_le_bytes([*low, *high])),
+            _ => Rgb([0, 0, 0])
+        }).collect(),
+        // The brightness darkens a white screen.
+        HdmaColourMode::Brightness => tables[0].iter().map(|values| Rgb([values.first().map_or(0, |value| (value & 0x0F) * 17); 3])).collect(),
+        HdmaColourMode::Mosaic | HdmaColourMode::Window | HdmaColourMode::ColourMath => return None
+    };
+
+    return Some(RgbImage::from_fn(PREVIEW_WIDTH, colours.len() as u32, |_, y| colours[y as usize]));
+}
+
+// The sampled colours on the left and the colours of the table with five bits per channel on the right.
+// The sampled colours haven't been posterized or dithered yet so any banding on the right comes from the conversion.
+pub fn render_comparison(original: &[Rgb<u8>], colours: &[Rgb<u8>]) -> RgbImage {
+    return RgbImage::from_fn(PREVIEW_WIDTH, colours.len() as u32, |x, y| {
+        let colour = colours[y as usize];
+        if x < PREVIEW_WIDTH / 2 {original[y as usize]} else {Rgb(colour.0.map(|value| expand_level(value >> 3)))}
+    });
+}
+
+// Moves a screen with this many scanlines over a big gradient, first down to the bottom and then back up again
+// so that the animation loops.
+pub fn scroll_frames(image: &RgbImage, scanlines: u32) -> Vec<Frame> {
+    let scanlines = scanlines.min(image.height());
+    let positions: Vec<u32> = (0..=image.height() - scanlines).step_by(SCROLL_STEP as usize).collect();
+
+    return positions.iter().chain(positions.iter().rev().skip(1)).map(|position| {
+        let screen = RgbaImage::from_fn(image.width(), scanlines, |x, y| {
+            let Rgb([red, green, blue]) = *image.get_pixel(x, position + y);
+            Rgba([red, green, blue, 0xFF])
+        });
+        Frame::from_parts(screen, 0, 0, Delay::from_numer_denom_ms(FRAME_DELAY, 1))
+    }).collect();
+}
+
+// A CGRAM colour has got five bits per channel, red at the bottom.
+fn from_cgram_colour(colour: u16) -> Rgb<u8> {
+    return Rgb([0, 5, 10].map(|shift| expand_level(((colour >> shift) & 0x1F) as u8)));
+}
+
+// Five bits to eight bits, the upper bits are repeated at the bottom so that white stays white.
+fn expand_level(level: u8) -> u8 {
+    return (level << 3) | (level >> 2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colour::{self, tests::settings};
+
+    use image::ImageBuffer;
+    use indicatif::ProgressBar;
+
+    // A long flat region followed by a gradient which changes on every line, including the last one so that the
+    // optimisation doesn't shorten the table.
+    fn gradient_image(height: u32) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        return ImageBuffer::from_fn(1, height, |_, y| {
+            if y < 200 {
+                Rgba([0x40, 0x80, 0xC0, 0xFF])
+            }
+            else {
+                Rgba([((y - 200) * 8 % 256) as u8, 0x80, if y + 1 == height {0xFF} else {0x00}, 0xFF])
+            }
+        });
+    }
+
+    // The preview is read back from the tables so it has to show the same colours as the ones which were sampled.
+    #[test]
+    fn preview_matches_sampled_colours() {
+        for (height, mode) in [(224, HdmaColourMode::FixedClourThree), (224, HdmaColourMode::CgRam), (300, HdmaColourMode::BigGradient)] {
+            let settings = settings(height, mode);
+            let image = gradient_image(height);
+            let expected: Vec<Rgb<u8>> = colour::sampled_colours(&settings, &image).into_iter()
+                .map(|colour| Rgb(colour.0.map(|value| expand_level(value >> 3)))).collect();
+            let tables = colour::write_table(&settings, image, &ProgressBar::hidden());
+            let preview = render_tables(mode, &tables.stats).unwrap();
+
+            let rows: Vec<Rgb<u8>> = (0..preview.height()).map(|y| *preview.get_pixel(0, y)).collect();
+            assert_eq!(rows, expected);
+        }
+    }
+}
@@ -0,0 +1,363 @@
+use crate::colour::{self, HdmaColourMode};
+use crate::error::RipperError;
+use crate::messages::Message;
+
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::str::FromStr;
+use image::{load_from_memory, load_from_memory_with_format, guess_format, AnimationDecoder, ColorType, DynamicImage, ImageBuffer, ImageFormat, ImageResult, Rgb, Rgba, RgbaImage};
+use image::codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder};
+use tiff::decoder::{Decoder as TiffDecoder, DecodingResult};
+
+// What happens to transparent pixels.
+#[derive(Copy, Clone, PartialEq)]
+pub enum AlphaPolicy {
+    // The transparency is simply dropped (i.e. you get whatever colour the transparent pixels have got).
+    Ignore,
+    // Transparent pixels are put on top of a solid colour.
+    Matte(Rgb<u8>),
+    // Transparent pixels aren't sampled and completely transparent rows are left out.
+    Skip,
+    // Same as above but completely transparent rows repeat the previous colour.
+    Hold
+}
+
+// Written as ignore, matte=#RRGGBB, skip or hold.
+impl FromStr for AlphaPolicy {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.split_once('=') {
+            Some(("matte", colour)) => colour::parse_hex_colour(colour).map(AlphaPolicy::Matte).ok_or(()),
+            Some(_) => Err(()),
+            None => match text {
+                "ignore" => Ok(AlphaPolicy::Ignore),
+                "skip" => Ok(AlphaPolicy::Skip),
+                "hold" => Ok(AlphaPolicy::Hold),
+                _ => Err(())
+            }
+        }
+    }
+}
+
+// How images with more than eight bits per channel (16-bit PNGs and TIFFs or HDR images like EXR) are brought down to eight bits.
+// Either way, the colours keep their precision until then which is still a lot more than the five bits of the SNES.
+#[derive(Copy, Clone, PartialEq)]
+pub enum ToneMap {
+    // Everything brighter than white is simply white.
+    Clamp,
+    // Bright colours are compressed rather than cut off (x / (1 + x)).
+    Reinhard
+}
+
+impl FromStr for ToneMap {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "clamp" => Ok(ToneMap::Clamp),
+            "reinhard" => Ok(ToneMap::Reinhard),
+            _ => Err(())
+        }
+    }
+}
+
+impl ToneMap {
+    // The exposure is in stops, i.e. every stop doubles the brightness.
+    fn apply(&self, value: f32, exposure: f32) -> u8 {
+        let value = value.max(0.0) * exposure.exp2();
+        let mapped = match self {
+            ToneMap::Clamp => value.min(1.0),
+            ToneMap::Reinhard => value / (1.0 + value)
+        };
+        return (mapped * 255.0).round() as u8;
+    }
+}
+
+// What the colours of an image were before it was converted to RGB.
+#[derive(Clone, PartialEq)]
+pub enum SourceColours {
+    Rgb,
+    Grayscale,
+    // The palette of an indexed image, in the order it's stored in.
+    Indexed(Vec<Rgb<u8>>)
+}
+
+// Everything which determines how an image is loaded.
+pub struct LoadSettings {
+    pub alpha: AlphaPolicy,
+    pub tone_map: ToneMap,
+    pub exposure: f32,
+    // Only for animated images (and TIFFs with multiple pages), the first one if there is none.
+    pub frame: Option<usize>
+}
+
+impl Default for LoadSettings {
+    fn default() -> Self {
+        Self { alpha: AlphaPolicy::Ignore, tone_map: ToneMap::Clamp, exposure: 0.0, frame: None }
+    }
+}
+
+// Images can be either files or links (e.g. images shared on a forum).
+pub fn is_url(input_name: &str) -> bool {
+    return input_name.starts_with("http://") || input_name.starts_with("https://");
+}
+
+// The name of the image file, without any folders or query for links.
+pub fn file_name(input_name: &str) -> &str {
+    if is_url(input_name) {
+        let path = input_name.split(|c| c == '?' || c == '#').next().unwrap_or(input_name);
+        return path.rsplit('/').next().filter(|name| !name.is_empty()).unwrap_or("gradient");
+    }
+
+    return Path::new(input_name).file_name().and_then(|name| name.to_str()).unwrap_or(input_name);
+}
+
+// Gets the raw data of the image, downloading it first if it's a link.
+pub fn read_source(input_name: &str) -> Result<Vec<u8>, RipperError> {
+    if !is_url(input_name) {
+        return match std::fs::read(input_name) {
+            Err(why) => Err(RipperError::UnreadableImage(tr!(Message::ReadFileFailed, input_name, why))),
+            Ok(data) => Ok(data)
+        };
+    }
+
+    let response = match ureq::get(input_name).call() {
+        Err(why) => return Err(RipperError::UnreadableImage(tr!(Message::DownloadFailed, input_name, why))),
+        Ok(response) => response
+    };
+
+    let mut data = Vec::new();
+
+    match response.into_reader().read_to_end(&mut data) {
+        Err(why) => Err(RipperError::UnreadableImage(tr!(Message::DownloadFailed, input_name, why))),
+        Ok(_) => Ok(data)
+    }
+}
+
+// Flips the image along its diagonal, i.e. rows become columns and vice versa.
+pub fn transpose(image: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    return ImageBuffer::from_fn(image.height(), image.width(), |x, y| *image.get_pixel(y, x));
+}
+
+pub fn load_image(input_name: &str, settings: &LoadSettings) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, RipperError> {
+    return decode_image(input_name, &read_source(input_name)?, settings).map(|(image, _)| image);
+}
+
+// Same as above but for data which has already been read (see read_source). It also gets what the colours of the image
+// were before, e.g. for the palette of an indexed image.
+pub fn decode_image(input_name: &str, data: &[u8], settings: &LoadSettings) -> Result<(RgbaImage, SourceColours), RipperError> {
+    let format = image_format(input_name, data);
+
+    let image = match (settings.frame, format) {
+        (Some(frame), _) => load_frame(input_name, data, format, frame)?,
+        (None, Some(format)) => open_image(input_name, load_from_memory_with_format(data, format))?,
+        (None, None) => open_image(input_name, load_from_memory(data))?
+    };
+
+    let colours = source_colours(data, &image);
+
+    return Ok((to_rgba(image, settings), colours));
+}
+
+// Gets every frame of an animated image along with how long it's displayed (in milliseconds).
+// Still images simply have got a single frame.
+// Just like decode_image, it takes data which has already been read. The colours are those of the first frame.
+pub fn load_frames(input_name: &str, data: &[u8], settings: &LoadSettings)
+    -> Result<(Vec<(RgbaImage, u32)>, SourceColours), RipperError> {
+    let frames = decode_frames(input_name, data, image_format(input_name, data))?;
+
+    let colours = match frames.first() {
+        Some((image, _)) => source_colours(data, image),
+        None => SourceColours::Rgb
+    };
+
+    return Ok((frames.into_iter().map(|(image, delay)| (to_rgba(image, settings), delay)).collect(), colours));
+}
+
+// Files use their extension to determine the format whereas links have to guess it from the data.
+fn image_format(input_name: &str, data: &[u8]) -> Option<ImageFormat> {
+    match ImageFormat::from_path(input_name) {
+        Ok(format) if !is_url(input_name) => Some(format),
+        _ => guess_format(data).ok()
+    }
+}
+
+// The decoder already expands palettes which is why indexed images have to be detected by hand.
+fn source_colours(data: &[u8], image: &DynamicImage) -> SourceColours {
+    if let Some(palette) = png_palette(data) {
+        return SourceColours::Indexed(palette);
+    }
+    if matches!(image.color(), ColorType::L8 | ColorType::La8 | ColorType::L16 | ColorType::La16) {
+        return SourceColours::Grayscale;
+    }
+    return SourceColours::Rgb;
+}
+
+// Everything is ripped as RGB so let the user know if the image had to be converted and what happens with it in this mode.
+pub fn report_conversion(input_name: &str, colours: &SourceColours, mode: HdmaColourMode) {
+    let levels_only = matches!(mode, HdmaColourMode::Brightness | HdmaColourMode::Mosaic | HdmaColourMode::ColourMath);
+
+    match colours {
+        SourceColours::Rgb => {}
+        SourceColours::Grayscale if levels_only => log::info!("{}", tr!(Message::GrayscaleLevels, input_name)),
+        SourceColours::Grayscale => log::info!("{}", tr!(Message::GrayscaleConverted, input_name)),
+        SourceColours::Indexed(_) if mode == HdmaColourMode::CgRam => log::info!("{}", tr!(Message::IndexedSnapped, input_name)),
+        SourceColours::Indexed(_) => log::info!("{}", tr!(Message::IndexedConverted, input_name))
+    }
+}
+
+fn to_rgba(image: DynamicImage, settings: &LoadSettings) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    let (tone_map, exposure) = (settings.tone_map, settings.exposure);
+
+    let high_precision = matches!(image.color(),
+        ColorType::L16 | ColorType::La16 | ColorType::Rgb16 | ColorType::Rgba16 | ColorType::Rgb32F | ColorType::Rgba32F);
+
+    let image = if high_precision {
+        log::debug!("High precision image, tone mapped down to eight bits.");
+
+        let image = image.into_rgba32f();
+        ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+            let pixel = image.get_pixel(x, y).0;
+            Rgba([
+                tone_map.apply(pixel[0], exposure),
+                tone_map.apply(pixel[1], exposure),
+                tone_map.apply(pixel[2], exposure),
+                (pixel[3].clamp(0.0, 1.0) * 255.0).round() as u8
+            ])
+        })
+    }
+    else {
+        image.into_rgba8()
+    };
+
+    return apply_alpha(image, settings.alpha);
+}
+
+fn open_image<T>(input_name: &str, image: ImageResult<T>) -> Result<T, RipperError> {
+    match image {
+        Err(why) => Err(RipperError::UnreadableImage(tr!(Message::OpenImageFailed, input_name, why))),
+        Ok(x) => Ok(x)
+    }
+}
+
+// Gets a single frame of an animated image.
+fn load_frame(input_name: &str, data: &[u8], format: Option<ImageFormat>, frame: usize) -> Result<DynamicImage, RipperError> {
+    if format == Some(ImageFormat::Tiff) {
+        return load_tiff_page(input_name, data, frame);
+    }
+
+    let frames = decode_frames(input_name, data, format)?;
+    let frame_count = frames.len();
+    match frames.into_iter().nth(frame) {
+        Some((image, _)) => Ok(image),
+        None => Err(RipperError::OutOfRange(tr!(Message::FrameOutOfRange, input_name, frame_count)))
+    }
+}
+
+// Every frame is the whole image at that point (i.e. not just the part which has changed since the last frame).
+fn decode_frames(input_name: &str, data: &[u8], format: Option<ImageFormat>) -> Result<Vec<(DynamicImage, u32)>, RipperError> {
+    let still_image = |image| vec![(image, 0)];
+
+    return Ok(match format {
+        Some(ImageFormat::Gif) => open_image(input_name, GifDecoder::new(Cursor::new(data)).and_then(collect_frames))?,
+        Some(ImageFormat::WebP) => open_image(input_name, WebPDecoder::new(Cursor::new(data)).and_then(collect_frames))?,
+        Some(ImageFormat::Png) => {
+            let decoder = open_image(input_name, PngDecoder::new(Cursor::new(data)))?;
+            if decoder.is_apng() {
+                open_image(input_name, collect_frames(decoder.apng()))?
+            }
+            else {
+                still_image(open_image(input_name, load_from_memory_with_format(data, ImageFormat::Png))?)
+            }
+        }
+        Some(format) => still_image(open_image(input_name, load_from_memory_with_format(data, format))?),
+        None => still_image(open_image(input_name, load_from_memory(data))?)
+    });
+}
+
+fn collect_frames<'a, D: AnimationDecoder<'a>>(decoder: D) -> ImageResult<Vec<(DynamicImage, u32)>> {
+    return decoder.into_frames().collect_frames().map(|frames| frames.into_iter().map(|frame| {
+        let (numerator, denominator) = frame.delay().numer_denom_ms();
+        (DynamicImage::ImageRgba8(frame.into_buffer()), numerator / denominator.max(1))
+    }).collect());
+}
+
+// The image crate only ever reads the first page of a TIFF so the other pages are read with the TIFF crate directly.
+// Only eight and 16-bit grayscale and RGB images are supported which covers pretty much every TIFF you'll come across.
+fn load_tiff_page(input_name: &str, data: &[u8], page: usize) -> Result<DynamicImage, RipperError> {
+    let tiff_failed = |why: tiff::TiffError| RipperError::UnreadableImage(tr!(Message::OpenImageFailed, input_name, why));
+
+    let mut decoder = TiffDecoder::new(Cursor::new(data)).map_err(tiff_failed)?;
+    for page_count in 1..=page {
+        if !decoder.more_images() {
+            return Err(RipperError::OutOfRange(tr!(Message::FrameOutOfRange, input_name, page_count)));
+        }
+        decoder.next_image().map_err(tiff_failed)?;
+    }
+
+    let (width, height) = decoder.dimensions().map_err(tiff_failed)?;
+    let colour_type = decoder.colortype().map_err(tiff_failed)?;
+
+    let image = match (decoder.read_image().map_err(tiff_failed)?, colour_type) {
+        (DecodingResult::U8(data), tiff::ColorType::Gray(8)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLuma8),
+        (DecodingResult::U8(data), tiff::ColorType::GrayA(8)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLumaA8),
+        (DecodingResult::U8(data), tiff::ColorType::RGB(8)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgb8),
+        (DecodingResult::U8(data), tiff::ColorType::RGBA(8)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba8),
+        (DecodingResult::U16(data), tiff::ColorType::Gray(16)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLuma16),
+        (DecodingResult::U16(data), tiff::ColorType::GrayA(16)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLumaA16),
+        (DecodingResult::U16(data), tiff::ColorType::RGB(16)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgb16),
+        (DecodingResult::U16(data), tiff::ColorType::RGBA(16)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba16),
+        _ => None
+    };
+
+    match image {
+        Some(image) => Ok(image),
+        None => Err(RipperError::UnreadableImage(tr!(Message::UnsupportedTiffPage, input_name, format!("{:?}", colour_type))))
+    }
+}
+
+// The colour type is stored in the IHDR chunk which always comes right after the signature.
+// Only the palette chunk (PLTE) is read, every chunk is its length, its type, its data and a checksum.
+fn png_palette(data: &[u8]) -> Option<Vec<Rgb<u8>>> {
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    const COLOUR_TYPE_OFFSET: usize = 25;
+    const INDEXED_COLOUR: u8 = 3;
+
+    if !data.starts_with(PNG_SIGNATURE) || data.get(COLOUR_TYPE_OFFSET) != Some(&INDEXED_COLOUR) {
+        return None;
+    }
+
+    let mut offset = PNG_SIGNATURE.len();
+    while let Some(header) = data.get(offset..offset + 8) {
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let chunk = data.get(offset + 8..offset + 8 + length)?;
+        if &header[4..8] == b"PLTE" {
+            return Some(chunk.chunks_exact(3).map(|entry| Rgb([entry[0], entry[1], entry[2]])).collect());
+        }
+        offset += length + 12;
+    }
+
+    return None;
+}
+
+// Anything but skipping makes the image fully opaque.
+fn apply_alpha(mut image: ImageBuffer<Rgba<u8>, Vec<u8>>, alpha: AlphaPolicy) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    let matte = match alpha {
+        AlphaPolicy::Skip | AlphaPolicy::Hold => return image,
+        AlphaPolicy::Ignore => None,
+        AlphaPolicy::Matte(colour) => Some(colour)
+    };
+
+    for pixel in image.pixels_mut() {
+        if let Some(matte) = matte {
+            let alpha = pixel.0[3] as u32;
+            for channel in 0..3 {
+                pixel.0[channel] = ((pixel.0[channel] as u32 * alpha + matte.0[channel] as u32 * (255 - alpha) + 127) / 255) as u8;
+            }
+        }
+        pixel.0[3] = 0xFF;
+    }
+
+    return image;
+}
@@ -0,0 +1,153 @@
+// Other consoles which can show a backdrop gradient by changing a colour after every line.
+// They share the sampling with the SNES, only the colour format and the code around the table differ.
+// There is no optimisation since the colour is written on every line anyway.
+use crate::colour::{HdmaColourMode, TableStats};
+
+use image::Rgb;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Target {
+    Snes,
+    Gba,
+    MegaDrive
+}
+
+impl FromStr for Target {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "snes" => Ok(Target::Snes),
+            "gba" => Ok(Target::Gba),
+            "md" => Ok(Target::MegaDrive),
+            _ => Err(())
+        }
+    }
+}
+
+impl Target {
+    // How many lines the screen has got. The SNES has got more than one option which is why it's handled separately.
+    pub fn scanlines(self: &Self) -> Option<u32> {
+        match self {
+            Target::Snes => None,
+            Target::Gba => Some(160),
+            Target::MegaDrive => Some(224)
+        }
+    }
+
+    // The tables of the SNES are bytes, the colours of the other consoles are written as a whole.
+    pub fn c_type(self: &Self) -> &'static str {
+        match self {
+            Target::Snes => "unsigned char",
+            Target::Gba | Target::MegaDrive => "unsigned short"
+        }
+    }
+
+    // How the values of a table are read back from its bytes, e.g. for C arrays.
+    pub fn values(self: &Self, data: &[u8]) -> Vec<u16> {
+        match self {
+            Target::Snes => data.iter().map(|value| *value as u16).collect(),
+            Target::Gba => data.chunks(2).map(|value| u16::from_le_bytes([value[0], *value.get(1).unwrap_or(&0)])).collect(),
+            // The 68000 is big endian.
+            Target::MegaDrive => data.chunks(2).map(|value| u16::from_be_bytes([value[0], *value.get(1).unwrap_or(&0)])).collect()
+        }
+    }
+
+    // The header is written with the comment character of the assembler.
+    pub fn comments(self: &Self, text: &str) -> String {
+        match self {
+            Target::Snes | Target::MegaDrive => String::from(text),
+            Target::Gba => text.lines().map(|line| format!("{}\n", line.replacen(';', "@", 1))).collect()
+        }
+    }
+}
+
+// The GBA uses the same colour format as CGRAM (BGR555) so the colours can be used as they are.
+// HBlank DMA copies one colour to the backdrop (the first colour of the palette) after every line. Since the first
+// line is drawn before the first HBlank, its colour is written during VBlank and DMA starts with the second colour.
+// There's an HBlank after the last line as well which is why the last colour is repeated at the end, just like on the
+// Mega Drive. The table is in ROM which only DMA 3 can read.
+pub fn gba_table(label: &str, colours: &[u16]) -> (String, TableStats) {
+    let mut colours = colours.to_vec();
+    if let Some(last) = colours.last().copied() {
+        colours.push(last);
+    }
+
+    let mut output = String::from("@ Write the first colour to 0x05000000 and (re)start DMA 3 in every VBlank:\n");
+    output.push_str(&format!("@ REG_DMA3SAD = (u32)&{}[1];\n", label));
+    output.push_str("@ REG_DMA3DAD = 0x05000000;\n");
+    output.push_str("@ REG_DMA3CNT = 0xA2600001; (enabled, HBlank, repeat, reload the destination, one halfword)\n");
+    output.push_str(&format!("\n    .section .rodata\n    .align 1\n    .global {0}\n{0}:\n", label));
+
+    for line in colours.chunks(16) {
+        let values: Vec<String> = line.iter().map(|colour| format!("0x{:04X}", colour)).collect();
+        output.push_str(&format!("    .hword {}\n", values.join(",")));
+    }
+
+    // It's no HDMA table but it's still a palette colour.
+    let data: Vec<u8> = colours.iter().flat_map(|colour| IntoIterator::into_iter(colour.to_le_bytes())).collect();
+    let stats = TableStats {
+        name: String::from(label),
+        row_count: colours.len(),
+        byte_size: data.len(),
+        unoptimised_size: data.len(),
+        data: data,
+        scanline_size: None,
+        mode: HdmaColourMode::CgRam
+    };
+
+    return (output, stats);
+}
+
+// Mega Drive colours have got three bits per channel: Blue, green and red from the top with a zero bit below each.
+// An H-Int on every line writes the next colour to the backdrop (the first colour of CRAM). Just like on the GBA, the
+// first line is drawn before the first H-Int so its colour is written during VBlank.
+// The last H-Int happens after the last line which is why the last colour is repeated at the end.
+pub fn md_table(label: &str, colours: &[Rgb<u8>]) -> (String, TableStats) {
+    let mut colours: Vec<u16> = colours.iter().map(|colour| {
+        let [red, green, blue] = colour.0.map(|value| (value >> 5) as u16);
+        (blue << 9) | (green << 5) | (red << 1)
+    }).collect();
+    if let Some(last) = colours.last().copied() {
+        colours.push(last);
+    }
+
+    let mut output = String::from("; Call gradient_init once the VDP has been set up (it sets VDP register 0 to $14), gradient_vblank\n");
+    output.push_str("; in every VBlank and use gradient_hint as the H-Int handler (vector $70).\n");
+    output.push_str("gradient_pointer equ $FFFF00 ; Four bytes of free RAM\n");
+
+    output.push_str("\ngradient_init:\n");
+    output.push_str("    move.w #$8A00,($C00004).l ; H-Int after every line\n");
+    output.push_str("    move.w #$8014,($C00004).l ; Enable H-Int\n");
+    output.push_str("    move.w #$2300,sr\n    rts\n");
+
+    output.push_str("\ngradient_vblank:\n");
+    output.push_str("    move.l #$C0000000,($C00004).l ; Write to CRAM colour 0\n");
+    output.push_str(&format!("    move.w ({}).l,($C00000).l\n", label));
+    output.push_str(&format!("    move.l #{}+2,(gradient_pointer).l\n    rts\n", label));
+
+    output.push_str("\ngradient_hint:\n");
+    output.push_str("    move.l a0,-(sp)\n    movea.l (gradient_pointer).l,a0\n");
+    output.push_str("    move.l #$C0000000,($C00004).l\n    move.w (a0)+,($C00000).l\n");
+    output.push_str("    move.l a0,(gradient_pointer).l\n    movea.l (sp)+,a0\n    rte\n");
+
+    output.push_str(&format!("\n    even\n{}:\n", label));
+    for line in colours.chunks(16) {
+        let values: Vec<String> = line.iter().map(|colour| format!("${:04X}", colour)).collect();
+        output.push_str(&format!("    dc.w {}\n", values.join(",")));
+    }
+
+    let data: Vec<u8> = colours.iter().flat_map(|colour| IntoIterator::into_iter(colour.to_be_bytes())).collect();
+    let stats = TableStats {
+        name: String::from(label),
+        row_count: colours.len(),
+        byte_size: data.len(),
+        unoptimised_size: data.len(),
+        data: data,
+        scanline_size: None,
+        mode: HdmaColourMode::CgRam
+    };
+
+    return (output, stats);
+}
@@ -0,0 +1,50 @@
+// Runs the tables through asar to make sure that they actually assemble.
+// asar patches a scratch ROM in a folder of its own which is removed afterwards, nothing of the output is touched.
+use std::path::Path;
+use std::process::Command;
+
+// Large enough for freecode and freedata.
+const SCRATCH_ROM_SIZE: usize = 0x80000;
+
+// The file to verify is included from a wrapper which puts it into the ROM. Patches and uberASM files place their
+// code themselves but uberASM files rely on the !addr define of the uberASM tool.
+const WRAPPER: &str = "lorom\n!addr = $0000\norg $008000\nincsrc \"gradient.asm\"\n";
+
+pub enum AsarError {
+    // asar couldn't be run at all (or the scratch files couldn't be written).
+    NotRun(String),
+    // The errors which asar reported.
+    Errors(String)
+}
+
+pub fn assemble(text: &str, asar: &str) -> Result<(), AsarError> {
+    let scratch_dir = std::env::temp_dir().join(format!("gradient_ripper_{}", std::process::id()));
+    let result = assemble_in(text, asar, &scratch_dir);
+    let _ = std::fs::remove_dir_all(&scratch_dir);
+    return result;
+}
+
+fn assemble_in(text: &str, asar: &str, scratch_dir: &Path) -> Result<(), AsarError> {
+    let write = |name: &str, data: &[u8]| std::fs::write(scratch_dir.join(name), data).map_err(|why| AsarError::NotRun(why.to_string()));
+    std::fs::create_dir_all(scratch_dir).map_err(|why| AsarError::NotRun(why.to_string()))?;
+    write("gradient.asm", text.as_bytes())?;
+    write("wrapper.asm", WRAPPER.as_bytes())?;
+    write("scratch.sfc", &vec![0u8; SCRATCH_ROM_SIZE])?;
+
+    let output = Command::new(asar)
+        .arg("--no-title-check")
+        .arg("wrapper.asm")
+        .arg("scratch.sfc")
+        .current_dir(scratch_dir)
+        .output()
+        .map_err(|why| AsarError::NotRun(why.to_string()))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    // asar writes its errors to stderr but older versions use stdout.
+    let mut errors = String::from_utf8_lossy(&output.stderr).into_owned();
+    errors.push_str(&String::from_utf8_lossy(&output.stdout));
+    return Err(AsarError::Errors(errors.trim().to_string()));
+}
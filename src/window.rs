@@ -0,0 +1,41 @@
+// Window tables are made from a mask rather than a column of colours.
+use crate::colour::RipSettings;
+use crate::hdma::{HdmaTable, HdmaRow, HdmaWriteMode};
+
+use image::{ImageBuffer, Luma, Rgba, Pixel};
+
+// Pixels at least this bright are inside the window.
+const MASK_THRESHOLD: u8 = 0x80;
+
+// Writes the left ($2126) and right ($2127) window positions of each scanline.
+// Each scanline uses the leftmost and rightmost pixel of the mask in its row, i.e. holes in the mask are filled up.
+// Rows without any such pixel get a window which is empty (the left position is larger than the right one).
+// The rows from Y_START to Y_END are stretched to the height just like the colours.
+pub fn create_window_table(image: &ImageBuffer<Rgba<u8>, Vec<u8>>, settings: &RipSettings) -> HdmaTable {
+    let mut output = HdmaTable::new_real_table(Vec::new(), 2, HdmaWriteMode::Bytes, "window_table");
+    let input_height = (settings.y_end - settings.y_start) as u64;
+
+    for scanline in 0..settings.height as u64 {
+        let y = settings.y_start + (scanline * input_height / settings.height as u64) as u32;
+        let (left, right) = row_edges(image, y).unwrap_or((0xFF, 0x00));
+
+        output.push(HdmaRow::new_scanline(&[left, right]));
+    }
+
+    return output;
+}
+
+// The leftmost and rightmost pixel of the mask in a row. The SNES screen is only 256 pixels wide so anything beyond
+// that is cut off.
+fn row_edges(image: &ImageBuffer<Rgba<u8>, Vec<u8>>, y: u32) -> Option<(u8, u8)> {
+    let width = image.width().min(0x100);
+    let inside = |x: &u32| {
+        let Luma([brightness]) = image.get_pixel(*x, y).to_luma();
+        brightness >= MASK_THRESHOLD
+    };
+
+    let left = (0..width).find(inside)?;
+    let right = (0..width).rev().find(inside)?;
+
+    return Some((left as u8, right as u8));
+}